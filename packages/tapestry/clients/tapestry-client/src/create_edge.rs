@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+use tapestry::state::{
+    AddressSeed, AnchorCompressedProof, EdgeArgs, EdgeCounters, NodeV2, ProvenNode, TokenGate,
+};
+
+use crate::{
+    derive_new_address, new_address_remaining_accounts, new_address_with_input_remaining_accounts,
+    pack_input_meta, LightSystemAccounts,
+};
+
+pub struct CreateEdgeBuilder {
+    payer: Pubkey,
+    owner: Pubkey,
+    update_authority: Option<Pubkey>,
+    address_seed: AddressSeed,
+    edge_args: EdgeArgs,
+    source: Option<ProvenNode>,
+    source_node_account: Option<Pubkey>,
+    target: Option<ProvenNode>,
+    target_node_account: Option<(Pubkey, TokenGate)>,
+    gate_token_account: Option<Pubkey>,
+    gate_metadata: Option<Pubkey>,
+    counters: Option<EdgeCounters>,
+}
+
+impl CreateEdgeBuilder {
+    pub fn new(
+        payer: Pubkey,
+        owner: Pubkey,
+        address_seed: AddressSeed,
+        edge_args: EdgeArgs,
+    ) -> Self {
+        Self {
+            payer,
+            owner,
+            update_authority: None,
+            address_seed,
+            edge_args,
+            source: None,
+            source_node_account: None,
+            target: None,
+            target_node_account: None,
+            gate_token_account: None,
+            gate_metadata: None,
+            counters: None,
+        }
+    }
+
+    pub fn update_authority(mut self, update_authority: Pubkey) -> Self {
+        self.update_authority = Some(update_authority);
+        self
+    }
+
+    // Proves the source node, which counted edges need. The owner then has to
+    // sign and the validity proof has to cover the source node as well.
+    pub fn source(mut self, source: ProvenNode) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    // Reads the source node from its node account while it is decompressed,
+    // instead of proving it
+    pub fn decompressed_source(mut self, node_account: Pubkey) -> Self {
+        self.source_node_account = Some(node_account);
+        self
+    }
+
+    // Proves the target node so its token gate is checked and recorded in the
+    // edge. The validity proof has to cover the target node as well.
+    pub fn target(mut self, target: ProvenNode) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    // Reads the target node from its node account while it is decompressed,
+    // instead of proving it. Takes the node's gate to know whether the owner
+    // has to sign.
+    pub fn decompressed_target(mut self, node_account: Pubkey, node: &NodeV2) -> Self {
+        self.target_node_account = Some((node_account, node.token_gate));
+        self
+    }
+
+    // Holding that satisfies the target node's token gate, the owner then has
+    // to sign
+    pub fn gate_accounts(
+        mut self,
+        gate_token_account: Pubkey,
+        gate_metadata: Option<Pubkey>,
+    ) -> Self {
+        self.gate_token_account = Some(gate_token_account);
+        self.gate_metadata = gate_metadata;
+        self
+    }
+
+    // Counts the edge on the source and target node counters, which needs the
+    // source node as well. The validity proof has to cover both counters.
+    pub fn counters(mut self, counters: EdgeCounters) -> Self {
+        self.counters = Some(counters);
+        self
+    }
+
+    // Seed the program derives from the address seed variant and edge args
+    pub fn seed(&self) -> Result<[u8; 32]> {
+        self.address_seed.edge_seed(&self.edge_args)
+    }
+
+    // Address of the edge, to request a non-inclusion proof for
+    pub fn address(&self) -> Result<[u8; 32]> {
+        Ok(derive_new_address(&self.seed()?))
+    }
+
+    pub fn instruction(
+        mut self,
+        proof: AnchorCompressedProof,
+        address_merkle_tree_root_index: u16,
+    ) -> Instruction {
+        let light_system_accounts = LightSystemAccounts::default();
+        let mut accounts = tapestry::accounts::CreateEdge {
+            payer: self.payer,
+            update_authority: self.update_authority,
+            owner: self.owner,
+            gate_token_account: self.gate_token_account,
+            gate_metadata: self.gate_metadata,
+            source_node_account: self.source_node_account,
+            target_node_account: self
+                .target_node_account
+                .map(|(node_account, _)| node_account),
+            cpi_authority_pda: light_system_accounts.cpi_authority_pda,
+            self_program: tapestry::ID,
+            light_system_program: light_system_accounts.light_system_program,
+            registered_program_pda: light_system_accounts.registered_program_pda,
+            noop_program: light_system_accounts.noop_program,
+            account_compression_authority: light_system_accounts.account_compression_authority,
+            account_compression_program: light_system_accounts.account_compression_program,
+            system_program: light_system_accounts.system_program,
+        }
+        .to_account_metas(None);
+        let token_gate = match (&self.target, &self.target_node_account) {
+            (Some(target), _) => target.latest().token_gate,
+            (None, Some((_, token_gate))) => *token_gate,
+            (None, None) => TokenGate::None,
+        };
+        let gated = token_gate != TokenGate::None;
+        let has_source = self.source.is_some() || self.source_node_account.is_some();
+        if gated || has_source {
+            for account in accounts.iter_mut().filter(|a| a.pubkey == self.owner) {
+                account.is_signer = true;
+            }
+        }
+        if self.source.is_some() || self.target.is_some() || self.counters.is_some() {
+            let remaining_accounts = new_address_with_input_remaining_accounts();
+            if let Some(source) = &mut self.source {
+                pack_input_meta(&mut source.node_meta, &remaining_accounts);
+            }
+            if let Some(target) = &mut self.target {
+                pack_input_meta(&mut target.node_meta, &remaining_accounts);
+            }
+            if let Some(counters) = &mut self.counters {
+                pack_input_meta(&mut counters.source_meta, &remaining_accounts);
+                pack_input_meta(&mut counters.target_meta, &remaining_accounts);
+            }
+            accounts.extend(remaining_accounts);
+        } else {
+            accounts.extend(new_address_remaining_accounts());
+        }
+
+        Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateEdge {
+                proof,
+                address_merkle_tree_root_index,
+                address_seed: self.address_seed,
+                edge_args: self.edge_args,
+                source: self.source,
+                target: self.target,
+                counters: self.counters,
+            }
+            .data(),
+        }
+    }
+}
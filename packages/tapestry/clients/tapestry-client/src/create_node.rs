@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+use tapestry::state::{AddressSeed, AnchorCompressedProof, NodeArgs};
+
+use crate::{derive_new_address, new_address_remaining_accounts, LightSystemAccounts};
+
+pub struct CreateNodeBuilder {
+    payer: Pubkey,
+    owner: Pubkey,
+    update_authority: Option<Pubkey>,
+    address_seed: AddressSeed,
+    node_args: NodeArgs,
+}
+
+impl CreateNodeBuilder {
+    pub fn new(
+        payer: Pubkey,
+        owner: Pubkey,
+        address_seed: AddressSeed,
+        node_args: NodeArgs,
+    ) -> Self {
+        Self {
+            payer,
+            owner,
+            update_authority: None,
+            address_seed,
+            node_args,
+        }
+    }
+
+    pub fn update_authority(mut self, update_authority: Pubkey) -> Self {
+        self.update_authority = Some(update_authority);
+        self
+    }
+
+    // Seed the program derives from the address seed variant and node args
+    pub fn seed(&self) -> Result<[u8; 32]> {
+        self.address_seed.node_seed(&self.node_args)
+    }
+
+    // Address of the node, to request a non-inclusion proof for
+    pub fn address(&self) -> Result<[u8; 32]> {
+        Ok(derive_new_address(&self.seed()?))
+    }
+
+    pub fn instruction(
+        self,
+        proof: AnchorCompressedProof,
+        address_merkle_tree_root_index: u16,
+    ) -> Instruction {
+        let light_system_accounts = LightSystemAccounts::default();
+        let mut accounts = tapestry::accounts::CreateNode {
+            payer: self.payer,
+            update_authority: self.update_authority,
+            owner: self.owner,
+            cpi_authority_pda: light_system_accounts.cpi_authority_pda,
+            self_program: tapestry::ID,
+            light_system_program: light_system_accounts.light_system_program,
+            registered_program_pda: light_system_accounts.registered_program_pda,
+            noop_program: light_system_accounts.noop_program,
+            account_compression_authority: light_system_accounts.account_compression_authority,
+            account_compression_program: light_system_accounts.account_compression_program,
+            system_program: light_system_accounts.system_program,
+        }
+        .to_account_metas(None);
+        accounts.extend(new_address_remaining_accounts());
+
+        Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateNode {
+                proof,
+                address_merkle_tree_root_index,
+                address_seed: self.address_seed,
+                node_args: self.node_args,
+            }
+            .data(),
+        }
+    }
+}
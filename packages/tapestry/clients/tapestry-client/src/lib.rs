@@ -0,0 +1,18 @@
+//! Instruction builders for the Tapestry program.
+//!
+//! Each builder derives the address of the account it creates, so the caller
+//! can fetch a validity proof for it before building the instruction:
+//!
+//! ```ignore
+//! let builder = CreateNodeBuilder::new(payer, owner, AddressSeed::Label, node_args);
+//! let proof = rpc.get_validity_proof(builder.address()?).await?;
+//! let ix = builder.instruction(proof, address_merkle_tree_root_index);
+//! ```
+
+pub use create_edge::*;
+pub use create_node::*;
+pub use light_system_accounts::*;
+
+mod create_edge;
+mod create_node;
+mod light_system_accounts;
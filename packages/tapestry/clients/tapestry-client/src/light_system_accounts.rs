@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+use light_sdk::address::derive_address;
+use light_sdk::merkle_context::AddressMerkleContext;
+use tapestry::constants::CPI_AUTHORITY_SEED;
+use tapestry::state::AnchorCompressedAccountMeta;
+use tapestry::utils::{
+    ADDRESS_MERKLE_TREE_PUBKEY, ADDRESS_QUEUE_PUBKEY, MERKLE_TREE_PUBKEY, NULLIFIER_QUEUE_PUBKEY,
+};
+
+pub const LIGHT_SYSTEM_PROGRAM_ID: Pubkey = pubkey!("SySTEM1eSU2p4BGQfQpimFEWWSC1XDFeun3Nqzz3rT7");
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey =
+    pubkey!("compr6CUsB5m2jS4Y3831ztGSTnDpnKJTKS95d64XVq");
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+// Accounts required by every instruction that invokes the light system program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LightSystemAccounts {
+    pub cpi_authority_pda: Pubkey,
+    pub light_system_program: Pubkey,
+    pub registered_program_pda: Pubkey,
+    pub noop_program: Pubkey,
+    pub account_compression_authority: Pubkey,
+    pub account_compression_program: Pubkey,
+    pub system_program: Pubkey,
+}
+
+impl Default for LightSystemAccounts {
+    fn default() -> Self {
+        Self {
+            cpi_authority_pda: Pubkey::find_program_address(
+                &[CPI_AUTHORITY_SEED.as_bytes()],
+                &tapestry::ID,
+            )
+            .0,
+            light_system_program: LIGHT_SYSTEM_PROGRAM_ID,
+            registered_program_pda: Pubkey::find_program_address(
+                &[LIGHT_SYSTEM_PROGRAM_ID.as_ref()],
+                &ACCOUNT_COMPRESSION_PROGRAM_ID,
+            )
+            .0,
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: Pubkey::find_program_address(
+                &[CPI_AUTHORITY_SEED.as_bytes()],
+                &LIGHT_SYSTEM_PROGRAM_ID,
+            )
+            .0,
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: anchor_lang::system_program::ID,
+        }
+    }
+}
+
+// Derives the address of a new compressed account from its seed
+pub fn derive_new_address(address_seed: &[u8; 32]) -> [u8; 32] {
+    derive_address(
+        address_seed,
+        &AddressMerkleContext {
+            address_merkle_tree_pubkey: ADDRESS_MERKLE_TREE_PUBKEY,
+            address_queue_pubkey: ADDRESS_QUEUE_PUBKEY,
+        },
+    )
+}
+
+// Remaining accounts in the order `validate_merkle_trees(0, Some(1), Some(2), None, ..)` expects
+pub fn new_address_remaining_accounts() -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(MERKLE_TREE_PUBKEY, false),
+        AccountMeta::new(ADDRESS_MERKLE_TREE_PUBKEY, false),
+        AccountMeta::new(ADDRESS_QUEUE_PUBKEY, false),
+    ]
+}
+
+// Same as `new_address_remaining_accounts`, followed by the nullifier queue for
+// instructions that also prove an existing account
+pub fn new_address_with_input_remaining_accounts() -> Vec<AccountMeta> {
+    let mut remaining_accounts = new_address_remaining_accounts();
+    remaining_accounts.push(AccountMeta::new(NULLIFIER_QUEUE_PUBKEY, false));
+    remaining_accounts
+}
+
+// Points an input account at the state tree and nullifier queue in
+// `remaining_accounts`, so its indices always match the accounts the builder
+// appends
+pub fn pack_input_meta(meta: &mut AnchorCompressedAccountMeta, remaining_accounts: &[AccountMeta]) {
+    let index = |pubkey: &Pubkey| {
+        remaining_accounts
+            .iter()
+            .position(|account| account.pubkey == *pubkey)
+            .expect("remaining accounts include the state tree and nullifier queue") as u8
+    };
+    meta.merkle_tree_pubkey_index = index(&MERKLE_TREE_PUBKEY);
+    meta.nullifier_queue_pubkey_index = index(&NULLIFIER_QUEUE_PUBKEY);
+}
@@ -0,0 +1,424 @@
+#[path = "../../../programs/tapestry/tests/common/mod.rs"]
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::{edge_args, node_args};
+use tapestry::state::{
+    AccountKey, AddressSeed, AnchorCompressedAccountMeta, AnchorCompressedProof, EdgeCounters,
+    NodeArgs, NodeCounterV1, NodeUpdateAuthority, NodeV2, ProvenNode, TokenGate, VersionedNode,
+};
+use tapestry::utils::{
+    get_account_seed, get_label_seed, ADDRESS_MERKLE_TREE_PUBKEY, ADDRESS_QUEUE_PUBKEY,
+    MERKLE_TREE_PUBKEY, NULLIFIER_QUEUE_PUBKEY,
+};
+use tapestry_client::{
+    derive_new_address, CreateEdgeBuilder, CreateNodeBuilder, LightSystemAccounts,
+};
+
+fn proof() -> AnchorCompressedProof {
+    AnchorCompressedProof {
+        a: [1; 32],
+        b: [2; 64],
+        c: [3; 32],
+    }
+}
+
+fn proven_node(token_gate: TokenGate) -> ProvenNode {
+    let node = NodeV2::new(
+        NodeArgs {
+            token_gate,
+            ..node_args("alice")
+        },
+        Pubkey::new_unique(),
+        NodeUpdateAuthority::None,
+    );
+    ProvenNode {
+        node: VersionedNode::V2(node),
+        node_meta: AnchorCompressedAccountMeta {
+            address: Pubkey::new_unique().to_bytes(),
+            ..stale_meta()
+        },
+    }
+}
+
+// Meta with tree indices from another transaction's accounts, which the
+// builder has to overwrite
+fn stale_meta() -> AnchorCompressedAccountMeta {
+    AnchorCompressedAccountMeta {
+        merkle_tree_pubkey_index: 7,
+        nullifier_queue_pubkey_index: 8,
+        ..Default::default()
+    }
+}
+
+// Positions of the state tree and nullifier queue in `remaining_accounts(true)`
+fn packed(meta: AnchorCompressedAccountMeta) -> AnchorCompressedAccountMeta {
+    AnchorCompressedAccountMeta {
+        merkle_tree_pubkey_index: 0,
+        nullifier_queue_pubkey_index: 3,
+        ..meta
+    }
+}
+
+fn packed_node(node: ProvenNode) -> ProvenNode {
+    ProvenNode {
+        node_meta: packed(node.node_meta),
+        ..node
+    }
+}
+
+fn counter(node: &str) -> NodeCounterV1 {
+    NodeCounterV1 {
+        key: AccountKey::NodeCounterV1,
+        node: node.to_string(),
+        edge_type: "follow".to_string(),
+        counted_in_edges: 0,
+        counted_out_edges: 0,
+    }
+}
+
+fn counters() -> EdgeCounters {
+    EdgeCounters {
+        source: counter("alice"),
+        source_meta: stale_meta(),
+        target: counter("bob"),
+        target_meta: stale_meta(),
+    }
+}
+
+fn packed_counters() -> EdgeCounters {
+    let counters = counters();
+    EdgeCounters {
+        source_meta: packed(counters.source_meta),
+        target_meta: packed(counters.target_meta),
+        ..counters
+    }
+}
+
+fn create_node_accounts(
+    payer: Pubkey,
+    owner: Pubkey,
+    update_authority: Option<Pubkey>,
+) -> Vec<AccountMeta> {
+    let light_system_accounts = LightSystemAccounts::default();
+    tapestry::accounts::CreateNode {
+        payer,
+        update_authority,
+        owner,
+        cpi_authority_pda: light_system_accounts.cpi_authority_pda,
+        self_program: tapestry::ID,
+        light_system_program: light_system_accounts.light_system_program,
+        registered_program_pda: light_system_accounts.registered_program_pda,
+        noop_program: light_system_accounts.noop_program,
+        account_compression_authority: light_system_accounts.account_compression_authority,
+        account_compression_program: light_system_accounts.account_compression_program,
+        system_program: light_system_accounts.system_program,
+    }
+    .to_account_metas(None)
+}
+
+fn create_edge_accounts(
+    payer: Pubkey,
+    owner: Pubkey,
+    gate_token_account: Option<Pubkey>,
+    gate_metadata: Option<Pubkey>,
+    target_node_account: Option<Pubkey>,
+) -> Vec<AccountMeta> {
+    let light_system_accounts = LightSystemAccounts::default();
+    tapestry::accounts::CreateEdge {
+        payer,
+        update_authority: None,
+        owner,
+        gate_token_account,
+        gate_metadata,
+        source_node_account: None,
+        target_node_account,
+        cpi_authority_pda: light_system_accounts.cpi_authority_pda,
+        self_program: tapestry::ID,
+        light_system_program: light_system_accounts.light_system_program,
+        registered_program_pda: light_system_accounts.registered_program_pda,
+        noop_program: light_system_accounts.noop_program,
+        account_compression_authority: light_system_accounts.account_compression_authority,
+        account_compression_program: light_system_accounts.account_compression_program,
+        system_program: light_system_accounts.system_program,
+    }
+    .to_account_metas(None)
+}
+
+// Remaining accounts in the order `validate_merkle_trees` reads them
+fn remaining_accounts(with_input: bool) -> Vec<AccountMeta> {
+    let mut remaining_accounts = vec![
+        AccountMeta::new(MERKLE_TREE_PUBKEY, false),
+        AccountMeta::new(ADDRESS_MERKLE_TREE_PUBKEY, false),
+        AccountMeta::new(ADDRESS_QUEUE_PUBKEY, false),
+    ];
+    if with_input {
+        remaining_accounts.push(AccountMeta::new(NULLIFIER_QUEUE_PUBKEY, false));
+    }
+    remaining_accounts
+}
+
+#[test]
+fn test_create_node_instruction() {
+    let payer = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let random_bytes = [7; 32];
+    let builder = CreateNodeBuilder::new(
+        payer,
+        owner,
+        AddressSeed::Random(random_bytes),
+        node_args("alice"),
+    );
+
+    let seed = get_account_seed(AccountKey::NodeV1, &random_bytes);
+    assert_eq!(builder.seed().unwrap(), seed);
+    assert_eq!(builder.address().unwrap(), derive_new_address(&seed));
+
+    let instruction = builder.instruction(proof(), 4);
+
+    let mut accounts = create_node_accounts(payer, owner, None);
+    accounts.extend(remaining_accounts(false));
+    assert_eq!(instruction.program_id, tapestry::ID);
+    assert_eq!(instruction.accounts, accounts);
+    assert_eq!(
+        instruction.data,
+        tapestry::instruction::CreateNode {
+            proof: proof(),
+            address_merkle_tree_root_index: 4,
+            address_seed: AddressSeed::Random(random_bytes),
+            node_args: node_args("alice"),
+        }
+        .data()
+    );
+}
+
+#[test]
+fn test_create_node_instruction_with_update_authority() {
+    let payer = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let update_authority = Pubkey::new_unique();
+    let builder = CreateNodeBuilder::new(payer, owner, AddressSeed::Label, node_args("alice"))
+        .update_authority(update_authority);
+
+    let seed = get_label_seed("social", "alice");
+    assert_eq!(builder.seed().unwrap(), seed);
+    assert_eq!(builder.address().unwrap(), derive_new_address(&seed));
+
+    let instruction = builder.instruction(proof(), 0);
+
+    let mut accounts = create_node_accounts(payer, owner, Some(update_authority));
+    accounts.extend(remaining_accounts(false));
+    assert_eq!(instruction.accounts, accounts);
+    assert!(instruction
+        .accounts
+        .contains(&AccountMeta::new_readonly(update_authority, true)));
+}
+
+#[test]
+fn test_create_edge_instruction() {
+    let payer = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let random_bytes = [9; 32];
+    let builder = CreateEdgeBuilder::new(
+        payer,
+        owner,
+        AddressSeed::Random(random_bytes),
+        edge_args("alice", "bob"),
+    );
+
+    let seed = get_account_seed(AccountKey::EdgeV1, &random_bytes);
+    assert_eq!(builder.seed().unwrap(), seed);
+    assert_eq!(builder.address().unwrap(), derive_new_address(&seed));
+
+    let instruction = builder.instruction(proof(), 2);
+
+    // Without a proven target only the new address is created
+    let mut accounts = create_edge_accounts(payer, owner, None, None, None);
+    accounts.extend(remaining_accounts(false));
+    assert_eq!(instruction.program_id, tapestry::ID);
+    assert_eq!(instruction.accounts, accounts);
+    assert_eq!(
+        instruction.data,
+        tapestry::instruction::CreateEdge {
+            proof: proof(),
+            address_merkle_tree_root_index: 2,
+            address_seed: AddressSeed::Random(random_bytes),
+            edge_args: edge_args("alice", "bob"),
+            source: None,
+            target: None,
+            counters: None,
+        }
+        .data()
+    );
+}
+
+#[test]
+fn test_create_edge_instruction_with_target() {
+    let payer = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let target = proven_node(TokenGate::None);
+    let edge_args = edge_args("alice", &target.address());
+    let instruction =
+        CreateEdgeBuilder::new(payer, owner, AddressSeed::EdgePair, edge_args.clone())
+            .target(target.clone())
+            .instruction(proof(), 1);
+
+    // The proven target is an input, which needs the nullifier queue. An
+    // ungated target does not require the owner to sign.
+    let mut accounts = create_edge_accounts(payer, owner, None, None, None);
+    accounts.extend(remaining_accounts(true));
+    assert_eq!(instruction.accounts, accounts);
+    assert!(instruction
+        .accounts
+        .contains(&AccountMeta::new_readonly(owner, false)));
+    assert_eq!(
+        instruction.data,
+        tapestry::instruction::CreateEdge {
+            proof: proof(),
+            address_merkle_tree_root_index: 1,
+            address_seed: AddressSeed::EdgePair,
+            edge_args,
+            source: None,
+            target: Some(packed_node(target)),
+            counters: None,
+        }
+        .data()
+    );
+}
+
+#[test]
+fn test_create_edge_instruction_gated() {
+    let payer = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let gate_token_account = Pubkey::new_unique();
+    let gate_metadata = Pubkey::new_unique();
+    let target = proven_node(TokenGate::Collection {
+        collection: Pubkey::new_unique(),
+        min_amount: 1,
+    });
+    let edge_args = edge_args("alice", &target.address());
+    let instruction =
+        CreateEdgeBuilder::new(payer, owner, AddressSeed::EdgePair, edge_args.clone())
+            .target(target.clone())
+            .gate_accounts(gate_token_account, Some(gate_metadata))
+            .instruction(proof(), 1);
+
+    // The owner has to sign for the holding checked against the gate
+    let mut accounts = create_edge_accounts(
+        payer,
+        owner,
+        Some(gate_token_account),
+        Some(gate_metadata),
+        None,
+    );
+    for account in accounts.iter_mut().filter(|a| a.pubkey == owner) {
+        account.is_signer = true;
+    }
+    accounts.extend(remaining_accounts(true));
+    assert_eq!(instruction.accounts, accounts);
+    assert!(instruction
+        .accounts
+        .contains(&AccountMeta::new_readonly(owner, true)));
+    assert_eq!(
+        instruction.data,
+        tapestry::instruction::CreateEdge {
+            proof: proof(),
+            address_merkle_tree_root_index: 1,
+            address_seed: AddressSeed::EdgePair,
+            edge_args,
+            source: None,
+            target: Some(packed_node(target)),
+            counters: None,
+        }
+        .data()
+    );
+}
+
+#[test]
+fn test_create_edge_instruction_decompressed_target() {
+    let payer = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let node_account = Pubkey::new_unique();
+    let target = proven_node(TokenGate::Mint {
+        mint: Pubkey::new_unique(),
+        min_amount: 1,
+    });
+    let edge_args = edge_args("alice", &target.address());
+    let instruction =
+        CreateEdgeBuilder::new(payer, owner, AddressSeed::EdgePair, edge_args.clone())
+            .decompressed_target(node_account, &target.latest())
+            .instruction(proof(), 1);
+
+    // The node account is read, so no input is proven, but its gate still
+    // requires the owner to sign
+    let mut accounts = create_edge_accounts(payer, owner, None, None, Some(node_account));
+    for account in accounts.iter_mut().filter(|a| a.pubkey == owner) {
+        account.is_signer = true;
+    }
+    accounts.extend(remaining_accounts(false));
+    assert_eq!(instruction.accounts, accounts);
+    assert_eq!(
+        instruction.data,
+        tapestry::instruction::CreateEdge {
+            proof: proof(),
+            address_merkle_tree_root_index: 1,
+            address_seed: AddressSeed::EdgePair,
+            edge_args,
+            source: None,
+            target: None,
+            counters: None,
+        }
+        .data()
+    );
+}
+
+#[test]
+fn test_create_edge_instruction_counted() {
+    let payer = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let source = proven_node(TokenGate::None);
+    let instruction = CreateEdgeBuilder::new(
+        payer,
+        owner,
+        AddressSeed::EdgePair,
+        edge_args("alice", "bob"),
+    )
+    .source(source.clone())
+    .counters(counters())
+    .instruction(proof(), 3);
+
+    // Counting proves the source node, whose owner has to sign. All inputs
+    // point at the appended state tree and nullifier queue.
+    let mut accounts = create_edge_accounts(payer, owner, None, None, None);
+    for account in accounts.iter_mut().filter(|a| a.pubkey == owner) {
+        account.is_signer = true;
+    }
+    accounts.extend(remaining_accounts(true));
+    assert_eq!(instruction.accounts, accounts);
+    assert_eq!(
+        instruction.data,
+        tapestry::instruction::CreateEdge {
+            proof: proof(),
+            address_merkle_tree_root_index: 3,
+            address_seed: AddressSeed::EdgePair,
+            edge_args: edge_args("alice", "bob"),
+            source: Some(packed_node(source)),
+            target: None,
+            counters: Some(packed_counters()),
+        }
+        .data()
+    );
+}
+
+#[test]
+fn test_create_edge_seed_rejects_label() {
+    let builder = CreateEdgeBuilder::new(
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        AddressSeed::Label,
+        edge_args("alice", "bob"),
+    );
+    assert!(builder.seed().is_err());
+    assert!(builder.address().is_err());
+}
@@ -0,0 +1,49 @@
+//! Test-only program standing in for a third-party program that creates
+//! tapestry edges with `create_edge_cpi`. It is loaded by the tapestry
+//! integration tests and never deployed.
+
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+solana_program::declare_id!("CpiCa11er11111111111111111111111111111111111");
+
+// Seed of the PDA the program signs as
+pub const AUTHORITY_SEED: &[u8] = b"authority";
+
+pub fn authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY_SEED], &ID)
+}
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+// Forwards the instruction data to the program in the first account, passing
+// the other accounts through and signing for the authority PDA among them
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (program, forwarded_accounts) = accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let (authority, bump) = authority();
+
+    let ix = Instruction {
+        program_id: *program.key,
+        accounts: forwarded_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer || *account.key == authority,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: data.to_vec(),
+    };
+    invoke_signed(&ix, accounts, &[&[AUTHORITY_SEED, &[bump]]])
+}
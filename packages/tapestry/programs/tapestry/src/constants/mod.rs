@@ -3,3 +3,10 @@ pub use plugins::*;
 mod plugins;
 
 pub const CPI_AUTHORITY_SEED: &str = "cpi_authority";
+pub const NODE_ACCOUNT_SEED: &str = "node";
+pub const CONFIG_SEED: &str = "config";
+pub const ACTION_NONCE_SEED: &str = "action_nonce";
+pub const MAX_EDGE_TYPE_LENGTH: usize = 32;
+pub const MAX_NAMESPACE_LENGTH: usize = 32;
+pub const MAX_CIPHERTEXT_LENGTH: usize = 256;
+pub const MAX_THRESHOLD_SIGNERS: usize = 10;
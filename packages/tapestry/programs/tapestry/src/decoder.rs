@@ -0,0 +1,158 @@
+//! Off-chain decoding of Tapestry compressed account data.
+//!
+//! Indexers pass the discriminator and data bytes of a compressed account owned
+//! by the program and get back a typed account with the nested Borsh-encoded
+//! properties and creators already decoded. Every layout version is accepted.
+
+use anchor_lang::prelude::*;
+use light_hasher::{DataHasher, Discriminator, Poseidon};
+
+use crate::errors::ZkNftError;
+use crate::state::{
+    Creator, DecompressedNodeV1, EdgeV1, EdgeV2, EncryptedProperty, IdentityLinkV1, NodeCounterV1,
+    NodeV1, NodeV2, PendingEdgeV1, Properties, VersionedEdge, VersionedNode,
+};
+use crate::utils::decode_bytes;
+
+#[derive(Clone, Debug)]
+pub enum TapestryAccount {
+    Node(DecodedNode),
+    Edge(DecodedEdge),
+    PendingEdge(DecodedPendingEdge),
+    NodeCounter(NodeCounterV1),
+    // Node moved into a `NodeAccount`, read it from there
+    DecompressedNode(DecompressedNodeV1),
+    IdentityLink(IdentityLinkV1),
+}
+
+#[derive(Clone, Debug)]
+pub struct DecodedNode {
+    pub node: VersionedNode,
+    pub properties: Vec<Properties>,
+    pub creators: Vec<Creator>,
+    pub encrypted_properties: Vec<EncryptedProperty>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DecodedEdge {
+    pub edge: VersionedEdge,
+    pub properties: Vec<Properties>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DecodedPendingEdge {
+    pub pending_edge: PendingEdgeV1,
+    pub properties: Vec<Properties>,
+}
+
+impl TapestryAccount {
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        match self {
+            TapestryAccount::Node(node) => node.node.hash(),
+            TapestryAccount::Edge(edge) => edge.edge.hash(),
+            TapestryAccount::PendingEdge(pending_edge) => {
+                let hash = pending_edge
+                    .pending_edge
+                    .hash::<Poseidon>()
+                    .map_err(ProgramError::from)?;
+                Ok(hash)
+            }
+            TapestryAccount::NodeCounter(counter) => {
+                let hash = counter.hash::<Poseidon>().map_err(ProgramError::from)?;
+                Ok(hash)
+            }
+            TapestryAccount::DecompressedNode(placeholder) => {
+                let hash = placeholder.hash::<Poseidon>().map_err(ProgramError::from)?;
+                Ok(hash)
+            }
+            TapestryAccount::IdentityLink(identity_link) => {
+                let hash = identity_link
+                    .hash::<Poseidon>()
+                    .map_err(ProgramError::from)?;
+                Ok(hash)
+            }
+        }
+    }
+}
+
+pub fn decode_account(discriminator: &[u8; 8], data: &[u8]) -> Result<TapestryAccount> {
+    if *discriminator == NodeV1::discriminator() {
+        let node = NodeV1::try_from_slice(data)?;
+        let (properties, creators) = decode_node_data(
+            &node.node_data.properties_bytes,
+            &node.node_data.creators_bytes,
+        )?;
+        Ok(TapestryAccount::Node(DecodedNode {
+            node: VersionedNode::V1(node),
+            properties,
+            creators,
+            encrypted_properties: vec![],
+        }))
+    } else if *discriminator == NodeV2::discriminator() {
+        let node = NodeV2::try_from_slice(data)?;
+        let (properties, creators) = decode_node_data(
+            &node.node_data.properties_bytes,
+            &node.node_data.creators_bytes,
+        )?;
+        let encrypted_properties = decode_bytes(&node.node_data.encrypted_properties_bytes)?;
+        Ok(TapestryAccount::Node(DecodedNode {
+            node: VersionedNode::V2(node),
+            properties,
+            creators,
+            encrypted_properties,
+        }))
+    } else if *discriminator == EdgeV1::discriminator() {
+        let edge = EdgeV1::try_from_slice(data)?;
+        let properties = decode_bytes(&edge.edge_data.properties_bytes)?;
+        Ok(TapestryAccount::Edge(DecodedEdge {
+            edge: VersionedEdge::V1(edge),
+            properties,
+        }))
+    } else if *discriminator == EdgeV2::discriminator() {
+        let edge = EdgeV2::try_from_slice(data)?;
+        let properties = decode_bytes(&edge.edge_data.properties_bytes)?;
+        Ok(TapestryAccount::Edge(DecodedEdge {
+            edge: VersionedEdge::V2(edge),
+            properties,
+        }))
+    } else if *discriminator == PendingEdgeV1::discriminator() {
+        let pending_edge = PendingEdgeV1::try_from_slice(data)?;
+        let properties = decode_bytes(&pending_edge.edge_data.properties_bytes)?;
+        Ok(TapestryAccount::PendingEdge(DecodedPendingEdge {
+            pending_edge,
+            properties,
+        }))
+    } else if *discriminator == NodeCounterV1::discriminator() {
+        let counter = NodeCounterV1::try_from_slice(data)?;
+        Ok(TapestryAccount::NodeCounter(counter))
+    } else if *discriminator == DecompressedNodeV1::discriminator() {
+        let placeholder = DecompressedNodeV1::try_from_slice(data)?;
+        Ok(TapestryAccount::DecompressedNode(placeholder))
+    } else if *discriminator == IdentityLinkV1::discriminator() {
+        let identity_link = IdentityLinkV1::try_from_slice(data)?;
+        Ok(TapestryAccount::IdentityLink(identity_link))
+    } else {
+        Err(error!(ZkNftError::UnknownAccountDiscriminator))
+    }
+}
+
+// Decodes the account and checks that it hashes to the data hash stored in the
+// state tree, so tampered data from an untrusted indexer is rejected
+pub fn decode_and_verify_account(
+    discriminator: &[u8; 8],
+    data: &[u8],
+    data_hash: &[u8; 32],
+) -> Result<TapestryAccount> {
+    let account = decode_account(discriminator, data)?;
+    require!(account.hash()? == *data_hash, ZkNftError::DataHashMismatch);
+    Ok(account)
+}
+
+fn decode_node_data(
+    properties_bytes: &[u8],
+    creators_bytes: &[u8],
+) -> Result<(Vec<Properties>, Vec<Creator>)> {
+    let properties = decode_bytes(properties_bytes)?;
+    let creators = decode_bytes(creators_bytes)?;
+    Ok((properties, creators))
+}
@@ -26,4 +26,84 @@ pub enum ZkNftError {
     InvalidPluginsToInitialize,
     #[msg("Self-referencing edges are not allowed")]
     SelfReferenceNotAllowed,
+    #[msg("Edge type must not be empty")]
+    EdgeTypeEmpty,
+    #[msg("Edge type exceeds the maximum length")]
+    EdgeTypeTooLong,
+    #[msg("Edge is not mutable")]
+    EdgeNotMutable,
+    #[msg("Edge weight overflow")]
+    EdgeWeightOverflow,
+    #[msg("Edge validity window is invalid")]
+    InvalidEdgeValidity,
+    #[msg("Edge is not active")]
+    EdgeNotActive,
+    #[msg("Edge has not expired")]
+    EdgeNotExpired,
+    #[msg("Unknown account discriminator")]
+    UnknownAccountDiscriminator,
+    #[msg("Invalid account data")]
+    InvalidAccountData,
+    #[msg("Account data does not match its data hash")]
+    DataHashMismatch,
+    #[msg("Program authority is not a PDA of the calling program")]
+    InvalidProgramAuthority,
+    #[msg("Namespace exceeds the maximum length")]
+    NamespaceTooLong,
+    #[msg("Encrypted property ciphertext exceeds the maximum length")]
+    CiphertextTooLong,
+    #[msg("Edge does not match the asserted relationship")]
+    EdgeMismatch,
+    #[msg("Token gate of the target node is not satisfied")]
+    TokenGateNotSatisfied,
+    #[msg("Gated target node does not match the edge target")]
+    GateTargetMismatch,
+    #[msg("Node counter does not belong to the edge endpoint")]
+    CounterMismatch,
+    #[msg("Node counter overflow")]
+    CounterOverflow,
+    #[msg("Counted edges must update their node counters")]
+    MissingCounters,
+    #[msg("Node is not mutable")]
+    NodeNotMutable,
+    #[msg("Invalid threshold update authority")]
+    InvalidThreshold,
+    #[msg("Not enough threshold signers")]
+    ThresholdNotMet,
+    #[msg("Group update authority requires the group node")]
+    MissingGroupAuthority,
+    #[msg("Group node does not match the update authority")]
+    GroupMismatch,
+    #[msg("Node account does not belong to the compressed address")]
+    NodeAccountMismatch,
+    #[msg("Node lamports overflow")]
+    LamportsOverflow,
+    #[msg("Node holds fewer lamports than requested")]
+    InsufficientLamports,
+    #[msg("Address seed variant does not apply to this account")]
+    InvalidAddressSeed,
+    #[msg("Signer is not the program upgrade authority")]
+    NotUpgradeAuthority,
+    #[msg("Expected an ed25519 signature instruction before this one")]
+    MissingEd25519Signature,
+    #[msg("Ed25519 signature does not match the expected signer or message")]
+    InvalidEd25519Signature,
+    #[msg("Signed action has expired")]
+    SignedActionExpired,
+    #[msg("Signed action nonce does not match the signer's next nonce")]
+    InvalidActionNonce,
+    #[msg("Proven node does not match the edge endpoint")]
+    EndpointMismatch,
+    #[msg("Edges to a node must prove the target node")]
+    MissingTargetNode,
+    #[msg("Rent payer does not match the node account")]
+    InvalidRentPayer,
+    #[msg("Node given both as a compressed account and a node account")]
+    AmbiguousNode,
+    #[msg("Node must be proven or given as a node account")]
+    MissingNode,
+    #[msg("Inclusion proof does not match a recent state root")]
+    InvalidInclusionProof,
+    #[msg("Account has been nullified")]
+    AccountNullified,
 }
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+// Emitted when a node is permanently locked, `address` is its compressed
+// account address
+#[event]
+pub struct NodeMadeImmutable {
+    pub address: [u8; 32],
+    pub authority: Pubkey,
+}
+
+// Emitted when an edge is permanently locked, see `NodeMadeImmutable`
+#[event]
+pub struct EdgeMadeImmutable {
+    pub address: [u8; 32],
+    pub authority: Pubkey,
+}
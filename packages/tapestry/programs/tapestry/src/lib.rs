@@ -1,5 +1,8 @@
 pub mod constants;
+#[cfg(feature = "no-entrypoint")]
+pub mod decoder;
 pub mod errors;
+pub mod events;
 pub mod processor;
 pub mod state;
 pub mod utils;
@@ -18,14 +21,14 @@ pub mod tapestry {
         ctx: Context<'_, '_, '_, 'info, CreateNode<'info>>,
         proof: AnchorCompressedProof,
         address_merkle_tree_root_index: u16,
-        random_bytes: [u8; 32],
+        address_seed: AddressSeed,
         node_args: NodeArgs,
     ) -> Result<()> {
         processor::create_node(
             ctx,
             proof,
             address_merkle_tree_root_index,
-            random_bytes,
+            address_seed,
             node_args,
         )
     }
@@ -34,15 +37,417 @@ pub mod tapestry {
         ctx: Context<'_, '_, '_, 'info, CreateEdge<'info>>,
         proof: AnchorCompressedProof,
         address_merkle_tree_root_index: u16,
-        random_bytes: [u8; 32],
+        address_seed: AddressSeed,
         edge_args: EdgeArgs,
+        source: Option<ProvenNode>,
+        target: Option<ProvenNode>,
+        counters: Option<EdgeCounters>,
     ) -> Result<()> {
         processor::create_edge(
             ctx,
             proof,
             address_merkle_tree_root_index,
-            random_bytes,
+            address_seed,
             edge_args,
+            source,
+            target,
+            counters,
         )
     }
+
+    pub fn create_undirected_edge<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateUndirectedEdge<'info>>,
+        proof: AnchorCompressedProof,
+        address_merkle_tree_root_index: u16,
+        edge_args: EdgeArgs,
+        source: Option<ProvenNode>,
+        target: Option<ProvenNode>,
+    ) -> Result<()> {
+        processor::create_undirected_edge(
+            ctx,
+            proof,
+            address_merkle_tree_root_index,
+            edge_args,
+            source,
+            target,
+        )
+    }
+
+    pub fn request_edge<'info>(
+        ctx: Context<'_, '_, '_, 'info, RequestEdge<'info>>,
+        proof: AnchorCompressedProof,
+        address_merkle_tree_root_index: u16,
+        address_seed: AddressSeed,
+        edge_args: EdgeArgs,
+        source: Option<ProvenNode>,
+        target: Option<ProvenNode>,
+    ) -> Result<()> {
+        processor::request_edge(
+            ctx,
+            proof,
+            address_merkle_tree_root_index,
+            address_seed,
+            edge_args,
+            source,
+            target,
+        )
+    }
+
+    pub fn accept_edge<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptEdge<'info>>,
+        proof: AnchorCompressedProof,
+        pending_edge: PendingEdgeV1,
+        pending_edge_meta: AnchorCompressedAccountMeta,
+    ) -> Result<()> {
+        processor::accept_edge(ctx, proof, pending_edge, pending_edge_meta)
+    }
+
+    pub fn reject_edge<'info>(
+        ctx: Context<'_, '_, '_, 'info, RejectEdge<'info>>,
+        proof: AnchorCompressedProof,
+        pending_edge: PendingEdgeV1,
+        pending_edge_meta: AnchorCompressedAccountMeta,
+    ) -> Result<()> {
+        processor::reject_edge(ctx, proof, pending_edge, pending_edge_meta)
+    }
+
+    pub fn cancel_edge_request<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelEdgeRequest<'info>>,
+        proof: AnchorCompressedProof,
+        pending_edge: PendingEdgeV1,
+        pending_edge_meta: AnchorCompressedAccountMeta,
+    ) -> Result<()> {
+        processor::cancel_edge_request(ctx, proof, pending_edge, pending_edge_meta)
+    }
+
+    pub fn adjust_edge_weight<'info>(
+        ctx: Context<'_, '_, '_, 'info, AdjustEdgeWeight<'info>>,
+        proof: AnchorCompressedProof,
+        edge: VersionedEdge,
+        edge_meta: AnchorCompressedAccountMeta,
+        delta: i64,
+        group: Option<GroupAuthority>,
+    ) -> Result<()> {
+        processor::adjust_edge_weight(ctx, proof, edge, edge_meta, delta, group)
+    }
+
+    pub fn prune_expired_edge<'info>(
+        ctx: Context<'_, '_, '_, 'info, PruneExpiredEdge<'info>>,
+        proof: AnchorCompressedProof,
+        edge: VersionedEdge,
+        edge_meta: AnchorCompressedAccountMeta,
+        counters: Option<EdgeCounters>,
+    ) -> Result<()> {
+        processor::prune_expired_edge(ctx, proof, edge, edge_meta, counters)
+    }
+
+    /// Entry point for other programs, see [`processor::create_edge_cpi`].
+    pub fn create_edge_cpi<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateEdgeCpi<'info>>,
+        proof: AnchorCompressedProof,
+        address_merkle_tree_root_index: u16,
+        address_seed: AddressSeed,
+        edge_args: EdgeArgs,
+        authority_seeds: Vec<Vec<u8>>,
+        target: Option<ProvenNode>,
+    ) -> Result<()> {
+        processor::create_edge_cpi(
+            ctx,
+            proof,
+            address_merkle_tree_root_index,
+            address_seed,
+            edge_args,
+            authority_seeds,
+            target,
+        )
+    }
+
+    pub fn migrate_node_v1_to_v2<'info>(
+        ctx: Context<'_, '_, '_, 'info, Migrate<'info>>,
+        proof: AnchorCompressedProof,
+        node: NodeV1,
+        node_meta: AnchorCompressedAccountMeta,
+    ) -> Result<()> {
+        processor::migrate_node_v1_to_v2(ctx, proof, node, node_meta)
+    }
+
+    pub fn migrate_edge_v1_to_v2<'info>(
+        ctx: Context<'_, '_, '_, 'info, Migrate<'info>>,
+        proof: AnchorCompressedProof,
+        edge: EdgeV1,
+        edge_meta: AnchorCompressedAccountMeta,
+    ) -> Result<()> {
+        processor::migrate_edge_v1_to_v2(ctx, proof, edge, edge_meta)
+    }
+
+    pub fn assert_edge<'info>(
+        ctx: Context<'_, '_, '_, 'info, AssertEdge<'info>>,
+        proof: AnchorCompressedProof,
+        edge: VersionedEdge,
+        edge_meta: AnchorCompressedAccountMeta,
+        source_node: String,
+        target_node: String,
+        edge_type: String,
+    ) -> Result<()> {
+        processor::assert_edge(
+            ctx,
+            proof,
+            edge,
+            edge_meta,
+            source_node,
+            target_node,
+            edge_type,
+        )
+    }
+
+    pub fn create_node_counter<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateNodeCounter<'info>>,
+        proof: AnchorCompressedProof,
+        address_merkle_tree_root_index: u16,
+        node: String,
+        edge_type: String,
+    ) -> Result<()> {
+        processor::create_node_counter(ctx, proof, address_merkle_tree_root_index, node, edge_type)
+    }
+
+    pub fn set_node_update_authority<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetUpdateAuthority<'info>>,
+        proof: AnchorCompressedProof,
+        node: VersionedNode,
+        node_meta: AnchorCompressedAccountMeta,
+        new_update_authority: NodeUpdateAuthority,
+        group: Option<GroupAuthority>,
+    ) -> Result<()> {
+        processor::set_node_update_authority(
+            ctx,
+            proof,
+            node,
+            node_meta,
+            new_update_authority,
+            group,
+        )
+    }
+
+    pub fn revoke_node_update_authority<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetUpdateAuthority<'info>>,
+        proof: AnchorCompressedProof,
+        node: VersionedNode,
+        node_meta: AnchorCompressedAccountMeta,
+        group: Option<GroupAuthority>,
+    ) -> Result<()> {
+        processor::revoke_node_update_authority(ctx, proof, node, node_meta, group)
+    }
+
+    pub fn set_edge_update_authority<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetUpdateAuthority<'info>>,
+        proof: AnchorCompressedProof,
+        edge: VersionedEdge,
+        edge_meta: AnchorCompressedAccountMeta,
+        new_update_authority: NodeUpdateAuthority,
+        group: Option<GroupAuthority>,
+    ) -> Result<()> {
+        processor::set_edge_update_authority(
+            ctx,
+            proof,
+            edge,
+            edge_meta,
+            new_update_authority,
+            group,
+        )
+    }
+
+    pub fn revoke_edge_update_authority<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetUpdateAuthority<'info>>,
+        proof: AnchorCompressedProof,
+        edge: VersionedEdge,
+        edge_meta: AnchorCompressedAccountMeta,
+        group: Option<GroupAuthority>,
+    ) -> Result<()> {
+        processor::revoke_edge_update_authority(ctx, proof, edge, edge_meta, group)
+    }
+
+    pub fn make_node_immutable<'info>(
+        ctx: Context<'_, '_, '_, 'info, MakeImmutable<'info>>,
+        proof: AnchorCompressedProof,
+        node: VersionedNode,
+        node_meta: AnchorCompressedAccountMeta,
+        group: Option<GroupAuthority>,
+    ) -> Result<()> {
+        processor::make_node_immutable(ctx, proof, node, node_meta, group)
+    }
+
+    pub fn make_edge_immutable<'info>(
+        ctx: Context<'_, '_, '_, 'info, MakeImmutable<'info>>,
+        proof: AnchorCompressedProof,
+        edge: VersionedEdge,
+        edge_meta: AnchorCompressedAccountMeta,
+        group: Option<GroupAuthority>,
+    ) -> Result<()> {
+        processor::make_edge_immutable(ctx, proof, edge, edge_meta, group)
+    }
+
+    pub fn decompress_node<'info>(
+        ctx: Context<'_, '_, '_, 'info, DecompressNode<'info>>,
+        proof: AnchorCompressedProof,
+        node: VersionedNode,
+        node_meta: AnchorCompressedAccountMeta,
+        group: Option<GroupAuthority>,
+    ) -> Result<()> {
+        processor::decompress_node(ctx, proof, node, node_meta, group)
+    }
+
+    pub fn recompress_node<'info>(
+        ctx: Context<'_, '_, '_, 'info, RecompressNode<'info>>,
+        proof: AnchorCompressedProof,
+        placeholder_meta: AnchorCompressedAccountMeta,
+        group: Option<GroupAuthority>,
+    ) -> Result<()> {
+        processor::recompress_node(ctx, proof, placeholder_meta, group)
+    }
+
+    pub fn tip_node<'info>(
+        ctx: Context<'_, '_, '_, 'info, TipNode<'info>>,
+        proof: AnchorCompressedProof,
+        node: Option<VersionedNode>,
+        node_meta: AnchorCompressedAccountMeta,
+        lamports: u64,
+    ) -> Result<()> {
+        processor::tip_node(ctx, proof, node, node_meta, lamports)
+    }
+
+    pub fn withdraw_from_node<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawFromNode<'info>>,
+        proof: AnchorCompressedProof,
+        node: VersionedNode,
+        node_meta: AnchorCompressedAccountMeta,
+        lamports: u64,
+    ) -> Result<()> {
+        processor::withdraw_from_node(ctx, proof, node, node_meta, lamports)
+    }
+
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        attestation_authority: Pubkey,
+    ) -> Result<()> {
+        processor::initialize_config(ctx, attestation_authority)
+    }
+
+    pub fn set_attestation_authority(
+        ctx: Context<SetAttestationAuthority>,
+        attestation_authority: Pubkey,
+    ) -> Result<()> {
+        processor::set_attestation_authority(ctx, attestation_authority)
+    }
+
+    pub fn create_identity_link<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateIdentityLink<'info>>,
+        proof: AnchorCompressedProof,
+        address_merkle_tree_root_index: u16,
+        node: Option<ProvenNode>,
+        identity: ExternalIdentity,
+    ) -> Result<()> {
+        processor::create_identity_link(ctx, proof, address_merkle_tree_root_index, node, identity)
+    }
+
+    pub fn revoke_identity_link<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevokeIdentityLink<'info>>,
+        proof: AnchorCompressedProof,
+        identity_link: IdentityLinkV1,
+        identity_link_meta: AnchorCompressedAccountMeta,
+    ) -> Result<()> {
+        processor::revoke_identity_link(ctx, proof, identity_link, identity_link_meta)
+    }
+
+    pub fn initialize_action_nonce(ctx: Context<InitializeActionNonce>) -> Result<()> {
+        processor::initialize_action_nonce(ctx)
+    }
+
+    pub fn create_node_signed<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateNodeSigned<'info>>,
+        proof: AnchorCompressedProof,
+        address_merkle_tree_root_index: u16,
+        address_seed: AddressSeed,
+        node_args: NodeArgs,
+        signed_action: SignedAction,
+    ) -> Result<()> {
+        processor::create_node_signed(
+            ctx,
+            proof,
+            address_merkle_tree_root_index,
+            address_seed,
+            node_args,
+            signed_action,
+        )
+    }
+
+    pub fn create_edge_signed<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateEdgeSigned<'info>>,
+        proof: AnchorCompressedProof,
+        address_merkle_tree_root_index: u16,
+        address_seed: AddressSeed,
+        edge_args: EdgeArgs,
+        signed_action: SignedAction,
+        target: Option<ProvenNode>,
+    ) -> Result<()> {
+        processor::create_edge_signed(
+            ctx,
+            proof,
+            address_merkle_tree_root_index,
+            address_seed,
+            edge_args,
+            signed_action,
+            target,
+        )
+    }
+
+    pub fn adjust_edge_weight_signed<'info>(
+        ctx: Context<'_, '_, '_, 'info, AdjustEdgeWeightSigned<'info>>,
+        proof: AnchorCompressedProof,
+        edge: VersionedEdge,
+        edge_meta: AnchorCompressedAccountMeta,
+        delta: i64,
+        group: Option<GroupAuthority>,
+        signed_action: SignedAction,
+    ) -> Result<()> {
+        processor::adjust_edge_weight_signed(
+            ctx,
+            proof,
+            edge,
+            edge_meta,
+            delta,
+            group,
+            signed_action,
+        )
+    }
+
+    pub fn set_node_update_authority_signed<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetUpdateAuthoritySigned<'info>>,
+        proof: AnchorCompressedProof,
+        node: VersionedNode,
+        node_meta: AnchorCompressedAccountMeta,
+        new_update_authority: NodeUpdateAuthority,
+        group: Option<GroupAuthority>,
+        signed_action: SignedAction,
+    ) -> Result<()> {
+        processor::set_node_update_authority_signed(
+            ctx,
+            proof,
+            node,
+            node_meta,
+            new_update_authority,
+            group,
+            signed_action,
+        )
+    }
+
+    pub fn make_node_immutable_signed<'info>(
+        ctx: Context<'_, '_, '_, 'info, MakeImmutableSigned<'info>>,
+        proof: AnchorCompressedProof,
+        node: VersionedNode,
+        node_meta: AnchorCompressedAccountMeta,
+        group: Option<GroupAuthority>,
+        signed_action: SignedAction,
+    ) -> Result<()> {
+        processor::make_node_immutable_signed(ctx, proof, node, node_meta, group, signed_action)
+    }
 }
@@ -0,0 +1,102 @@
+use crate::constants::CPI_AUTHORITY_SEED;
+use crate::errors::ZkNftError;
+use crate::state::{AccountKey, AnchorCompressedAccountMeta, EdgeDirection, EdgeV2, PendingEdgeV1};
+use crate::utils::validate_merkle_trees;
+use crate::utils::{input_compressed_account, output_compressed_account};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_account_update;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+pub fn accept_edge<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcceptEdge<'info>>,
+    proof: AnchorCompressedProof,
+    pending_edge: PendingEdgeV1,
+    pending_edge_meta: AnchorCompressedAccountMeta,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    // Only the owner of the target node can accept the request
+    require!(
+        pending_edge.target_owner == ctx.accounts.target_owner.key(),
+        ZkNftError::InvalidAuthority
+    );
+
+    // A request for a relationship that has already lapsed cannot be accepted
+    let clock = Clock::get()?;
+    require!(
+        !pending_edge.validity.is_expired(clock.unix_timestamp),
+        ZkNftError::EdgeNotActive
+    );
+
+    let merkle_context = pending_edge_meta.merkle_context();
+    let pending_edge_compressed_account = input_compressed_account(
+        &pending_edge,
+        &pending_edge_meta.address,
+        &crate::ID,
+        &merkle_context,
+        pending_edge_meta.root_index,
+    )?;
+
+    // Convert the request into a live edge at the same address
+    let edge = EdgeV2 {
+        key: AccountKey::EdgeV2,
+        source_node: pending_edge.source_node,
+        target_node: pending_edge.target_node,
+        edge_type: pending_edge.edge_type,
+        namespace: pending_edge.namespace,
+        direction: EdgeDirection::Directed,
+        edge_data: pending_edge.edge_data,
+        weight: pending_edge.weight,
+        validity: pending_edge.validity,
+        is_mutable: pending_edge.is_mutable,
+        owner: pending_edge.owner,
+        update_authority: pending_edge.update_authority,
+    };
+    let edge_compressed_account = output_compressed_account(
+        &edge,
+        &pending_edge_meta.address,
+        &crate::ID,
+        &merkle_context,
+    )?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Replace the pending edge with the edge
+    let cpi_inputs = create_cpi_inputs_for_account_update(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        pending_edge_compressed_account,
+        edge_compressed_account,
+        None,
+    );
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct AcceptEdge<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    pub target_owner: Signer<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
@@ -0,0 +1,68 @@
+use crate::constants::CPI_AUTHORITY_SEED;
+use crate::errors::ZkNftError;
+use crate::state::{AnchorCompressedAccountMeta, EdgeV2, GroupAuthority, VersionedEdge};
+use crate::utils::{update_cpi_inputs, validate_merkle_trees};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+pub fn adjust_edge_weight<'info>(
+    ctx: Context<'_, '_, '_, 'info, AdjustEdgeWeight<'info>>,
+    proof: AnchorCompressedProof,
+    edge: VersionedEdge,
+    edge_meta: AnchorCompressedAccountMeta,
+    delta: i64,
+    group: Option<GroupAuthority>,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let edge_input_compressed_account = edge.input_compressed_account(&edge_meta)?;
+    let edge = edge.into_latest();
+
+    require!(edge.is_mutable, ZkNftError::EdgeNotMutable);
+    edge.update_authority.validate_update(
+        &edge.owner,
+        &ctx.accounts.authority.key(),
+        ctx.remaining_accounts,
+        group.as_ref(),
+    )?;
+
+    // Apply the signed delta
+    let weight = edge
+        .weight
+        .checked_add_signed(delta)
+        .ok_or(ZkNftError::EdgeWeightOverflow)?;
+    let updated_edge = EdgeV2 { weight, ..edge };
+    let cpi_inputs = update_cpi_inputs(
+        proof,
+        edge_input_compressed_account,
+        &updated_edge,
+        &edge_meta,
+        group.as_ref(),
+    )?;
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct AdjustEdgeWeight<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
@@ -0,0 +1,94 @@
+use crate::constants::{ACTION_NONCE_SEED, CPI_AUTHORITY_SEED};
+use crate::errors::ZkNftError;
+use crate::state::{
+    ActionNonce, AnchorCompressedAccountMeta, EdgeV2, GroupAuthority, SignedAction, VersionedEdge,
+};
+use crate::utils::{update_cpi_inputs, validate_merkle_trees};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+/// `adjust_edge_weight` for an authority who signed the edge address and delta
+/// off chain, so a relayer can pay the fees
+pub fn adjust_edge_weight_signed<'info>(
+    ctx: Context<'_, '_, '_, 'info, AdjustEdgeWeightSigned<'info>>,
+    proof: AnchorCompressedProof,
+    edge: VersionedEdge,
+    edge_meta: AnchorCompressedAccountMeta,
+    delta: i64,
+    group: Option<GroupAuthority>,
+    signed_action: SignedAction,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let authority = ctx.accounts.authority.key();
+    let args = (edge_meta.address, delta).try_to_vec().unwrap();
+    signed_action.verify(
+        &mut ctx.accounts.action_nonce,
+        &ctx.accounts.instructions_sysvar,
+        "adjust_edge_weight",
+        &authority,
+        &args,
+    )?;
+
+    let edge_input_compressed_account = edge.input_compressed_account(&edge_meta)?;
+    let edge = edge.into_latest();
+
+    require!(edge.is_mutable, ZkNftError::EdgeNotMutable);
+    edge.update_authority.validate_update(
+        &edge.owner,
+        &authority,
+        ctx.remaining_accounts,
+        group.as_ref(),
+    )?;
+
+    // Apply the signed delta
+    let weight = edge
+        .weight
+        .checked_add_signed(delta)
+        .ok_or(ZkNftError::EdgeWeightOverflow)?;
+    let updated_edge = EdgeV2 { weight, ..edge };
+    let cpi_inputs = update_cpi_inputs(
+        proof,
+        edge_input_compressed_account,
+        &updated_edge,
+        &edge_meta,
+        group.as_ref(),
+    )?;
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct AdjustEdgeWeightSigned<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    /// CHECK: Signs the action in the ed25519 instruction.
+    pub authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [ACTION_NONCE_SEED.as_bytes(), authority.key().as_ref()],
+        bump = action_nonce.bump
+    )]
+    pub action_nonce: Account<'info, ActionNonce>,
+    /// CHECK: Address is checked against the instructions sysvar.
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
@@ -0,0 +1,46 @@
+use crate::errors::ZkNftError;
+use crate::state::{AnchorCompressedAccountMeta, VersionedEdge};
+use crate::utils::{verify_inclusion, MERKLE_TREE_PUBKEY, NULLIFIER_QUEUE_PUBKEY};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+
+// Succeeds only if the edge exists, is active and connects the given nodes.
+// Meant to be called via CPI by programs gating actions on a relationship,
+// the edge is proven against the state root and left untouched, so asserting
+// an edge doesn't contend with other transactions using it.
+pub fn assert_edge<'info>(
+    ctx: Context<'_, '_, '_, 'info, AssertEdge<'info>>,
+    proof: AnchorCompressedProof,
+    edge: VersionedEdge,
+    edge_meta: AnchorCompressedAccountMeta,
+    source_node: String,
+    target_node: String,
+    edge_type: String,
+) -> Result<()> {
+    let edge_compressed_account = edge.input_compressed_account(&edge_meta)?;
+    verify_inclusion(
+        &proof,
+        &edge_compressed_account,
+        &ctx.accounts.merkle_tree,
+        &ctx.accounts.nullifier_queue,
+    )?;
+
+    let edge = edge.into_latest();
+    require!(
+        edge.connects(&source_node, &target_node, &edge_type),
+        ZkNftError::EdgeMismatch
+    );
+    edge.require_active()?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AssertEdge<'info> {
+    /// CHECK: The state tree the edge is proven against.
+    #[account(address = MERKLE_TREE_PUBKEY @ ZkNftError::InvalidMerkleTrees)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: The nullifier queue of the state tree, only read.
+    #[account(address = NULLIFIER_QUEUE_PUBKEY @ ZkNftError::InvalidMerkleTrees)]
+    pub nullifier_queue: UncheckedAccount<'info>,
+}
@@ -0,0 +1,71 @@
+use crate::constants::CPI_AUTHORITY_SEED;
+use crate::errors::ZkNftError;
+use crate::state::{AnchorCompressedAccountMeta, PendingEdgeV1};
+use crate::utils::input_compressed_account;
+use crate::utils::validate_merkle_trees;
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_account_deletion;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+pub fn cancel_edge_request<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelEdgeRequest<'info>>,
+    proof: AnchorCompressedProof,
+    pending_edge: PendingEdgeV1,
+    pending_edge_meta: AnchorCompressedAccountMeta,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    // Only the requester can withdraw their request
+    require!(
+        pending_edge.owner == ctx.accounts.owner.key(),
+        ZkNftError::InvalidAuthority
+    );
+
+    let pending_edge_compressed_account = input_compressed_account(
+        &pending_edge,
+        &pending_edge_meta.address,
+        &crate::ID,
+        &pending_edge_meta.merkle_context(),
+        pending_edge_meta.root_index,
+    )?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Nullify the pending edge
+    let cpi_inputs = create_cpi_inputs_for_account_deletion(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        pending_edge_compressed_account,
+        None,
+    );
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct CancelEdgeRequest<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    pub owner: Signer<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
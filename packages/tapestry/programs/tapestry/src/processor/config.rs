@@ -0,0 +1,62 @@
+use crate::constants::CONFIG_SEED;
+use crate::errors::ZkNftError;
+use crate::state::TapestryConfig;
+use anchor_lang::prelude::*;
+
+// The upgrade authority becomes the admin, so nobody can front-run the
+// deployment and install their own attestation authority
+pub fn initialize_config(
+    ctx: Context<InitializeConfig>,
+    attestation_authority: Pubkey,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.admin.key();
+    config.attestation_authority = attestation_authority;
+    config.bump = ctx.bumps.config;
+    Ok(())
+}
+
+// Links attested by the previous authority stay in place, they record the
+// authority that signed them
+pub fn set_attestation_authority(
+    ctx: Context<SetAttestationAuthority>,
+    attestation_authority: Pubkey,
+) -> Result<()> {
+    ctx.accounts.config.attestation_authority = attestation_authority;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = TapestryConfig::SPACE,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump
+    )]
+    pub config: Account<'info, TapestryConfig>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Tapestry>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ ZkNftError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetAttestationAuthority<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, TapestryConfig>,
+}
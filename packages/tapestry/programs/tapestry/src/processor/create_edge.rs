@@ -1,10 +1,15 @@
 use crate::constants::CPI_AUTHORITY_SEED;
 use crate::errors::ZkNftError;
-use crate::state::{AccountKey, EdgeArgs, EdgeData, EdgeV1, NodeUpdateAuthority};
+use crate::state::{
+    AddressSeed, EdgeArgs, EdgeCounters, EdgeDataV2, EdgeV2, EndpointNode, NodeAccount,
+    NodeUpdateAuthority, ProvenNode, TokenGate,
+};
+use crate::utils::new_compressed_account;
 use crate::utils::validate_merkle_trees;
-use crate::utils::{get_account_seed, new_compressed_account};
 use crate::AnchorCompressedProof;
 use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::token::TokenAccount;
 use light_sdk::merkle_context::{PackedAddressMerkleContext, PackedMerkleOutputContext};
 use light_sdk::proof::CompressedProof;
 use light_sdk::utils::create_cpi_inputs_for_new_account;
@@ -15,8 +20,11 @@ pub fn create_edge<'info>(
     ctx: Context<'_, '_, '_, 'info, CreateEdge<'info>>,
     proof: AnchorCompressedProof,
     address_merkle_tree_root_index: u16,
-    random_bytes: [u8; 32],
+    address_seed: AddressSeed,
     edge_args: EdgeArgs,
+    source: Option<ProvenNode>,
+    target: Option<ProvenNode>,
+    counters: Option<EdgeCounters>,
 ) -> Result<()> {
     let merkle_output_context = PackedMerkleOutputContext {
         merkle_tree_pubkey_index: 0,
@@ -25,32 +33,89 @@ pub fn create_edge<'info>(
         address_merkle_tree_pubkey_index: 1,
         address_queue_pubkey_index: 2,
     };
-    validate_merkle_trees(0, Some(1), Some(2), None, ctx.remaining_accounts)?;
+    // The endpoints and the node counters are proven as inputs, which needs the
+    // nullifier queue
+    let nullifier_queue_pubkey_index =
+        (source.is_some() || target.is_some() || counters.is_some()).then_some(3);
+    validate_merkle_trees(
+        0,
+        Some(1),
+        Some(2),
+        nullifier_queue_pubkey_index,
+        ctx.remaining_accounts,
+    )?;
+
+    edge_args.validate()?;
+    let edge_seed = address_seed.edge_seed(&edge_args)?;
+
+    // Check the token gate declared by the target node
+    let target =
+        EndpointNode::from_accounts(target.as_ref(), ctx.accounts.target_node_account.as_deref())?;
+    let token_gate = TokenGate::of_target(&edge_args.target_node, target.as_ref())?;
+    if token_gate != TokenGate::None {
+        // Only the holder can satisfy a gate for themselves
+        require!(ctx.accounts.owner.is_signer, ErrorCode::AccountNotSigner);
+    }
+    token_gate.verify(
+        &ctx.accounts.owner.key(),
+        ctx.accounts.gate_token_account.as_deref(),
+        ctx.accounts.gate_metadata.as_deref(),
+    )?;
+    let target_compressed_accounts = target
+        .as_ref()
+        .map(EndpointNode::proven_compressed_accounts)
+        .transpose()?
+        .flatten();
 
-    // Validate that source and target nodes are different
-    if edge_args.source_node == edge_args.target_node {
-        return Err(error!(ZkNftError::SelfReferenceNotAllowed));
+    // Only the owner of the source node may count an edge from it, or the
+    // counters could be inflated with edges from nodes that don't exist
+    let source =
+        EndpointNode::from_accounts(source.as_ref(), ctx.accounts.source_node_account.as_deref())?;
+    if let Some(source) = &source {
+        source.require_endpoint(&edge_args.source_node)?;
+        require!(ctx.accounts.owner.is_signer, ErrorCode::AccountNotSigner);
+        require_keys_eq!(
+            source.latest().owner,
+            ctx.accounts.owner.key(),
+            ZkNftError::InvalidAuthority
+        );
     }
+    let source_compressed_accounts = source
+        .as_ref()
+        .map(EndpointNode::proven_compressed_accounts)
+        .transpose()?
+        .flatten();
+
+    // Count the edge on both nodes, if their counters were given
+    let (counter_inputs, counter_outputs) = match &counters {
+        Some(counters) => {
+            require!(source.is_some(), ZkNftError::MissingNode);
+            counters.require_matches(
+                &edge_args.source_node,
+                &edge_args.target_node,
+                &edge_args.edge_type,
+            )?;
+            counters.compressed_accounts(1)?
+        }
+        None => (Vec::new(), Vec::new()),
+    };
 
     // Create the edge data from properties
-    let edge_data = EdgeData::new(&edge_args.properties);
+    let edge_data = EdgeDataV2::new(&edge_args.properties, token_gate, counters.is_some());
 
     // Create the edge
-    let edge = EdgeV1 {
-        key: AccountKey::EdgeV1,
-        source_node: edge_args.source_node,
-        target_node: edge_args.target_node,
+    let update_authority = ctx
+        .accounts
+        .update_authority
+        .as_ref()
+        .map(|update_authority| update_authority.key());
+    let edge = EdgeV2::new(
+        edge_args,
         edge_data,
-        is_mutable: edge_args.is_mutable,
-        owner: ctx.accounts.owner.key(),
-        update_authority: match &ctx.accounts.update_authority {
-            Some(update_authority) => NodeUpdateAuthority::Address(update_authority.key()),
-            None => NodeUpdateAuthority::None,
-        },
-        initialized_plugins: 0,
-    };
+        ctx.accounts.owner.key(),
+        NodeUpdateAuthority::from_signer(update_authority),
+    );
 
-    let edge_seed = get_account_seed(AccountKey::EdgeV1, &random_bytes);
     let (edge_compressed_account, edge_new_address_params) = new_compressed_account(
         &edge,
         &edge_seed,
@@ -65,7 +130,7 @@ pub fn create_edge<'info>(
     let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
 
     // Create account
-    let cpi_inputs = create_cpi_inputs_for_new_account(
+    let mut cpi_inputs = create_cpi_inputs_for_new_account(
         CompressedProof {
             a: proof.a,
             b: proof.b,
@@ -75,6 +140,21 @@ pub fn create_edge<'info>(
         edge_compressed_account,
         None,
     );
+    for (input, output) in [source_compressed_accounts, target_compressed_accounts]
+        .into_iter()
+        .flatten()
+    {
+        cpi_inputs
+            .input_compressed_accounts_with_merkle_context
+            .push(input);
+        cpi_inputs.output_compressed_accounts.push(output);
+    }
+    cpi_inputs
+        .input_compressed_accounts_with_merkle_context
+        .extend(counter_inputs);
+    cpi_inputs
+        .output_compressed_accounts
+        .extend(counter_outputs);
 
     verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
 
@@ -88,8 +168,17 @@ pub struct CreateEdge<'info> {
     #[fee_payer]
     pub payer: Signer<'info>,
     pub update_authority: Option<Signer<'info>>,
-    /// CHECK: This can be any valid public key.
+    /// CHECK: This can be any valid public key, it must sign for gated targets
+    /// and counted edges.
     pub owner: UncheckedAccount<'info>,
+    // Holding checked against the token gate of the target node
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    // Metadata of the held NFT, for collection gates
+    pub gate_metadata: Option<Account<'info, MetadataAccount>>,
+    // Source node while decompressed, read instead of proven
+    pub source_node_account: Option<Account<'info, NodeAccount>>,
+    // Target node while decompressed, read instead of proven
+    pub target_node_account: Option<Account<'info, NodeAccount>>,
 
     /// CHECK: Checked in light-system-program.
     #[authority]
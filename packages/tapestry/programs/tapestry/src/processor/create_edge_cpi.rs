@@ -0,0 +1,181 @@
+use crate::constants::CPI_AUTHORITY_SEED;
+use crate::errors::ZkNftError;
+use crate::state::{
+    AddressSeed, EdgeArgs, EdgeDataV2, EdgeV2, EndpointNode, NodeAccount, NodeUpdateAuthority,
+    ProvenNode, TokenGate,
+};
+use crate::utils::new_compressed_account;
+use crate::utils::validate_merkle_trees;
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::token::TokenAccount;
+use light_sdk::merkle_context::{PackedAddressMerkleContext, PackedMerkleOutputContext};
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_new_account;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+/// Creates an edge on behalf of another program.
+///
+/// The calling program signs with one of its PDAs, which becomes the owner of
+/// the edge. `authority_seeds` are the seeds of that PDA including the bump,
+/// and are used to check that it really belongs to `calling_program`. The edge
+/// records the calling program in its edge data, so programmatic edges can be
+/// told apart from user-signed ones even after their update authority changed.
+///
+/// Edges to a node must pass the proven target node, whose token gate the
+/// program authority has to satisfy, and cover it in the validity proof.
+///
+/// ```ignore
+/// tapestry::cpi::create_edge_cpi(
+///     CpiContext::new_with_signer(
+///         tapestry_program.to_account_info(),
+///         tapestry::cpi::accounts::CreateEdgeCpi { .. },
+///         &[&[b"tapestry", &[bump]]],
+///     )
+///     .with_remaining_accounts(merkle_tree_accounts),
+///     proof,
+///     address_merkle_tree_root_index,
+///     tapestry::state::AddressSeed::EdgePair,
+///     edge_args,
+///     vec![b"tapestry".to_vec(), vec![bump]],
+///     Some(target),
+/// )?;
+/// ```
+pub fn create_edge_cpi<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateEdgeCpi<'info>>,
+    proof: AnchorCompressedProof,
+    address_merkle_tree_root_index: u16,
+    address_seed: AddressSeed,
+    edge_args: EdgeArgs,
+    authority_seeds: Vec<Vec<u8>>,
+    target: Option<ProvenNode>,
+) -> Result<()> {
+    let merkle_output_context = PackedMerkleOutputContext {
+        merkle_tree_pubkey_index: 0,
+    };
+    let address_merkle_context = PackedAddressMerkleContext {
+        address_merkle_tree_pubkey_index: 1,
+        address_queue_pubkey_index: 2,
+    };
+    // The target node is proven as an input, which needs the nullifier queue
+    let nullifier_queue_pubkey_index = target.is_some().then_some(3);
+    validate_merkle_trees(
+        0,
+        Some(1),
+        Some(2),
+        nullifier_queue_pubkey_index,
+        ctx.remaining_accounts,
+    )?;
+
+    // The signer must be a PDA of the calling program
+    let program_id = ctx.accounts.calling_program.key();
+    let authority_seeds: Vec<&[u8]> = authority_seeds.iter().map(Vec::as_slice).collect();
+    let expected_authority = Pubkey::create_program_address(&authority_seeds, &program_id)
+        .map_err(|_| error!(ZkNftError::InvalidProgramAuthority))?;
+    require_keys_eq!(
+        expected_authority,
+        ctx.accounts.program_authority.key(),
+        ZkNftError::InvalidProgramAuthority
+    );
+
+    edge_args.validate()?;
+    let edge_seed = address_seed.edge_seed(&edge_args)?;
+
+    // Check the token gate declared by the target node
+    let target =
+        EndpointNode::from_accounts(target.as_ref(), ctx.accounts.target_node_account.as_deref())?;
+    let token_gate = TokenGate::of_target(&edge_args.target_node, target.as_ref())?;
+    token_gate.verify(
+        &expected_authority,
+        ctx.accounts.gate_token_account.as_deref(),
+        ctx.accounts.gate_metadata.as_deref(),
+    )?;
+    let target_compressed_accounts = target
+        .as_ref()
+        .map(EndpointNode::proven_compressed_accounts)
+        .transpose()?
+        .flatten();
+
+    // Create the edge data from properties
+    let edge_data = EdgeDataV2 {
+        caller_program: Some(program_id),
+        ..EdgeDataV2::new(&edge_args.properties, token_gate, false)
+    };
+
+    // Create the edge
+    let edge = EdgeV2::new(
+        edge_args,
+        edge_data,
+        expected_authority,
+        NodeUpdateAuthority::Program {
+            program_id,
+            authority: expected_authority,
+        },
+    );
+
+    let (edge_compressed_account, edge_new_address_params) = new_compressed_account(
+        &edge,
+        &edge_seed,
+        &crate::ID,
+        &merkle_output_context,
+        &address_merkle_context,
+        address_merkle_tree_root_index,
+        ctx.remaining_accounts,
+    )?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Create account
+    let mut cpi_inputs = create_cpi_inputs_for_new_account(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        edge_new_address_params,
+        edge_compressed_account,
+        None,
+    );
+    if let Some((target_input, target_output)) = target_compressed_accounts {
+        cpi_inputs
+            .input_compressed_accounts_with_merkle_context
+            .push(target_input);
+        cpi_inputs.output_compressed_accounts.push(target_output);
+    }
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct CreateEdgeCpi<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    /// PDA of the calling program, signed for with `invoke_signed`.
+    pub program_authority: Signer<'info>,
+    /// CHECK: Only used to verify the program authority derivation.
+    #[account(executable)]
+    pub calling_program: UncheckedAccount<'info>,
+    // Holding checked against the token gate of the target node
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    // Metadata of the held NFT, for collection gates
+    pub gate_metadata: Option<Account<'info, MetadataAccount>>,
+    // Target node while decompressed, read instead of proven
+    pub target_node_account: Option<Account<'info, NodeAccount>>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
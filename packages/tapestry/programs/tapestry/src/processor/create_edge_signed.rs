@@ -0,0 +1,165 @@
+use crate::constants::{ACTION_NONCE_SEED, CPI_AUTHORITY_SEED};
+use crate::state::{
+    ActionNonce, AddressSeed, EdgeArgs, EdgeDataV2, EdgeV2, EndpointNode, NodeAccount,
+    NodeUpdateAuthority, ProvenNode, SignedAction, TokenGate,
+};
+use crate::utils::new_compressed_account;
+use crate::utils::validate_merkle_trees;
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::token::TokenAccount;
+use light_sdk::merkle_context::{PackedAddressMerkleContext, PackedMerkleOutputContext};
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_new_account;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+/// Creates an edge for an owner who signed the action off chain, so a relayer
+/// can pay the fees. Edges to a node prove the target node and its token gate
+/// like `create_edge`, node counters go through `create_edge`.
+pub fn create_edge_signed<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateEdgeSigned<'info>>,
+    proof: AnchorCompressedProof,
+    address_merkle_tree_root_index: u16,
+    address_seed: AddressSeed,
+    edge_args: EdgeArgs,
+    signed_action: SignedAction,
+    target: Option<ProvenNode>,
+) -> Result<()> {
+    let merkle_output_context = PackedMerkleOutputContext {
+        merkle_tree_pubkey_index: 0,
+    };
+    let address_merkle_context = PackedAddressMerkleContext {
+        address_merkle_tree_pubkey_index: 1,
+        address_queue_pubkey_index: 2,
+    };
+    // The target node is proven as an input, which needs the nullifier queue
+    let nullifier_queue_pubkey_index = target.is_some().then_some(3);
+    validate_merkle_trees(
+        0,
+        Some(1),
+        Some(2),
+        nullifier_queue_pubkey_index,
+        ctx.remaining_accounts,
+    )?;
+
+    let owner = ctx.accounts.owner.key();
+    let update_authority = ctx
+        .accounts
+        .update_authority
+        .as_ref()
+        .map(|update_authority| update_authority.key());
+    let args = (&address_seed, &edge_args, update_authority)
+        .try_to_vec()
+        .unwrap();
+    signed_action.verify(
+        &mut ctx.accounts.action_nonce,
+        &ctx.accounts.instructions_sysvar,
+        "create_edge",
+        &owner,
+        &args,
+    )?;
+
+    edge_args.validate()?;
+    let edge_seed = address_seed.edge_seed(&edge_args)?;
+
+    // Check the token gate declared by the target node, the owner is
+    // authenticated by the signed action
+    let target =
+        EndpointNode::from_accounts(target.as_ref(), ctx.accounts.target_node_account.as_deref())?;
+    let token_gate = TokenGate::of_target(&edge_args.target_node, target.as_ref())?;
+    token_gate.verify(
+        &owner,
+        ctx.accounts.gate_token_account.as_deref(),
+        ctx.accounts.gate_metadata.as_deref(),
+    )?;
+    let target_compressed_accounts = target
+        .as_ref()
+        .map(EndpointNode::proven_compressed_accounts)
+        .transpose()?
+        .flatten();
+
+    // Create the edge data from properties
+    let edge_data = EdgeDataV2::new(&edge_args.properties, token_gate, false);
+
+    // Create the edge
+    let edge = EdgeV2::new(
+        edge_args,
+        edge_data,
+        owner,
+        NodeUpdateAuthority::from_signer(update_authority),
+    );
+
+    let (edge_compressed_account, edge_new_address_params) = new_compressed_account(
+        &edge,
+        &edge_seed,
+        &crate::ID,
+        &merkle_output_context,
+        &address_merkle_context,
+        address_merkle_tree_root_index,
+        ctx.remaining_accounts,
+    )?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Create account
+    let mut cpi_inputs = create_cpi_inputs_for_new_account(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        edge_new_address_params,
+        edge_compressed_account,
+        None,
+    );
+    if let Some((target_input, target_output)) = target_compressed_accounts {
+        cpi_inputs
+            .input_compressed_accounts_with_merkle_context
+            .push(target_input);
+        cpi_inputs.output_compressed_accounts.push(target_output);
+    }
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct CreateEdgeSigned<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    pub update_authority: Option<Signer<'info>>,
+    /// CHECK: Signs the action in the ed25519 instruction.
+    pub owner: UncheckedAccount<'info>,
+    // Holding checked against the token gate of the target node
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    // Metadata of the held NFT, for collection gates
+    pub gate_metadata: Option<Account<'info, MetadataAccount>>,
+    // Target node while decompressed, read instead of proven
+    pub target_node_account: Option<Account<'info, NodeAccount>>,
+    #[account(
+        mut,
+        seeds = [ACTION_NONCE_SEED.as_bytes(), owner.key().as_ref()],
+        bump = action_nonce.bump
+    )]
+    pub action_nonce: Account<'info, ActionNonce>,
+    /// CHECK: Address is checked against the instructions sysvar.
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
@@ -0,0 +1,126 @@
+use crate::constants::{CONFIG_SEED, CPI_AUTHORITY_SEED};
+use crate::state::{
+    AccountKey, EndpointNode, ExternalIdentity, IdentityLinkV1, NodeAccount, ProvenNode,
+    TapestryConfig,
+};
+use crate::utils::validate_merkle_trees;
+use crate::utils::{get_identity_link_seed, new_compressed_account, verify_ed25519_signature};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use light_sdk::merkle_context::{PackedAddressMerkleContext, PackedMerkleOutputContext};
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_new_account;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+// Permissionless: the attestation authority signs the node and identity in an
+// ed25519 instruction right before this one, after checking off chain that the
+// node's owner controls the external account. The node is proven, so links
+// only ever point at nodes that exist.
+pub fn create_identity_link<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateIdentityLink<'info>>,
+    proof: AnchorCompressedProof,
+    address_merkle_tree_root_index: u16,
+    node: Option<ProvenNode>,
+    identity: ExternalIdentity,
+) -> Result<()> {
+    let merkle_output_context = PackedMerkleOutputContext {
+        merkle_tree_pubkey_index: 0,
+    };
+    let address_merkle_context = PackedAddressMerkleContext {
+        address_merkle_tree_pubkey_index: 1,
+        address_queue_pubkey_index: 2,
+    };
+    // A compressed node is proven as an input, which needs the nullifier queue
+    let nullifier_queue_pubkey_index = node.is_some().then_some(3);
+    validate_merkle_trees(
+        0,
+        Some(1),
+        Some(2),
+        nullifier_queue_pubkey_index,
+        ctx.remaining_accounts,
+    )?;
+
+    let node = EndpointNode::required(node.as_ref(), ctx.accounts.node_account.as_deref())?;
+    let node_compressed_accounts = node.proven_compressed_accounts()?;
+    let node = node.address();
+
+    let attestation_authority = ctx.accounts.config.attestation_authority;
+    verify_ed25519_signature(
+        &ctx.accounts.instructions_sysvar,
+        &attestation_authority,
+        &IdentityLinkV1::attestation_message(&node, &identity),
+    )?;
+
+    let identity_link_seed = get_identity_link_seed(&identity);
+    let identity_link = IdentityLinkV1 {
+        key: AccountKey::IdentityLinkV1,
+        node,
+        identity,
+        attestation_authority,
+    };
+    let (identity_link_compressed_account, identity_link_new_address_params) =
+        new_compressed_account(
+            &identity_link,
+            &identity_link_seed,
+            &crate::ID,
+            &merkle_output_context,
+            &address_merkle_context,
+            address_merkle_tree_root_index,
+            ctx.remaining_accounts,
+        )?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Create account
+    let mut cpi_inputs = create_cpi_inputs_for_new_account(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        identity_link_new_address_params,
+        identity_link_compressed_account,
+        None,
+    );
+    if let Some((node_input, node_output)) = node_compressed_accounts {
+        cpi_inputs
+            .input_compressed_accounts_with_merkle_context
+            .push(node_input);
+        cpi_inputs.output_compressed_accounts.push(node_output);
+    }
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct CreateIdentityLink<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, TapestryConfig>,
+    // Linked node while decompressed, read instead of proven
+    pub node_account: Option<Account<'info, NodeAccount>>,
+    /// CHECK: Address is checked against the instructions sysvar.
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
@@ -1,7 +1,7 @@
 use crate::constants::CPI_AUTHORITY_SEED;
-use crate::state::{AccountKey, NodeArgs, NodeData, NodeUpdateAuthority, NodeV1};
+use crate::state::{AddressSeed, NodeArgs, NodeUpdateAuthority, NodeV2};
+use crate::utils::new_compressed_account;
 use crate::utils::validate_merkle_trees;
-use crate::utils::{get_account_seed, new_compressed_account};
 use crate::AnchorCompressedProof;
 use anchor_lang::prelude::*;
 use light_sdk::merkle_context::{PackedAddressMerkleContext, PackedMerkleOutputContext};
@@ -14,7 +14,7 @@ pub fn create_node<'info>(
     ctx: Context<'_, '_, '_, 'info, CreateNode<'info>>,
     proof: AnchorCompressedProof,
     address_merkle_tree_root_index: u16,
-    random_bytes: [u8; 32],
+    address_seed: AddressSeed,
     node_args: NodeArgs,
 ) -> Result<()> {
     let merkle_output_context = PackedMerkleOutputContext {
@@ -26,24 +26,21 @@ pub fn create_node<'info>(
     };
     validate_merkle_trees(0, Some(1), Some(2), None, ctx.remaining_accounts)?;
 
-    // Create the node data from properties and creators
-    let node_data = NodeData::new(&node_args.properties, &node_args.creators);
+    node_args.validate()?;
+    let node_seed = address_seed.node_seed(&node_args)?;
 
     // Create the node
-    let node = NodeV1 {
-        key: AccountKey::NodeV1,
-        owner: ctx.accounts.owner.key(),
-        update_authority: match &ctx.accounts.update_authority {
-            Some(update_authority) => NodeUpdateAuthority::Address(update_authority.key()),
-            None => NodeUpdateAuthority::None,
-        },
-        label: node_args.label,
-        node_data,
-        is_mutable: node_args.is_mutable,
-        initialized_plugins: 0,
-    };
+    let update_authority = ctx
+        .accounts
+        .update_authority
+        .as_ref()
+        .map(|update_authority| update_authority.key());
+    let node = NodeV2::new(
+        node_args,
+        ctx.accounts.owner.key(),
+        NodeUpdateAuthority::from_signer(update_authority),
+    );
 
-    let node_seed = get_account_seed(AccountKey::NodeV1, &random_bytes);
     let (node_compressed_account, node_new_address_params) = new_compressed_account(
         &node,
         &node_seed,
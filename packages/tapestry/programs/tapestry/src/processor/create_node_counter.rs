@@ -0,0 +1,86 @@
+use crate::constants::CPI_AUTHORITY_SEED;
+use crate::state::{AccountKey, NodeCounterV1};
+use crate::utils::validate_merkle_trees;
+use crate::utils::{get_node_counter_seed, new_compressed_account};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_sdk::merkle_context::{PackedAddressMerkleContext, PackedMerkleOutputContext};
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_new_account;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+// Permissionless: counters start at zero and only count edges created with
+// them (see `NodeCounterV1`), the address makes sure there is a single counter
+// per node and type
+pub fn create_node_counter<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateNodeCounter<'info>>,
+    proof: AnchorCompressedProof,
+    address_merkle_tree_root_index: u16,
+    node: String,
+    edge_type: String,
+) -> Result<()> {
+    let merkle_output_context = PackedMerkleOutputContext {
+        merkle_tree_pubkey_index: 0,
+    };
+    let address_merkle_context = PackedAddressMerkleContext {
+        address_merkle_tree_pubkey_index: 1,
+        address_queue_pubkey_index: 2,
+    };
+    validate_merkle_trees(0, Some(1), Some(2), None, ctx.remaining_accounts)?;
+
+    let counter_seed = get_node_counter_seed(&node, &edge_type);
+    let counter = NodeCounterV1 {
+        key: AccountKey::NodeCounterV1,
+        node,
+        edge_type,
+        counted_in_edges: 0,
+        counted_out_edges: 0,
+    };
+    let (counter_compressed_account, counter_new_address_params) = new_compressed_account(
+        &counter,
+        &counter_seed,
+        &crate::ID,
+        &merkle_output_context,
+        &address_merkle_context,
+        address_merkle_tree_root_index,
+        ctx.remaining_accounts,
+    )?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Create account
+    let cpi_inputs = create_cpi_inputs_for_new_account(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        counter_new_address_params,
+        counter_compressed_account,
+        None,
+    );
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct CreateNodeCounter<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
@@ -0,0 +1,119 @@
+use crate::constants::{ACTION_NONCE_SEED, CPI_AUTHORITY_SEED};
+use crate::state::{ActionNonce, AddressSeed, NodeArgs, NodeUpdateAuthority, NodeV2, SignedAction};
+use crate::utils::new_compressed_account;
+use crate::utils::validate_merkle_trees;
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use light_sdk::merkle_context::{PackedAddressMerkleContext, PackedMerkleOutputContext};
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_new_account;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+/// Creates a node for an owner who signed the action off chain, so a relayer
+/// can pay the fees. The owner signs the address seed, the node arguments and
+/// the update authority.
+pub fn create_node_signed<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateNodeSigned<'info>>,
+    proof: AnchorCompressedProof,
+    address_merkle_tree_root_index: u16,
+    address_seed: AddressSeed,
+    node_args: NodeArgs,
+    signed_action: SignedAction,
+) -> Result<()> {
+    let merkle_output_context = PackedMerkleOutputContext {
+        merkle_tree_pubkey_index: 0,
+    };
+    let address_merkle_context = PackedAddressMerkleContext {
+        address_merkle_tree_pubkey_index: 1,
+        address_queue_pubkey_index: 2,
+    };
+    validate_merkle_trees(0, Some(1), Some(2), None, ctx.remaining_accounts)?;
+
+    let owner = ctx.accounts.owner.key();
+    let update_authority = ctx
+        .accounts
+        .update_authority
+        .as_ref()
+        .map(|update_authority| update_authority.key());
+    let args = (&address_seed, &node_args, update_authority)
+        .try_to_vec()
+        .unwrap();
+    signed_action.verify(
+        &mut ctx.accounts.action_nonce,
+        &ctx.accounts.instructions_sysvar,
+        "create_node",
+        &owner,
+        &args,
+    )?;
+
+    node_args.validate()?;
+    let node_seed = address_seed.node_seed(&node_args)?;
+
+    // Create the node
+    let node = NodeV2::new(
+        node_args,
+        owner,
+        NodeUpdateAuthority::from_signer(update_authority),
+    );
+
+    let (node_compressed_account, node_new_address_params) = new_compressed_account(
+        &node,
+        &node_seed,
+        &crate::ID,
+        &merkle_output_context,
+        &address_merkle_context,
+        address_merkle_tree_root_index,
+        ctx.remaining_accounts,
+    )?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Create account
+    let cpi_inputs = create_cpi_inputs_for_new_account(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        node_new_address_params,
+        node_compressed_account,
+        None,
+    );
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct CreateNodeSigned<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    pub update_authority: Option<Signer<'info>>,
+    /// CHECK: Signs the action in the ed25519 instruction.
+    pub owner: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [ACTION_NONCE_SEED.as_bytes(), owner.key().as_ref()],
+        bump = action_nonce.bump
+    )]
+    pub action_nonce: Account<'info, ActionNonce>,
+    /// CHECK: Address is checked against the instructions sysvar.
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
@@ -0,0 +1,197 @@
+use crate::constants::CPI_AUTHORITY_SEED;
+use crate::errors::ZkNftError;
+use crate::state::{
+    AccountKey, EdgeArgs, EdgeDataV2, EdgeDirection, EdgeV2, EndpointNode, NodeAccount,
+    NodeUpdateAuthority, ProvenNode,
+};
+use crate::utils::validate_merkle_trees;
+use crate::utils::{get_undirected_edge_seed, new_compressed_account};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::token::TokenAccount;
+use light_sdk::merkle_context::{PackedAddressMerkleContext, PackedMerkleOutputContext};
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_new_account;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+pub fn create_undirected_edge<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateUndirectedEdge<'info>>,
+    proof: AnchorCompressedProof,
+    address_merkle_tree_root_index: u16,
+    edge_args: EdgeArgs,
+    source: Option<ProvenNode>,
+    target: Option<ProvenNode>,
+) -> Result<()> {
+    let merkle_output_context = PackedMerkleOutputContext {
+        merkle_tree_pubkey_index: 0,
+    };
+    let address_merkle_context = PackedAddressMerkleContext {
+        address_merkle_tree_pubkey_index: 1,
+        address_queue_pubkey_index: 2,
+    };
+    // Compressed endpoint nodes are proven as inputs, which needs the nullifier
+    // queue
+    let nullifier_queue_pubkey_index = (source.is_some() || target.is_some()).then_some(3);
+    validate_merkle_trees(
+        0,
+        Some(1),
+        Some(2),
+        nullifier_queue_pubkey_index,
+        ctx.remaining_accounts,
+    )?;
+
+    edge_args.validate()?;
+    let source =
+        EndpointNode::required(source.as_ref(), ctx.accounts.source_node_account.as_deref())?;
+    let target =
+        EndpointNode::required(target.as_ref(), ctx.accounts.target_node_account.as_deref())?;
+
+    // Each side of the edge must be signed for by the owner of its endpoint
+    source.require_endpoint(&edge_args.source_node)?;
+    target.require_endpoint(&edge_args.target_node)?;
+    require_keys_eq!(
+        source.latest().owner,
+        ctx.accounts.owner.key(),
+        ZkNftError::InvalidAuthority
+    );
+    require_keys_eq!(
+        target.latest().owner,
+        ctx.accounts.counterparty.key(),
+        ZkNftError::InvalidAuthority
+    );
+
+    // The edge connects both ways, so each side has to satisfy the token gate
+    // of the other side's node. The edge records the target's gate, like a
+    // directed edge.
+    let token_gate = target.latest().token_gate;
+    token_gate.verify(
+        &ctx.accounts.owner.key(),
+        ctx.accounts.gate_token_account.as_deref(),
+        ctx.accounts.gate_metadata.as_deref(),
+    )?;
+    source.latest().token_gate.verify(
+        &ctx.accounts.counterparty.key(),
+        ctx.accounts.counterparty_gate_token_account.as_deref(),
+        ctx.accounts.counterparty_gate_metadata.as_deref(),
+    )?;
+
+    let endpoint_compressed_accounts = [
+        source.proven_compressed_accounts()?,
+        target.proven_compressed_accounts()?,
+    ];
+
+    // The address only depends on the unordered pair, so a second edge between
+    // the same nodes fails on address creation
+    let edge_seed = get_undirected_edge_seed(
+        &edge_args.namespace,
+        &edge_args.source_node,
+        &edge_args.target_node,
+        &edge_args.edge_type,
+    );
+
+    // Store endpoints sorted so both sides decode the same edge
+    let (source_node, target_node) = if edge_args.source_node <= edge_args.target_node {
+        (edge_args.source_node, edge_args.target_node)
+    } else {
+        (edge_args.target_node, edge_args.source_node)
+    };
+
+    // Create the edge data from properties
+    let edge_data = EdgeDataV2 {
+        counterparty: Some(ctx.accounts.counterparty.key()),
+        ..EdgeDataV2::new(&edge_args.properties, token_gate, false)
+    };
+
+    // Create the edge
+    let update_authority = ctx
+        .accounts
+        .update_authority
+        .as_ref()
+        .map(|update_authority| update_authority.key());
+    let edge = EdgeV2 {
+        key: AccountKey::EdgeV2,
+        source_node,
+        target_node,
+        edge_type: edge_args.edge_type,
+        namespace: edge_args.namespace,
+        direction: EdgeDirection::Undirected,
+        edge_data,
+        weight: edge_args.weight,
+        validity: edge_args.validity,
+        is_mutable: edge_args.is_mutable,
+        owner: ctx.accounts.owner.key(),
+        update_authority: NodeUpdateAuthority::from_signer(update_authority),
+    };
+
+    let (edge_compressed_account, edge_new_address_params) = new_compressed_account(
+        &edge,
+        &edge_seed,
+        &crate::ID,
+        &merkle_output_context,
+        &address_merkle_context,
+        address_merkle_tree_root_index,
+        ctx.remaining_accounts,
+    )?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Create account
+    let mut cpi_inputs = create_cpi_inputs_for_new_account(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        edge_new_address_params,
+        edge_compressed_account,
+        None,
+    );
+    for (input, output) in endpoint_compressed_accounts.into_iter().flatten() {
+        cpi_inputs
+            .input_compressed_accounts_with_merkle_context
+            .push(input);
+        cpi_inputs.output_compressed_accounts.push(output);
+    }
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct CreateUndirectedEdge<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    pub update_authority: Option<Signer<'info>>,
+    /// Owner of the source node, recorded as the edge owner.
+    pub owner: Signer<'info>,
+    /// Owner of the target node, co-signing to approve the edge and recorded
+    /// in the edge data.
+    pub counterparty: Signer<'info>,
+    // Holding checked against the token gate of the target node
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    // Metadata of the held NFT, for collection gates
+    pub gate_metadata: Option<Account<'info, MetadataAccount>>,
+    // Holding of the counterparty checked against the token gate of the source
+    // node, and its NFT metadata
+    pub counterparty_gate_token_account: Option<Account<'info, TokenAccount>>,
+    pub counterparty_gate_metadata: Option<Account<'info, MetadataAccount>>,
+    // Endpoint nodes while decompressed, read instead of proven
+    pub source_node_account: Option<Account<'info, NodeAccount>>,
+    pub target_node_account: Option<Account<'info, NodeAccount>>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
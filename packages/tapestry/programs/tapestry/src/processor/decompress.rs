@@ -0,0 +1,214 @@
+use crate::constants::{CPI_AUTHORITY_SEED, NODE_ACCOUNT_SEED};
+use crate::errors::ZkNftError;
+use crate::state::{
+    AccountKey, AnchorCompressedAccountMeta, DecompressedNodeV1, GroupAuthority, NodeAccount,
+    VersionedNode,
+};
+use crate::utils::validate_merkle_trees;
+use crate::utils::{input_compressed_account, output_compressed_account};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_account_update;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+// Moves a node into a `NodeAccount` PDA, leaving a placeholder at its
+// compressed address that keeps any lamports the node holds. The node is
+// always stored in the latest layout.
+pub fn decompress_node<'info>(
+    ctx: Context<'_, '_, '_, 'info, DecompressNode<'info>>,
+    proof: AnchorCompressedProof,
+    node: VersionedNode,
+    node_meta: AnchorCompressedAccountMeta,
+    group: Option<GroupAuthority>,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let node_input_compressed_account = node.input_compressed_account(&node_meta)?;
+    let node = node.into_latest();
+
+    node.update_authority.validate_update(
+        &node.owner,
+        &ctx.accounts.authority.key(),
+        ctx.remaining_accounts,
+        group.as_ref(),
+    )?;
+
+    let placeholder = DecompressedNodeV1 {
+        key: AccountKey::DecompressedNodeV1,
+        node_account: ctx.accounts.node_account.key(),
+    };
+    let mut placeholder_output_compressed_account = output_compressed_account(
+        &placeholder,
+        &node_meta.address,
+        &crate::ID,
+        &node_meta.merkle_context(),
+    )?;
+    placeholder_output_compressed_account
+        .compressed_account
+        .lamports = node_meta.lamports;
+
+    let node_account = &mut ctx.accounts.node_account;
+    node_account.address = node_meta.address;
+    node_account.node = node;
+    node_account.rent_payer = ctx.accounts.payer.key();
+    node_account.bump = ctx.bumps.node_account;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Replace the node with the placeholder
+    let mut cpi_inputs = create_cpi_inputs_for_account_update(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        node_input_compressed_account,
+        placeholder_output_compressed_account,
+        None,
+    );
+    if let Some(group) = &group {
+        let (group_input, group_output) = group.proven_compressed_accounts()?;
+        cpi_inputs
+            .input_compressed_accounts_with_merkle_context
+            .push(group_input);
+        cpi_inputs.output_compressed_accounts.push(group_output);
+    }
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+// Moves a decompressed node back to its compressed address and closes the PDA,
+// refunding the rent to whoever paid it on decompression
+pub fn recompress_node<'info>(
+    ctx: Context<'_, '_, '_, 'info, RecompressNode<'info>>,
+    proof: AnchorCompressedProof,
+    placeholder_meta: AnchorCompressedAccountMeta,
+    group: Option<GroupAuthority>,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let node_account = &ctx.accounts.node_account;
+    require!(
+        placeholder_meta.address == node_account.address,
+        ZkNftError::NodeAccountMismatch
+    );
+    node_account.node.update_authority.validate_update(
+        &node_account.node.owner,
+        &ctx.accounts.authority.key(),
+        ctx.remaining_accounts,
+        group.as_ref(),
+    )?;
+
+    let merkle_context = placeholder_meta.merkle_context();
+    let placeholder = DecompressedNodeV1 {
+        key: AccountKey::DecompressedNodeV1,
+        node_account: node_account.key(),
+    };
+    let mut placeholder_input_compressed_account = input_compressed_account(
+        &placeholder,
+        &placeholder_meta.address,
+        &crate::ID,
+        &merkle_context,
+        placeholder_meta.root_index,
+    )?;
+    placeholder_input_compressed_account
+        .compressed_account
+        .lamports = placeholder_meta.lamports;
+    let mut node_output_compressed_account = output_compressed_account(
+        &node_account.node,
+        &placeholder_meta.address,
+        &crate::ID,
+        &merkle_context,
+    )?;
+    node_output_compressed_account.compressed_account.lamports = placeholder_meta.lamports;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Replace the placeholder with the node
+    let mut cpi_inputs = create_cpi_inputs_for_account_update(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        placeholder_input_compressed_account,
+        node_output_compressed_account,
+        None,
+    );
+    if let Some(group) = &group {
+        let (group_input, group_output) = group.proven_compressed_accounts()?;
+        cpi_inputs
+            .input_compressed_accounts_with_merkle_context
+            .push(group_input);
+        cpi_inputs.output_compressed_accounts.push(group_output);
+    }
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+#[instruction(proof: AnchorCompressedProof, node: VersionedNode, node_meta: AnchorCompressedAccountMeta)]
+pub struct DecompressNode<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    // Update authority, or the owner when there is none
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = NodeAccount::space(&node.clone().into_latest()),
+        seeds = [NODE_ACCOUNT_SEED.as_bytes(), node_meta.address.as_ref()],
+        bump
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct RecompressNode<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    // Update authority, or the owner when there is none
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [NODE_ACCOUNT_SEED.as_bytes(), node_account.address.as_ref()],
+        bump = node_account.bump
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+    /// CHECK: Checked against the rent payer stored in the node account.
+    #[account(mut, address = node_account.rent_payer @ ZkNftError::InvalidRentPayer)]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
@@ -0,0 +1,30 @@
+use crate::constants::ACTION_NONCE_SEED;
+use crate::state::ActionNonce;
+use anchor_lang::prelude::*;
+
+// Permissionless: nonces start at zero and only move with the signer's
+// signature, so a relayer can set one up before the first signed action
+pub fn initialize_action_nonce(ctx: Context<InitializeActionNonce>) -> Result<()> {
+    let action_nonce = &mut ctx.accounts.action_nonce;
+    action_nonce.signer = ctx.accounts.signer.key();
+    action_nonce.nonce = 0;
+    action_nonce.bump = ctx.bumps.action_nonce;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeActionNonce<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: This can be any valid public key.
+    pub signer: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = ActionNonce::SPACE,
+        seeds = [ACTION_NONCE_SEED.as_bytes(), signer.key().as_ref()],
+        bump
+    )]
+    pub action_nonce: Account<'info, ActionNonce>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,123 @@
+use crate::constants::CPI_AUTHORITY_SEED;
+use crate::state::{AnchorCompressedAccountMeta, EdgeV1, EdgeV2, NodeV1, NodeV2};
+use crate::utils::validate_merkle_trees;
+use crate::utils::{input_compressed_account, output_compressed_account};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_account_update;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+// Permissionless: migration only rewrites the layout, every field the account
+// carries is preserved and the address stays the same
+pub fn migrate_node_v1_to_v2<'info>(
+    ctx: Context<'_, '_, '_, 'info, Migrate<'info>>,
+    proof: AnchorCompressedProof,
+    node: NodeV1,
+    node_meta: AnchorCompressedAccountMeta,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let merkle_context = node_meta.merkle_context();
+    let node_input_compressed_account = input_compressed_account(
+        &node,
+        &node_meta.address,
+        &crate::ID,
+        &merkle_context,
+        node_meta.root_index,
+    )?;
+
+    let migrated_node = NodeV2::from(node);
+    migrated_node.node_data.validate()?;
+    let node_output_compressed_account = output_compressed_account(
+        &migrated_node,
+        &node_meta.address,
+        &crate::ID,
+        &merkle_context,
+    )?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Update account
+    let cpi_inputs = create_cpi_inputs_for_account_update(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        node_input_compressed_account,
+        node_output_compressed_account,
+        None,
+    );
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+// Permissionless, see `migrate_node_v1_to_v2`
+pub fn migrate_edge_v1_to_v2<'info>(
+    ctx: Context<'_, '_, '_, 'info, Migrate<'info>>,
+    proof: AnchorCompressedProof,
+    edge: EdgeV1,
+    edge_meta: AnchorCompressedAccountMeta,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let merkle_context = edge_meta.merkle_context();
+    let edge_input_compressed_account = input_compressed_account(
+        &edge,
+        &edge_meta.address,
+        &crate::ID,
+        &merkle_context,
+        edge_meta.root_index,
+    )?;
+
+    let migrated_edge = EdgeV2::from(edge);
+    migrated_edge.edge_data.validate()?;
+    let edge_output_compressed_account = output_compressed_account(
+        &migrated_edge,
+        &edge_meta.address,
+        &crate::ID,
+        &merkle_context,
+    )?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Update account
+    let cpi_inputs = create_cpi_inputs_for_account_update(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        edge_input_compressed_account,
+        edge_output_compressed_account,
+        None,
+    );
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct Migrate<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
@@ -1,5 +1,55 @@
+pub use accept_edge::*;
+pub use adjust_edge_weight::*;
+pub use adjust_edge_weight_signed::*;
+pub use assert_edge::*;
+pub use cancel_edge_request::*;
+pub use config::*;
 pub use create_edge::*;
+pub use create_edge_cpi::*;
+pub use create_edge_signed::*;
+pub use create_identity_link::*;
 pub use create_node::*;
+pub use create_node_counter::*;
+pub use create_node_signed::*;
+pub use create_undirected_edge::*;
+pub use decompress::*;
+pub use initialize_action_nonce::*;
+pub use make_immutable::*;
+pub use make_immutable_signed::*;
+pub use migrate::*;
+pub use prune_expired_edge::*;
+pub use reject_edge::*;
+pub use request_edge::*;
+pub use revoke_identity_link::*;
+pub use set_update_authority::*;
+pub use set_update_authority_signed::*;
+pub use tip_node::*;
+pub use withdraw_from_node::*;
 
+mod accept_edge;
+mod adjust_edge_weight;
+mod adjust_edge_weight_signed;
+mod assert_edge;
+mod cancel_edge_request;
+mod config;
 mod create_edge;
+mod create_edge_cpi;
+mod create_edge_signed;
+mod create_identity_link;
 mod create_node;
+mod create_node_counter;
+mod create_node_signed;
+mod create_undirected_edge;
+mod decompress;
+mod initialize_action_nonce;
+mod make_immutable;
+mod make_immutable_signed;
+mod migrate;
+mod prune_expired_edge;
+mod reject_edge;
+mod request_edge;
+mod revoke_identity_link;
+mod set_update_authority;
+mod set_update_authority_signed;
+mod tip_node;
+mod withdraw_from_node;
@@ -0,0 +1,82 @@
+use crate::constants::CPI_AUTHORITY_SEED;
+use crate::errors::ZkNftError;
+use crate::state::{AnchorCompressedAccountMeta, EdgeCounters, VersionedEdge};
+use crate::utils::validate_merkle_trees;
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_account_deletion;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+// Permissionless: anyone may nullify an edge once its validity window has lapsed
+pub fn prune_expired_edge<'info>(
+    ctx: Context<'_, '_, '_, 'info, PruneExpiredEdge<'info>>,
+    proof: AnchorCompressedProof,
+    edge: VersionedEdge,
+    edge_meta: AnchorCompressedAccountMeta,
+    counters: Option<EdgeCounters>,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let edge_compressed_account = edge.input_compressed_account(&edge_meta)?;
+
+    let edge = edge.into_latest();
+    let clock = Clock::get()?;
+    require!(
+        edge.validity.is_expired(clock.unix_timestamp),
+        ZkNftError::EdgeNotExpired
+    );
+
+    // A counted edge must be uncounted from both nodes when it is pruned
+    let (counter_inputs, counter_outputs) = match (&counters, edge.edge_data.counted) {
+        (Some(counters), true) => {
+            counters.require_matches(&edge.source_node, &edge.target_node, &edge.edge_type)?;
+            counters.compressed_accounts(-1)?
+        }
+        (None, true) => return err!(ZkNftError::MissingCounters),
+        (_, false) => (Vec::new(), Vec::new()),
+    };
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Nullify the edge
+    let mut cpi_inputs = create_cpi_inputs_for_account_deletion(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        edge_compressed_account,
+        None,
+    );
+    cpi_inputs
+        .input_compressed_accounts_with_merkle_context
+        .extend(counter_inputs);
+    cpi_inputs
+        .output_compressed_accounts
+        .extend(counter_outputs);
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct PruneExpiredEdge<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
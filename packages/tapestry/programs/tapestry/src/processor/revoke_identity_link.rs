@@ -0,0 +1,74 @@
+use crate::constants::{CONFIG_SEED, CPI_AUTHORITY_SEED};
+use crate::state::{AnchorCompressedAccountMeta, IdentityLinkV1, TapestryConfig};
+use crate::utils::input_compressed_account;
+use crate::utils::validate_merkle_trees;
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_account_deletion;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+// The current attestation authority can revoke any link, including ones its
+// predecessors attested, e.g. when an attestation turns out to be wrong.
+// Addresses can't be created twice, so a revoked identity can't be linked
+// again.
+pub fn revoke_identity_link<'info>(
+    ctx: Context<'_, '_, '_, 'info, RevokeIdentityLink<'info>>,
+    proof: AnchorCompressedProof,
+    identity_link: IdentityLinkV1,
+    identity_link_meta: AnchorCompressedAccountMeta,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let identity_link_compressed_account = input_compressed_account(
+        &identity_link,
+        &identity_link_meta.address,
+        &crate::ID,
+        &identity_link_meta.merkle_context(),
+        identity_link_meta.root_index,
+    )?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Nullify the link
+    let cpi_inputs = create_cpi_inputs_for_account_deletion(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        identity_link_compressed_account,
+        None,
+    );
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct RevokeIdentityLink<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    pub attestation_authority: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+        has_one = attestation_authority
+    )]
+    pub config: Account<'info, TapestryConfig>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
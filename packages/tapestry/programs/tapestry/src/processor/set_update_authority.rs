@@ -0,0 +1,194 @@
+use crate::constants::CPI_AUTHORITY_SEED;
+use crate::errors::ZkNftError;
+use crate::state::{
+    AnchorCompressedAccountMeta, EdgeV2, GroupAuthority, NodeUpdateAuthority, NodeV2,
+    VersionedEdge, VersionedNode,
+};
+use crate::utils::{update_cpi_inputs, validate_merkle_trees};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+// Rotates the update authority of a mutable node, signed by the current one
+pub fn set_node_update_authority<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetUpdateAuthority<'info>>,
+    proof: AnchorCompressedProof,
+    node: VersionedNode,
+    node_meta: AnchorCompressedAccountMeta,
+    new_update_authority: NodeUpdateAuthority,
+    group: Option<GroupAuthority>,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let node_input_compressed_account = node.input_compressed_account(&node_meta)?;
+    let node = node.into_latest();
+
+    require!(node.is_mutable, ZkNftError::NodeNotMutable);
+    node.update_authority.validate_update(
+        &node.owner,
+        &ctx.accounts.authority.key(),
+        ctx.remaining_accounts,
+        group.as_ref(),
+    )?;
+
+    new_update_authority.validate_threshold()?;
+    let updated_node = NodeV2 {
+        update_authority: new_update_authority,
+        ..node
+    };
+    let cpi_inputs = update_cpi_inputs(
+        proof,
+        node_input_compressed_account,
+        &updated_node,
+        &node_meta,
+        group.as_ref(),
+    )?;
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+// Removes the update authority for good, which also freezes the node
+pub fn revoke_node_update_authority<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetUpdateAuthority<'info>>,
+    proof: AnchorCompressedProof,
+    node: VersionedNode,
+    node_meta: AnchorCompressedAccountMeta,
+    group: Option<GroupAuthority>,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let node_input_compressed_account = node.input_compressed_account(&node_meta)?;
+    let node = node.into_latest();
+
+    require!(node.is_mutable, ZkNftError::NodeNotMutable);
+    node.update_authority.validate_update(
+        &node.owner,
+        &ctx.accounts.authority.key(),
+        ctx.remaining_accounts,
+        group.as_ref(),
+    )?;
+
+    let updated_node = NodeV2 {
+        update_authority: NodeUpdateAuthority::None,
+        is_mutable: false,
+        ..node
+    };
+    let cpi_inputs = update_cpi_inputs(
+        proof,
+        node_input_compressed_account,
+        &updated_node,
+        &node_meta,
+        group.as_ref(),
+    )?;
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+// Rotates the update authority of a mutable edge, see `set_node_update_authority`
+pub fn set_edge_update_authority<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetUpdateAuthority<'info>>,
+    proof: AnchorCompressedProof,
+    edge: VersionedEdge,
+    edge_meta: AnchorCompressedAccountMeta,
+    new_update_authority: NodeUpdateAuthority,
+    group: Option<GroupAuthority>,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let edge_input_compressed_account = edge.input_compressed_account(&edge_meta)?;
+    let edge = edge.into_latest();
+
+    require!(edge.is_mutable, ZkNftError::EdgeNotMutable);
+    edge.update_authority.validate_update(
+        &edge.owner,
+        &ctx.accounts.authority.key(),
+        ctx.remaining_accounts,
+        group.as_ref(),
+    )?;
+
+    new_update_authority.validate_threshold()?;
+    let updated_edge = EdgeV2 {
+        update_authority: new_update_authority,
+        ..edge
+    };
+    let cpi_inputs = update_cpi_inputs(
+        proof,
+        edge_input_compressed_account,
+        &updated_edge,
+        &edge_meta,
+        group.as_ref(),
+    )?;
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+// Removes the update authority of an edge for good, see
+// `revoke_node_update_authority`
+pub fn revoke_edge_update_authority<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetUpdateAuthority<'info>>,
+    proof: AnchorCompressedProof,
+    edge: VersionedEdge,
+    edge_meta: AnchorCompressedAccountMeta,
+    group: Option<GroupAuthority>,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let edge_input_compressed_account = edge.input_compressed_account(&edge_meta)?;
+    let edge = edge.into_latest();
+
+    require!(edge.is_mutable, ZkNftError::EdgeNotMutable);
+    edge.update_authority.validate_update(
+        &edge.owner,
+        &ctx.accounts.authority.key(),
+        ctx.remaining_accounts,
+        group.as_ref(),
+    )?;
+
+    let updated_edge = EdgeV2 {
+        update_authority: NodeUpdateAuthority::None,
+        is_mutable: false,
+        ..edge
+    };
+    let cpi_inputs = update_cpi_inputs(
+        proof,
+        edge_input_compressed_account,
+        &updated_edge,
+        &edge_meta,
+        group.as_ref(),
+    )?;
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct SetUpdateAuthority<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    // Current update authority, or the owner when there is none
+    pub authority: Signer<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
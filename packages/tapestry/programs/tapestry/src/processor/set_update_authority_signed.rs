@@ -0,0 +1,96 @@
+use crate::constants::{ACTION_NONCE_SEED, CPI_AUTHORITY_SEED};
+use crate::errors::ZkNftError;
+use crate::state::{
+    ActionNonce, AnchorCompressedAccountMeta, GroupAuthority, NodeUpdateAuthority, NodeV2,
+    SignedAction, VersionedNode,
+};
+use crate::utils::{update_cpi_inputs, validate_merkle_trees};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+/// `set_node_update_authority` for an authority who signed the node address
+/// and the new update authority off chain, so a relayer can pay the fees
+pub fn set_node_update_authority_signed<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetUpdateAuthoritySigned<'info>>,
+    proof: AnchorCompressedProof,
+    node: VersionedNode,
+    node_meta: AnchorCompressedAccountMeta,
+    new_update_authority: NodeUpdateAuthority,
+    group: Option<GroupAuthority>,
+    signed_action: SignedAction,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let authority = ctx.accounts.authority.key();
+    let args = (node_meta.address, &new_update_authority)
+        .try_to_vec()
+        .unwrap();
+    signed_action.verify(
+        &mut ctx.accounts.action_nonce,
+        &ctx.accounts.instructions_sysvar,
+        "set_node_update_authority",
+        &authority,
+        &args,
+    )?;
+
+    let node_input_compressed_account = node.input_compressed_account(&node_meta)?;
+    let node = node.into_latest();
+
+    require!(node.is_mutable, ZkNftError::NodeNotMutable);
+    node.update_authority.validate_update(
+        &node.owner,
+        &authority,
+        ctx.remaining_accounts,
+        group.as_ref(),
+    )?;
+
+    new_update_authority.validate_threshold()?;
+    let updated_node = NodeV2 {
+        update_authority: new_update_authority,
+        ..node
+    };
+    let cpi_inputs = update_cpi_inputs(
+        proof,
+        node_input_compressed_account,
+        &updated_node,
+        &node_meta,
+        group.as_ref(),
+    )?;
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct SetUpdateAuthoritySigned<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    /// CHECK: Signs the action in the ed25519 instruction.
+    pub authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [ACTION_NONCE_SEED.as_bytes(), authority.key().as_ref()],
+        bump = action_nonce.bump
+    )]
+    pub action_nonce: Account<'info, ActionNonce>,
+    /// CHECK: Address is checked against the instructions sysvar.
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
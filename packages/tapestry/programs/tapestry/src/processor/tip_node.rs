@@ -0,0 +1,125 @@
+use crate::constants::CPI_AUTHORITY_SEED;
+use crate::errors::ZkNftError;
+use crate::state::{
+    AccountKey, AnchorCompressedAccountMeta, DecompressedNodeV1, NodeAccount, VersionedNode,
+};
+use crate::utils::validate_merkle_trees;
+use crate::utils::{input_compressed_account, output_compressed_account};
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_account_update;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+// Permissionless: compresses `lamports` from the payer into the node's
+// compressed account, where only the owner can withdraw them. A decompressed
+// node is tipped through the placeholder at its address, which keeps its
+// lamports until it is recompressed.
+pub fn tip_node<'info>(
+    ctx: Context<'_, '_, '_, 'info, TipNode<'info>>,
+    proof: AnchorCompressedProof,
+    node: Option<VersionedNode>,
+    node_meta: AnchorCompressedAccountMeta,
+    lamports: u64,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let merkle_context = node_meta.merkle_context();
+    let (node_input_compressed_account, mut node_output_compressed_account) =
+        match (node, ctx.accounts.node_account.as_ref()) {
+            (Some(node), None) => {
+                let node_input_compressed_account = node.input_compressed_account(&node_meta)?;
+                let node_output_compressed_account = output_compressed_account(
+                    &node.into_latest(),
+                    &node_meta.address,
+                    &crate::ID,
+                    &merkle_context,
+                )?;
+                (
+                    node_input_compressed_account,
+                    node_output_compressed_account,
+                )
+            }
+            (None, Some(node_account)) => {
+                require!(
+                    node_account.address == node_meta.address,
+                    ZkNftError::NodeAccountMismatch
+                );
+                let placeholder = DecompressedNodeV1 {
+                    key: AccountKey::DecompressedNodeV1,
+                    node_account: node_account.key(),
+                };
+                let mut placeholder_input_compressed_account = input_compressed_account(
+                    &placeholder,
+                    &node_meta.address,
+                    &crate::ID,
+                    &merkle_context,
+                    node_meta.root_index,
+                )?;
+                placeholder_input_compressed_account
+                    .compressed_account
+                    .lamports = node_meta.lamports;
+                let placeholder_output_compressed_account = output_compressed_account(
+                    &placeholder,
+                    &node_meta.address,
+                    &crate::ID,
+                    &merkle_context,
+                )?;
+                (
+                    placeholder_input_compressed_account,
+                    placeholder_output_compressed_account,
+                )
+            }
+            (Some(_), Some(_)) => return err!(ZkNftError::AmbiguousNode),
+            (None, None) => return err!(ZkNftError::MissingNode),
+        };
+    node_output_compressed_account.compressed_account.lamports = node_meta
+        .lamports
+        .checked_add(lamports)
+        .ok_or(ZkNftError::LamportsOverflow)?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Update account, compressing the tip from the payer
+    let mut cpi_inputs = create_cpi_inputs_for_account_update(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        node_input_compressed_account,
+        node_output_compressed_account,
+        None,
+    );
+    cpi_inputs.compress_or_decompress_lamports = Some(lamports);
+    cpi_inputs.is_compress = true;
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct TipNode<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    /// CHECK: Checked in light-system-program.
+    #[account(mut)]
+    pub sol_pool_pda: Option<UncheckedAccount<'info>>,
+    // Node account of a decompressed node, tipped through its placeholder
+    pub node_account: Option<Account<'info, NodeAccount>>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
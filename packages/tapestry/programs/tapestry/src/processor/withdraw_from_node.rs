@@ -0,0 +1,87 @@
+use crate::constants::CPI_AUTHORITY_SEED;
+use crate::errors::ZkNftError;
+use crate::state::{AnchorCompressedAccountMeta, VersionedNode};
+use crate::utils::output_compressed_account;
+use crate::utils::validate_merkle_trees;
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_account_update;
+use light_sdk::verify::verify;
+use light_sdk::{light_system_accounts, LightTraits};
+
+// Decompresses `lamports` held by a node to the recipient, signed by the owner
+pub fn withdraw_from_node<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawFromNode<'info>>,
+    proof: AnchorCompressedProof,
+    node: VersionedNode,
+    node_meta: AnchorCompressedAccountMeta,
+    lamports: u64,
+) -> Result<()> {
+    validate_merkle_trees(0, None, None, Some(1), ctx.remaining_accounts)?;
+
+    let node_input_compressed_account = node.input_compressed_account(&node_meta)?;
+    let node = node.into_latest();
+
+    require!(
+        node.owner == ctx.accounts.owner.key(),
+        ZkNftError::InvalidAuthority
+    );
+
+    let mut node_output_compressed_account = output_compressed_account(
+        &node,
+        &node_meta.address,
+        &crate::ID,
+        &node_meta.merkle_context(),
+    )?;
+    node_output_compressed_account.compressed_account.lamports = node_meta
+        .lamports
+        .checked_sub(lamports)
+        .ok_or(ZkNftError::InsufficientLamports)?;
+
+    let bump = ctx.bumps.cpi_authority_pda;
+    let signer_seeds = [CPI_AUTHORITY_SEED.as_bytes(), &[bump]];
+
+    // Update account, decompressing the withdrawal to the recipient
+    let mut cpi_inputs = create_cpi_inputs_for_account_update(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        node_input_compressed_account,
+        node_output_compressed_account,
+        None,
+    );
+    cpi_inputs.compress_or_decompress_lamports = Some(lamports);
+    cpi_inputs.is_compress = false;
+
+    verify(&ctx, &cpi_inputs, &[&signer_seeds])?;
+
+    Ok(())
+}
+
+#[light_system_accounts]
+#[derive(Accounts, LightTraits)]
+pub struct WithdrawFromNode<'info> {
+    #[account(mut)]
+    #[fee_payer]
+    pub payer: Signer<'info>,
+    pub owner: Signer<'info>,
+    /// CHECK: Checked in light-system-program.
+    #[account(mut)]
+    pub sol_pool_pda: Option<UncheckedAccount<'info>>,
+    /// CHECK: Any account may receive the withdrawn lamports.
+    #[account(mut)]
+    pub decompression_recipient: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Checked in light-system-program.
+    #[authority]
+    #[account(
+        seeds = [CPI_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub cpi_authority_pda: UncheckedAccount<'info>,
+    #[self_program]
+    pub self_program: Program<'info, crate::program::Tapestry>,
+}
@@ -0,0 +1,56 @@
+use crate::errors::ZkNftError;
+use crate::state::{AccountKey, EdgeArgs, NodeArgs};
+use crate::utils::{get_account_seed, get_edge_pair_seed, get_external_id_seed, get_label_seed};
+use anchor_lang::prelude::*;
+
+// How the address of a new node or edge is derived. The program computes the
+// final seed from the account being created, so the uniqueness each variant
+// promises holds whatever the client sends.
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum AddressSeed {
+    // Client-chosen bytes, no uniqueness beyond the bytes themselves
+    Random([u8; 32]),
+    // Nodes only: one node per namespace and label
+    Label,
+    // Edges only: one edge per namespace, source, target and edge type
+    EdgePair,
+    // One account per namespace and identifier in an external system
+    ExternalId(String),
+}
+
+impl AddressSeed {
+    pub fn node_seed(&self, node_args: &NodeArgs) -> Result<[u8; 32]> {
+        match self {
+            AddressSeed::Random(random_bytes) => {
+                Ok(get_account_seed(AccountKey::NodeV1, random_bytes))
+            }
+            AddressSeed::Label => Ok(get_label_seed(&node_args.namespace, &node_args.label)),
+            AddressSeed::EdgePair => Err(error!(ZkNftError::InvalidAddressSeed)),
+            AddressSeed::ExternalId(external_id) => Ok(get_external_id_seed(
+                AccountKey::NodeV1,
+                &node_args.namespace,
+                external_id,
+            )),
+        }
+    }
+
+    pub fn edge_seed(&self, edge_args: &EdgeArgs) -> Result<[u8; 32]> {
+        match self {
+            AddressSeed::Random(random_bytes) => {
+                Ok(get_account_seed(AccountKey::EdgeV1, random_bytes))
+            }
+            AddressSeed::Label => Err(error!(ZkNftError::InvalidAddressSeed)),
+            AddressSeed::EdgePair => Ok(get_edge_pair_seed(
+                &edge_args.namespace,
+                &edge_args.source_node,
+                &edge_args.target_node,
+                &edge_args.edge_type,
+            )),
+            AddressSeed::ExternalId(external_id) => Ok(get_external_id_seed(
+                AccountKey::EdgeV1,
+                &edge_args.namespace,
+                external_id,
+            )),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+use light_sdk::merkle_context::PackedMerkleContext;
+
+// Locates an existing compressed account that is consumed by an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, Default)]
+pub struct AnchorCompressedAccountMeta {
+    pub address: [u8; 32],
+    pub merkle_tree_pubkey_index: u8,
+    pub nullifier_queue_pubkey_index: u8,
+    pub leaf_index: u32,
+    pub root_index: u16,
+    // Compressed lamports held by the account, carried over when it is updated
+    pub lamports: u64,
+}
+
+impl AnchorCompressedAccountMeta {
+    pub fn merkle_context(&self) -> PackedMerkleContext {
+        PackedMerkleContext {
+            merkle_tree_pubkey_index: self.merkle_tree_pubkey_index,
+            nullifier_queue_pubkey_index: self.nullifier_queue_pubkey_index,
+            leaf_index: self.leaf_index,
+            queue_index: None,
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+// Program wide settings, kept in a single PDA at `CONFIG_SEED`
+#[account]
+#[derive(Debug)]
+pub struct TapestryConfig {
+    // Program upgrade authority at the time the config was initialized
+    pub admin: Pubkey,
+    // Signs attestations for identity links
+    pub attestation_authority: Pubkey,
+    pub bump: u8,
+}
+
+impl TapestryConfig {
+    pub const SPACE: usize = 8 + 32 + 32 + 1;
+}
@@ -0,0 +1,33 @@
+use crate::state::{AccountKey, NodeV2};
+use anchor_lang::prelude::*;
+use light_sdk::light_account;
+
+// Left at the node's compressed address while the node lives in a
+// `NodeAccount`, so the address stays taken and no compressed instruction can
+// consume the node until it is recompressed
+#[light_account]
+#[derive(Clone, Debug, Default)]
+pub struct DecompressedNodeV1 {
+    pub key: AccountKey,
+    #[truncate]
+    pub node_account: Pubkey,
+}
+
+// Regular account holding a decompressed node, readable by other programs
+// without an inclusion proof
+#[account]
+#[derive(Debug)]
+pub struct NodeAccount {
+    // Compressed address the node returns to on `recompress_node`
+    pub address: [u8; 32],
+    pub node: NodeV2,
+    // Paid the rent on `decompress_node` and gets it back on `recompress_node`
+    pub rent_payer: Pubkey,
+    pub bump: u8,
+}
+
+impl NodeAccount {
+    pub fn space(node: &NodeV2) -> usize {
+        8 + 32 + node.try_to_vec().unwrap().len() + 32 + 1
+    }
+}
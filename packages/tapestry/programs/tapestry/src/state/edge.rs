@@ -1,8 +1,11 @@
+use crate::constants::{MAX_EDGE_TYPE_LENGTH, MAX_NAMESPACE_LENGTH};
+use crate::errors::ZkNftError;
 use crate::state::node::UpdateAuthority;
-use crate::state::{AccountKey, Properties};
+use crate::state::{AccountKey, Properties, TokenGate};
+use crate::utils::{decode_bytes, property_tree_root};
 use anchor_lang::prelude::*;
-use borsh;
 use light_hasher::bytes::AsByteVec;
+use light_hasher::{Hasher, Poseidon};
 use light_sdk::light_account;
 use light_utils::hash_to_bn254_field_size_be;
 
@@ -13,10 +16,97 @@ pub struct EdgeArgs {
     pub source_node: String,
     /// The target node of the edge
     pub target_node: String,
+    /// The type of the edge, e.g. "follow", "like" or "block"
+    pub edge_type: String,
     /// Properties of the edge
     pub properties: Vec<Properties>,
     /// Whether or not the edge is mutable
     pub is_mutable: bool,
+    /// Numeric weight of the edge, e.g. affinity or trust level
+    pub weight: u64,
+    /// Time window in which the edge is valid
+    pub validity: EdgeValidity,
+    /// Namespace the edge belongs to, may be empty
+    pub namespace: String,
+}
+
+impl EdgeArgs {
+    pub fn validate(&self) -> Result<()> {
+        // Validate that source and target nodes are different
+        if self.source_node == self.target_node {
+            return Err(error!(ZkNftError::SelfReferenceNotAllowed));
+        }
+
+        // Validate the edge type
+        require!(!self.edge_type.is_empty(), ZkNftError::EdgeTypeEmpty);
+        require!(
+            self.edge_type.len() <= MAX_EDGE_TYPE_LENGTH,
+            ZkNftError::EdgeTypeTooLong
+        );
+        require!(
+            self.namespace.len() <= MAX_NAMESPACE_LENGTH,
+            ZkNftError::NamespaceTooLong
+        );
+
+        self.validity.validate()
+    }
+}
+
+// Optional unix timestamps bounding when an edge counts as a relationship
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, Default)]
+pub struct EdgeValidity {
+    pub valid_from: Option<i64>,
+    pub valid_until: Option<i64>,
+}
+
+impl EdgeValidity {
+    pub fn validate(&self) -> Result<()> {
+        if let (Some(valid_from), Some(valid_until)) = (self.valid_from, self.valid_until) {
+            require!(valid_from < valid_until, ZkNftError::InvalidEdgeValidity);
+        }
+        Ok(())
+    }
+
+    pub fn is_active(&self, unix_timestamp: i64) -> bool {
+        let started = self
+            .valid_from
+            .map_or(true, |valid_from| valid_from <= unix_timestamp);
+        started && !self.is_expired(unix_timestamp)
+    }
+
+    pub fn is_expired(&self, unix_timestamp: i64) -> bool {
+        self.valid_until
+            .map_or(false, |valid_until| valid_until <= unix_timestamp)
+    }
+}
+
+impl AsByteVec for EdgeValidity {
+    fn as_byte_vec(&self) -> Vec<Vec<u8>> {
+        let validity_bytes = self.try_to_vec().unwrap();
+        let truncated_validity_bytes = hash_to_bn254_field_size_be(&validity_bytes.as_slice())
+            .unwrap()
+            .0;
+        vec![truncated_validity_bytes.to_vec()]
+    }
+}
+
+// Direction of an edge. Undirected edges store their endpoints sorted, so
+// `source_node` and `target_node` carry no ordering semantics for them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, Default)]
+pub enum EdgeDirection {
+    #[default]
+    Directed,
+    Undirected,
+}
+
+impl AsByteVec for EdgeDirection {
+    fn as_byte_vec(&self) -> Vec<Vec<u8>> {
+        let direction_bytes = self.try_to_vec().unwrap();
+        let truncated_direction_bytes = hash_to_bn254_field_size_be(&direction_bytes.as_slice())
+            .unwrap()
+            .0;
+        vec![truncated_direction_bytes.to_vec()]
+    }
 }
 
 // EdgeData structure to store properties as serialized bytes
@@ -32,9 +122,8 @@ impl EdgeData {
         }
     }
 
-    pub fn get_properties(&self) -> Vec<Properties> {
-        borsh::BorshDeserialize::deserialize(&mut self.properties_bytes.as_slice())
-            .unwrap_or_default()
+    pub fn get_properties(&self) -> Result<Vec<Properties>> {
+        decode_bytes(&self.properties_bytes)
     }
 }
 
@@ -48,7 +137,90 @@ impl AsByteVec for EdgeData {
     }
 }
 
-// Edge structure to represent connections between nodes
+// Like `EdgeData`, but properties are committed as a merkle root so a single
+// property can be proven, see `utils::property_tree`. Also records the token
+// gate of the target node that was satisfied when the edge was created,
+// whether the edge is included in its endpoints' node counters, for
+// undirected edges the owner of the target node who co-signed it and, for
+// edges created through `create_edge_cpi`, the calling program. Unlike the
+// update authority, no instruction changes the edge data after creation.
+#[derive(Clone, Debug, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct EdgeDataV2 {
+    pub properties_bytes: Vec<u8>,
+    pub token_gate: TokenGate,
+    pub counted: bool,
+    pub counterparty: Option<Pubkey>,
+    pub caller_program: Option<Pubkey>,
+}
+
+impl EdgeDataV2 {
+    pub fn new(properties: &Vec<Properties>, token_gate: TokenGate, counted: bool) -> Self {
+        Self {
+            properties_bytes: properties.try_to_vec().unwrap(),
+            token_gate,
+            counted,
+            counterparty: None,
+            caller_program: None,
+        }
+    }
+
+    pub fn get_properties(&self) -> Result<Vec<Properties>> {
+        decode_bytes(&self.properties_bytes)
+    }
+
+    pub fn properties_root(&self) -> Result<[u8; 32]> {
+        Ok(property_tree_root(&self.get_properties()?))
+    }
+
+    // See `NodeDataV2::validate`
+    pub fn validate(&self) -> Result<()> {
+        self.get_properties()?;
+        Ok(())
+    }
+}
+
+impl From<EdgeData> for EdgeDataV2 {
+    fn from(edge_data: EdgeData) -> Self {
+        Self {
+            properties_bytes: edge_data.properties_bytes,
+            token_gate: TokenGate::None,
+            counted: false,
+            counterparty: None,
+            caller_program: None,
+        }
+    }
+}
+
+impl AsByteVec for EdgeDataV2 {
+    fn as_byte_vec(&self) -> Vec<Vec<u8>> {
+        let token_gate_hash = &self.token_gate.as_byte_vec()[0];
+        let mut counted = [0u8; 32];
+        counted[31] = self.counted as u8;
+        let counterparty_bytes = self.counterparty.try_to_vec().unwrap();
+        let counterparty_hash = hash_to_bn254_field_size_be(&counterparty_bytes.as_slice())
+            .unwrap()
+            .0;
+        let caller_program_bytes = self.caller_program.try_to_vec().unwrap();
+        let caller_program_hash = hash_to_bn254_field_size_be(&caller_program_bytes.as_slice())
+            .unwrap()
+            .0;
+        // See `NodeDataV2::as_byte_vec`
+        let properties_root = self.properties_root().unwrap();
+        let edge_data_hash = Poseidon::hashv(&[
+            &properties_root,
+            token_gate_hash,
+            &counted,
+            &counterparty_hash,
+            &caller_program_hash,
+        ])
+        .unwrap();
+        vec![edge_data_hash.to_vec()]
+    }
+}
+
+// Edge structure to represent connections between nodes. Only read to migrate
+// existing edges, new fields go into `EdgeV2` so the layout and hash stay those
+// of the deployed accounts.
 #[light_account]
 #[derive(Clone, Debug, Default)]
 pub struct EdgeV1 {
@@ -64,3 +236,103 @@ pub struct EdgeV1 {
     pub update_authority: UpdateAuthority,
     pub initialized_plugins: u16,
 }
+
+// Second edge layout: adds the edge type, direction, weight, validity window
+// and namespace, and drops the unused plugin flags.
+// Addresses are still derived with `AccountKey::EdgeV1`, so migrating an edge
+// keeps its address.
+#[light_account]
+#[derive(Clone, Debug, Default)]
+pub struct EdgeV2 {
+    pub key: AccountKey,
+    #[truncate]
+    pub source_node: String,
+    #[truncate]
+    pub target_node: String,
+    #[truncate]
+    pub edge_type: String,
+    #[truncate]
+    pub namespace: String,
+    pub direction: EdgeDirection,
+    pub edge_data: EdgeDataV2,
+    pub weight: u64,
+    pub validity: EdgeValidity,
+    pub is_mutable: bool,
+    #[truncate]
+    pub owner: Pubkey,
+    pub update_authority: UpdateAuthority,
+}
+
+impl EdgeV2 {
+    // Directed edge shared by the instructions creating edges, after the
+    // arguments were validated
+    pub fn new(
+        edge_args: EdgeArgs,
+        edge_data: EdgeDataV2,
+        owner: Pubkey,
+        update_authority: UpdateAuthority,
+    ) -> Self {
+        Self {
+            key: AccountKey::EdgeV2,
+            source_node: edge_args.source_node,
+            target_node: edge_args.target_node,
+            edge_type: edge_args.edge_type,
+            namespace: edge_args.namespace,
+            direction: EdgeDirection::Directed,
+            edge_data,
+            weight: edge_args.weight,
+            validity: edge_args.validity,
+            is_mutable: edge_args.is_mutable,
+            owner,
+            update_authority,
+        }
+    }
+
+    // Undirected edges connect their endpoints in either order
+    pub fn connects(&self, source_node: &str, target_node: &str, edge_type: &str) -> bool {
+        if self.edge_type != edge_type {
+            return false;
+        }
+        let directed_match = self.source_node == source_node && self.target_node == target_node;
+        match self.direction {
+            EdgeDirection::Directed => directed_match,
+            EdgeDirection::Undirected => {
+                directed_match
+                    || (self.source_node == target_node && self.target_node == source_node)
+            }
+        }
+    }
+
+    // Must be checked whenever the edge is consumed as proof of a relationship,
+    // which only `assert_edge` does. Instructions managing an edge, like
+    // adjusting its weight or pruning it, work outside the window.
+    pub fn require_active(&self) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            self.validity.is_active(clock.unix_timestamp),
+            ZkNftError::EdgeNotActive
+        );
+        Ok(())
+    }
+}
+
+// Edges created before edge types existed migrate untyped, directed and
+// without weight or validity window
+impl From<EdgeV1> for EdgeV2 {
+    fn from(edge: EdgeV1) -> Self {
+        Self {
+            key: AccountKey::EdgeV2,
+            source_node: edge.source_node,
+            target_node: edge.target_node,
+            edge_type: String::new(),
+            namespace: String::new(),
+            direction: EdgeDirection::Directed,
+            edge_data: edge.edge_data.into(),
+            weight: 0,
+            validity: EdgeValidity::default(),
+            is_mutable: edge.is_mutable,
+            owner: edge.owner,
+            update_authority: edge.update_authority,
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::state::AccountKey;
+use anchor_lang::prelude::*;
+use light_hasher::bytes::AsByteVec;
+use light_sdk::light_account;
+use light_utils::{hash_to_bn254_field_size_be, hashv_to_bn254_field_size_be};
+
+const IDENTITY_LINK_ATTESTATION_TAG: &str = "tapestry:identity_link";
+
+// External account systems a node can be linked to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, Default)]
+pub enum IdentityProvider {
+    #[default]
+    Wallet,
+    Twitter,
+    Ens,
+}
+
+// An account in an external system. Only the hash of the external id is
+// stored, so handles never end up on chain in the clear. Ids are hashed as
+// given, callers normalize them (e.g. lowercase handles) before hashing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, Default)]
+pub struct ExternalIdentity {
+    pub provider: IdentityProvider,
+    pub external_id_hash: [u8; 32],
+}
+
+impl ExternalIdentity {
+    pub fn new(provider: IdentityProvider, external_id: &str) -> Self {
+        Self {
+            provider,
+            external_id_hash: hashv_to_bn254_field_size_be(&[external_id.as_bytes()]),
+        }
+    }
+}
+
+impl AsByteVec for ExternalIdentity {
+    fn as_byte_vec(&self) -> Vec<Vec<u8>> {
+        let identity_bytes = self.try_to_vec().unwrap();
+        let truncated_identity_bytes = hash_to_bn254_field_size_be(&identity_bytes.as_slice())
+            .unwrap()
+            .0;
+        vec![truncated_identity_bytes.to_vec()]
+    }
+}
+
+// Edge from a node to an external identity, only created with a signature of
+// the configured attestation authority. Lives at an address derived from the
+// identity, see `get_identity_link_seed`, so an external account can be linked
+// to a single node.
+#[light_account]
+#[derive(Clone, Debug, Default)]
+pub struct IdentityLinkV1 {
+    pub key: AccountKey,
+    #[truncate]
+    pub node: String,
+    pub identity: ExternalIdentity,
+    // Authority that attested the link, kept so badges can be dropped for
+    // links attested before an authority rotation
+    #[truncate]
+    pub attestation_authority: Pubkey,
+}
+
+impl IdentityLinkV1 {
+    // Message signed by the attestation authority. It includes the program id
+    // so an attestation can't be replayed against another deployment.
+    pub fn attestation_message(node: &str, identity: &ExternalIdentity) -> Vec<u8> {
+        (
+            IDENTITY_LINK_ATTESTATION_TAG,
+            crate::ID.to_bytes(),
+            node,
+            identity,
+        )
+            .try_to_vec()
+            .unwrap()
+    }
+}
@@ -2,16 +2,38 @@ use anchor_lang::prelude::*;
 use light_hasher::bytes::AsByteVec;
 use light_utils::hash_to_bn254_field_size_be;
 
+pub use address_seed::*;
+pub use anchor_compressed_account_meta::*;
 pub use anchor_compressed_proof::*;
+pub use config::*;
+pub use decompressed_node::*;
 pub use edge::*;
+pub use identity_link::*;
 pub use node::*;
+pub use node_counter::*;
+pub use pending_edge::*;
+pub use proven_node::*;
+pub use signed_action::*;
+pub use token_gate::*;
+pub use versioned::*;
 
 // Explicitly re-export the UpdateAuthority types with different names
 pub use node::UpdateAuthority as NodeUpdateAuthority;
 
+mod address_seed;
+mod anchor_compressed_account_meta;
 mod anchor_compressed_proof;
+mod config;
+mod decompressed_node;
 mod edge;
+mod identity_link;
 mod node;
+mod node_counter;
+mod pending_edge;
+mod proven_node;
+mod signed_action;
+mod token_gate;
+mod versioned;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, Default)]
 #[repr(u8)]
@@ -19,6 +41,12 @@ pub enum AccountKey {
     #[default]
     NodeV1,
     EdgeV1,
+    PendingEdgeV1,
+    NodeV2,
+    EdgeV2,
+    NodeCounterV1,
+    DecompressedNodeV1,
+    IdentityLinkV1,
 }
 
 impl AsByteVec for AccountKey {
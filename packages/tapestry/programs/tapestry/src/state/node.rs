@@ -1,7 +1,13 @@
-use crate::state::AccountKey;
+use crate::constants::{MAX_CIPHERTEXT_LENGTH, MAX_NAMESPACE_LENGTH, MAX_THRESHOLD_SIGNERS};
+use crate::errors::ZkNftError;
+use crate::state::{AccountKey, AnchorCompressedAccountMeta, TokenGate, VersionedNode};
+use crate::utils::{decode_bytes, property_tree_root};
 use anchor_lang::prelude::*;
-use borsh;
 use light_hasher::bytes::AsByteVec;
+use light_hasher::{Hasher, Poseidon};
+use light_sdk::compressed_account::{
+    OutputCompressedAccountWithPackedContext, PackedCompressedAccountWithMerkleContext,
+};
 use light_sdk::light_account;
 use light_utils::hash_to_bn254_field_size_be;
 
@@ -41,6 +47,18 @@ impl AsByteVec for Properties {
     }
 }
 
+// Property whose value is encrypted client-side to `recipient`, e.g. with an
+// X25519 key exchange against `ephemeral_public_key`. Only the ciphertext is
+// stored and committed, the program never sees the plaintext.
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, Default)]
+pub struct EncryptedProperty {
+    pub key: String,
+    pub recipient: Pubkey,
+    pub ephemeral_public_key: [u8; 32],
+    pub nonce: [u8; 24],
+    pub ciphertext: Vec<u8>,
+}
+
 // Wrapper for Vec<Properties>
 #[derive(Clone, Debug, Default)]
 pub struct PropertiesVec(pub Vec<Properties>);
@@ -79,6 +97,28 @@ pub struct NodeArgs {
     // Whether or not the data struct is mutable, default is not
     pub is_mutable: bool,
     pub creators: Vec<Creator>,
+    /// Namespace the node belongs to, may be empty
+    pub namespace: String,
+    /// Properties only readable by their recipient
+    pub encrypted_properties: Vec<EncryptedProperty>,
+    /// Holding required to create edges pointing at the node
+    pub token_gate: TokenGate,
+}
+
+impl NodeArgs {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.namespace.len() <= MAX_NAMESPACE_LENGTH,
+            ZkNftError::NamespaceTooLong
+        );
+        for encrypted_property in &self.encrypted_properties {
+            require!(
+                encrypted_property.ciphertext.len() <= MAX_CIPHERTEXT_LENGTH,
+                ZkNftError::CiphertextTooLong
+            );
+        }
+        Ok(())
+    }
 }
 
 // NodeData structure to store properties and creators as serialized bytes
@@ -96,14 +136,12 @@ impl NodeData {
         }
     }
 
-    pub fn get_properties(&self) -> Vec<Properties> {
-        borsh::BorshDeserialize::deserialize(&mut self.properties_bytes.as_slice())
-            .unwrap_or_default()
+    pub fn get_properties(&self) -> Result<Vec<Properties>> {
+        decode_bytes(&self.properties_bytes)
     }
 
-    pub fn get_creators(&self) -> Vec<Creator> {
-        borsh::BorshDeserialize::deserialize(&mut self.creators_bytes.as_slice())
-            .unwrap_or_default()
+    pub fn get_creators(&self) -> Result<Vec<Creator>> {
+        decode_bytes(&self.creators_bytes)
     }
 }
 
@@ -117,6 +155,85 @@ impl AsByteVec for NodeData {
     }
 }
 
+// Like `NodeData`, but properties are committed as a merkle root so a single
+// property can be proven, see `utils::property_tree`. Encrypted properties are
+// committed as a whole.
+#[derive(Clone, Debug, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct NodeDataV2 {
+    pub properties_bytes: Vec<u8>,
+    pub creators_bytes: Vec<u8>,
+    pub encrypted_properties_bytes: Vec<u8>,
+}
+
+impl NodeDataV2 {
+    pub fn new(
+        properties: &Vec<Properties>,
+        creators: &Vec<Creator>,
+        encrypted_properties: &Vec<EncryptedProperty>,
+    ) -> Self {
+        Self {
+            properties_bytes: properties.try_to_vec().unwrap(),
+            creators_bytes: creators.try_to_vec().unwrap(),
+            encrypted_properties_bytes: encrypted_properties.try_to_vec().unwrap(),
+        }
+    }
+
+    pub fn get_properties(&self) -> Result<Vec<Properties>> {
+        decode_bytes(&self.properties_bytes)
+    }
+
+    pub fn get_creators(&self) -> Result<Vec<Creator>> {
+        decode_bytes(&self.creators_bytes)
+    }
+
+    pub fn get_encrypted_properties(&self) -> Result<Vec<EncryptedProperty>> {
+        decode_bytes(&self.encrypted_properties_bytes)
+    }
+
+    pub fn properties_root(&self) -> Result<[u8; 32]> {
+        Ok(property_tree_root(&self.get_properties()?))
+    }
+
+    // The properties are committed by their root, so bytes that do not decode
+    // canonically must never be written. Data built from arguments always
+    // does, data carried over from a V1 node is checked when migrating.
+    pub fn validate(&self) -> Result<()> {
+        self.get_properties()?;
+        self.get_creators()?;
+        self.get_encrypted_properties()?;
+        Ok(())
+    }
+}
+
+impl From<NodeData> for NodeDataV2 {
+    fn from(node_data: NodeData) -> Self {
+        Self {
+            properties_bytes: node_data.properties_bytes,
+            creators_bytes: node_data.creators_bytes,
+            encrypted_properties_bytes: Vec::<EncryptedProperty>::new().try_to_vec().unwrap(),
+        }
+    }
+}
+
+impl AsByteVec for NodeDataV2 {
+    fn as_byte_vec(&self) -> Vec<Vec<u8>> {
+        let creators_hash = hash_to_bn254_field_size_be(&self.creators_bytes.as_slice())
+            .unwrap()
+            .0;
+        let encrypted_properties_hash =
+            hash_to_bn254_field_size_be(&self.encrypted_properties_bytes.as_slice())
+                .unwrap()
+                .0;
+        // Only written after `validate`, data that does not decode cannot be
+        // hashed and fails the transaction
+        let properties_root = self.properties_root().unwrap();
+        let node_data_hash =
+            Poseidon::hashv(&[&properties_root, &creators_hash, &encrypted_properties_hash])
+                .unwrap();
+        vec![node_data_hash.to_vec()]
+    }
+}
+
 // The actual Node account structure
 #[light_account]
 #[derive(Clone, Debug, Default)]
@@ -131,14 +248,170 @@ pub struct NodeV1 {
     pub initialized_plugins: u16,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, Default)]
+// Second node layout: adds a namespace and drops the unused plugin flags.
+// Addresses are still derived with `AccountKey::NodeV1`, so migrating a node
+// keeps its address.
+#[light_account]
+#[derive(Clone, Debug, Default)]
+pub struct NodeV2 {
+    pub key: AccountKey,
+    #[truncate]
+    pub owner: Pubkey,
+    pub update_authority: UpdateAuthority,
+    #[truncate]
+    pub namespace: String,
+    pub label: String,
+    pub node_data: NodeDataV2,
+    pub is_mutable: bool,
+    pub token_gate: TokenGate,
+}
+
+impl NodeV2 {
+    // Shared by the instructions creating nodes, after the arguments were
+    // validated
+    pub fn new(node_args: NodeArgs, owner: Pubkey, update_authority: UpdateAuthority) -> Self {
+        Self {
+            key: AccountKey::NodeV2,
+            owner,
+            update_authority,
+            namespace: node_args.namespace,
+            label: node_args.label,
+            node_data: NodeDataV2::new(
+                &node_args.properties,
+                &node_args.creators,
+                &node_args.encrypted_properties,
+            ),
+            is_mutable: node_args.is_mutable,
+            token_gate: node_args.token_gate,
+        }
+    }
+}
+
+impl From<NodeV1> for NodeV2 {
+    fn from(node: NodeV1) -> Self {
+        Self {
+            key: AccountKey::NodeV2,
+            owner: node.owner,
+            update_authority: node.update_authority,
+            namespace: String::new(),
+            label: node.label,
+            node_data: node.node_data.into(),
+            is_mutable: node.is_mutable,
+            token_gate: TokenGate::None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, Default)]
 pub enum UpdateAuthority {
     #[default]
     None,
     Address(Pubkey),
+    // A PDA of `program_id`, for accounts created by other programs via CPI
+    Program {
+        program_id: Pubkey,
+        authority: Pubkey,
+    },
+    // M-of-N: `threshold` of the distinct `signers` have to sign
+    Threshold {
+        threshold: u8,
+        signers: Vec<Pubkey>,
+    },
+    // Defers to the update authority of the group node at this address
+    Group([u8; 32]),
 }
 
 impl UpdateAuthority {
+    // The optional signer given when creating an account
+    pub fn from_signer(update_authority: Option<Pubkey>) -> Self {
+        match update_authority {
+            Some(update_authority) => Self::Address(update_authority),
+            None => Self::None,
+        }
+    }
+
+    pub fn validate(&self, authority: &Pubkey) -> Result<()> {
+        match self {
+            UpdateAuthority::Address(address) if address == authority => Ok(()),
+            UpdateAuthority::Program {
+                authority: program_authority,
+                ..
+            } if program_authority == authority => Ok(()),
+            _ => Err(error!(ZkNftError::InvalidAuthority)),
+        }
+    }
+
+    // Without an update authority, a mutable account is updated by its owner.
+    // Threshold signers other than `authority` sign as remaining accounts, and
+    // group authorities need the group node proven in `group`.
+    pub fn validate_update(
+        &self,
+        owner: &Pubkey,
+        authority: &Pubkey,
+        remaining_accounts: &[AccountInfo],
+        group: Option<&GroupAuthority>,
+    ) -> Result<()> {
+        match self {
+            UpdateAuthority::None if owner == authority => Ok(()),
+            UpdateAuthority::Group(address) => {
+                let group = group.ok_or(ZkNftError::MissingGroupAuthority)?;
+                require!(
+                    group.group_meta.address == *address,
+                    ZkNftError::GroupMismatch
+                );
+                let group_node = group.group.clone().into_latest();
+                // Groups do not nest, so the check never recurses further
+                require!(
+                    !matches!(group_node.update_authority, UpdateAuthority::Group(_)),
+                    ZkNftError::InvalidAuthority
+                );
+                group_node.update_authority.validate_update(
+                    &group_node.owner,
+                    authority,
+                    remaining_accounts,
+                    None,
+                )
+            }
+            UpdateAuthority::Threshold { threshold, signers } => {
+                let approvals = signers
+                    .iter()
+                    .filter(|signer| {
+                        *signer == authority
+                            || remaining_accounts
+                                .iter()
+                                .any(|account| account.is_signer && account.key == *signer)
+                    })
+                    .count();
+                require!(
+                    approvals >= usize::from(*threshold),
+                    ZkNftError::ThresholdNotMet
+                );
+                Ok(())
+            }
+            _ => self.validate(authority),
+        }
+    }
+
+    // Checked before an authority is set, so a threshold can always be met
+    pub fn validate_threshold(&self) -> Result<()> {
+        if let UpdateAuthority::Threshold { threshold, signers } = self {
+            require!(
+                *threshold > 0
+                    && usize::from(*threshold) <= signers.len()
+                    && signers.len() <= MAX_THRESHOLD_SIGNERS,
+                ZkNftError::InvalidThreshold
+            );
+            let mut unique_signers = signers.clone();
+            unique_signers.sort();
+            unique_signers.dedup();
+            require!(
+                unique_signers.len() == signers.len(),
+                ZkNftError::InvalidThreshold
+            );
+        }
+        Ok(())
+    }
+
     pub fn as_byte_vec(&self) -> Vec<Vec<u8>> {
         let update_authority_bytes = self.try_to_vec().unwrap();
         let truncated_update_authority_bytes =
@@ -148,3 +421,22 @@ impl UpdateAuthority {
         vec![truncated_update_authority_bytes.to_vec()]
     }
 }
+
+// Group node of an `UpdateAuthority::Group`, proven alongside the updated
+// account and written back unchanged
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct GroupAuthority {
+    pub group: VersionedNode,
+    pub group_meta: AnchorCompressedAccountMeta,
+}
+
+impl GroupAuthority {
+    pub fn proven_compressed_accounts(
+        &self,
+    ) -> Result<(
+        PackedCompressedAccountWithMerkleContext,
+        OutputCompressedAccountWithPackedContext,
+    )> {
+        self.group.proven_compressed_accounts(&self.group_meta)
+    }
+}
@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+use light_sdk::compressed_account::{
+    OutputCompressedAccountWithPackedContext, PackedCompressedAccountWithMerkleContext,
+};
+use light_sdk::light_account;
+
+use crate::errors::ZkNftError;
+use crate::state::{AccountKey, AnchorCompressedAccountMeta};
+use crate::utils::{input_compressed_account, output_compressed_account};
+
+// Number of live counted edges of one type touching a node. Lives at an
+// address derived from the node and edge type, see `get_node_counter_seed`,
+// so other programs can prove how many edges a node has without an indexer.
+//
+// Counters are opt-in: only edges created by `create_edge` with `counters`
+// are marked `counted` and included, and only those have to update the
+// counters again when they are pruned. Counting needs the source node proven
+// and its owner signing, so only real nodes add to a counter. Edges created
+// without counters, or through the other edge instructions, are never
+// included, hence counted edges rather than a node's degree.
+#[light_account]
+#[derive(Clone, Debug, Default)]
+pub struct NodeCounterV1 {
+    pub key: AccountKey,
+    #[truncate]
+    pub node: String,
+    #[truncate]
+    pub edge_type: String,
+    pub counted_in_edges: u64,
+    pub counted_out_edges: u64,
+}
+
+impl NodeCounterV1 {
+    pub fn require_matches(&self, node: &str, edge_type: &str) -> Result<()> {
+        require!(
+            self.node == node && self.edge_type == edge_type,
+            ZkNftError::CounterMismatch
+        );
+        Ok(())
+    }
+
+    pub fn adjust_counted_in_edges(&self, delta: i64) -> Result<Self> {
+        let counted_in_edges = self
+            .counted_in_edges
+            .checked_add_signed(delta)
+            .ok_or(ZkNftError::CounterOverflow)?;
+        Ok(Self {
+            counted_in_edges,
+            ..self.clone()
+        })
+    }
+
+    pub fn adjust_counted_out_edges(&self, delta: i64) -> Result<Self> {
+        let counted_out_edges = self
+            .counted_out_edges
+            .checked_add_signed(delta)
+            .ok_or(ZkNftError::CounterOverflow)?;
+        Ok(Self {
+            counted_out_edges,
+            ..self.clone()
+        })
+    }
+}
+
+// Counters of both endpoints, passed to keep them in step with an edge
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct EdgeCounters {
+    pub source: NodeCounterV1,
+    pub source_meta: AnchorCompressedAccountMeta,
+    pub target: NodeCounterV1,
+    pub target_meta: AnchorCompressedAccountMeta,
+}
+
+impl EdgeCounters {
+    pub fn require_matches(
+        &self,
+        source_node: &str,
+        target_node: &str,
+        edge_type: &str,
+    ) -> Result<()> {
+        self.source.require_matches(source_node, edge_type)?;
+        self.target.require_matches(target_node, edge_type)
+    }
+
+    // Input and output accounts moving both counters by `delta` edges, the
+    // source counter first
+    pub fn compressed_accounts(
+        &self,
+        delta: i64,
+    ) -> Result<(
+        Vec<PackedCompressedAccountWithMerkleContext>,
+        Vec<OutputCompressedAccountWithPackedContext>,
+    )> {
+        let source = self.source.adjust_counted_out_edges(delta)?;
+        let target = self.target.adjust_counted_in_edges(delta)?;
+
+        let mut inputs = Vec::with_capacity(2);
+        let mut outputs = Vec::with_capacity(2);
+        for (counter, updated_counter, meta) in [
+            (&self.source, &source, &self.source_meta),
+            (&self.target, &target, &self.target_meta),
+        ] {
+            let merkle_context = meta.merkle_context();
+            inputs.push(input_compressed_account(
+                counter,
+                &meta.address,
+                &crate::ID,
+                &merkle_context,
+                meta.root_index,
+            )?);
+            outputs.push(output_compressed_account(
+                updated_counter,
+                &meta.address,
+                &crate::ID,
+                &merkle_context,
+            )?);
+        }
+        Ok((inputs, outputs))
+    }
+}
@@ -0,0 +1,31 @@
+use crate::state::edge::{EdgeDataV2, EdgeValidity};
+use crate::state::node::UpdateAuthority;
+use crate::state::AccountKey;
+use anchor_lang::prelude::*;
+use light_sdk::light_account;
+
+// An edge awaiting approval by the owner of its target node. Accepting it
+// replaces it with an `EdgeV2` at the same address, rejecting it or cancelling
+// it by the requester nullifies it.
+#[light_account]
+#[derive(Clone, Debug, Default)]
+pub struct PendingEdgeV1 {
+    pub key: AccountKey,
+    #[truncate]
+    pub source_node: String,
+    #[truncate]
+    pub target_node: String,
+    #[truncate]
+    pub edge_type: String,
+    #[truncate]
+    pub namespace: String,
+    pub edge_data: EdgeDataV2,
+    pub weight: u64,
+    pub validity: EdgeValidity,
+    pub is_mutable: bool,
+    #[truncate]
+    pub owner: Pubkey,
+    #[truncate]
+    pub target_owner: Pubkey,
+    pub update_authority: UpdateAuthority,
+}
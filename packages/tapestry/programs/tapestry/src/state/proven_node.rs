@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use light_sdk::compressed_account::{
+    OutputCompressedAccountWithPackedContext, PackedCompressedAccountWithMerkleContext,
+};
+
+use crate::errors::ZkNftError;
+use crate::state::{AnchorCompressedAccountMeta, NodeAccount, NodeV2, VersionedNode};
+
+// Node proven alongside an instruction and written back unchanged, for
+// instructions that depend on an endpoint's owner or settings
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct ProvenNode {
+    pub node: VersionedNode,
+    pub node_meta: AnchorCompressedAccountMeta,
+}
+
+impl ProvenNode {
+    // Edges refer to nodes by their address in base58
+    pub fn address(&self) -> String {
+        Pubkey::new_from_array(self.node_meta.address).to_string()
+    }
+
+    pub fn require_endpoint(&self, endpoint: &str) -> Result<()> {
+        require!(self.address() == endpoint, ZkNftError::EndpointMismatch);
+        Ok(())
+    }
+
+    pub fn latest(&self) -> NodeV2 {
+        self.node.clone().into_latest()
+    }
+
+    pub fn proven_compressed_accounts(
+        &self,
+    ) -> Result<(
+        PackedCompressedAccountWithMerkleContext,
+        OutputCompressedAccountWithPackedContext,
+    )> {
+        self.node.proven_compressed_accounts(&self.node_meta)
+    }
+}
+
+// Node an instruction depends on, either proven as a compressed account or read
+// from its `NodeAccount` while decompressed. Only `decompress_node` creates
+// node accounts, so one owned by the program proves the node exists without an
+// inclusion proof and is read without being rewritten.
+pub enum EndpointNode<'a> {
+    Compressed(&'a ProvenNode),
+    Decompressed(&'a NodeAccount),
+}
+
+impl<'a> EndpointNode<'a> {
+    pub fn from_accounts(
+        node: Option<&'a ProvenNode>,
+        node_account: Option<&'a NodeAccount>,
+    ) -> Result<Option<Self>> {
+        match (node, node_account) {
+            (Some(_), Some(_)) => err!(ZkNftError::AmbiguousNode),
+            (Some(node), None) => Ok(Some(Self::Compressed(node))),
+            (None, Some(node_account)) => Ok(Some(Self::Decompressed(node_account))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    // For instructions that always depend on the node
+    pub fn required(
+        node: Option<&'a ProvenNode>,
+        node_account: Option<&'a NodeAccount>,
+    ) -> Result<Self> {
+        Self::from_accounts(node, node_account)?.ok_or(error!(ZkNftError::MissingNode))
+    }
+
+    pub fn address(&self) -> String {
+        match self {
+            Self::Compressed(node) => node.address(),
+            Self::Decompressed(node_account) => {
+                Pubkey::new_from_array(node_account.address).to_string()
+            }
+        }
+    }
+
+    pub fn require_endpoint(&self, endpoint: &str) -> Result<()> {
+        require!(self.address() == endpoint, ZkNftError::EndpointMismatch);
+        Ok(())
+    }
+
+    pub fn latest(&self) -> NodeV2 {
+        match self {
+            Self::Compressed(node) => node.latest(),
+            Self::Decompressed(node_account) => node_account.node.clone(),
+        }
+    }
+
+    // Input and output to add to the CPI, a decompressed node has none
+    pub fn proven_compressed_accounts(
+        &self,
+    ) -> Result<
+        Option<(
+            PackedCompressedAccountWithMerkleContext,
+            OutputCompressedAccountWithPackedContext,
+        )>,
+    > {
+        match self {
+            Self::Compressed(node) => node.proven_compressed_accounts().map(Some),
+            Self::Decompressed(_) => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::errors::ZkNftError;
+use crate::utils::verify_ed25519_signature;
+use anchor_lang::prelude::*;
+
+const SIGNED_ACTION_TAG: &str = "tapestry:signed_action";
+
+// Authorization a user signs off chain so a relayer can submit an instruction
+// for them. The ed25519 signature sits in the instruction before the signed
+// instruction, see `verify_ed25519_signature`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct SignedAction {
+    // Must equal the signer's `ActionNonce`, which is bumped on use
+    pub nonce: u64,
+    // Unix timestamp after which the signature is no longer accepted
+    pub expires_at: i64,
+}
+
+impl SignedAction {
+    // Message the user signs. It commits to the program, the instruction and
+    // its arguments, so a relayer can neither replay the signature elsewhere
+    // nor change what it authorizes.
+    pub fn message(&self, action: &str, signer: &Pubkey, args: &[u8]) -> Vec<u8> {
+        (
+            SIGNED_ACTION_TAG,
+            crate::ID.to_bytes(),
+            action,
+            signer.to_bytes(),
+            self.nonce,
+            self.expires_at,
+            args,
+        )
+            .try_to_vec()
+            .unwrap()
+    }
+
+    pub fn verify(
+        &self,
+        action_nonce: &mut ActionNonce,
+        instructions_sysvar: &AccountInfo,
+        action: &str,
+        signer: &Pubkey,
+        args: &[u8],
+    ) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp <= self.expires_at,
+            ZkNftError::SignedActionExpired
+        );
+        require!(
+            self.nonce == action_nonce.nonce,
+            ZkNftError::InvalidActionNonce
+        );
+        verify_ed25519_signature(
+            instructions_sysvar,
+            signer,
+            &self.message(action, signer, args),
+        )?;
+
+        action_nonce.nonce += 1;
+        Ok(())
+    }
+}
+
+// Next nonce a signer's `SignedAction` must carry, at a PDA derived from
+// `ACTION_NONCE_SEED` and the signer
+#[account]
+#[derive(Debug)]
+pub struct ActionNonce {
+    pub signer: Pubkey,
+    pub nonce: u64,
+    pub bump: u8,
+}
+
+impl ActionNonce {
+    pub const SPACE: usize = 8 + 32 + 8 + 1;
+}
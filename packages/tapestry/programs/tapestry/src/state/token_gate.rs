@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::token::TokenAccount;
+use light_hasher::bytes::AsByteVec;
+use light_utils::hash_to_bn254_field_size_be;
+
+use crate::errors::ZkNftError;
+use crate::state::EndpointNode;
+
+// Holding requirement a node declares for edges pointing at it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, Default)]
+pub enum TokenGate {
+    #[default]
+    None,
+    // At least `min_amount` of the given SPL mint
+    Mint {
+        mint: Pubkey,
+        min_amount: u64,
+    },
+    // At least `min_amount` of an NFT in a verified Metaplex collection
+    Collection {
+        collection: Pubkey,
+        min_amount: u64,
+    },
+}
+
+impl TokenGate {
+    // Gate an edge pointing at `target_node` has to satisfy. Compressed
+    // accounts can't be read without being rewritten, so proving the target
+    // would serialize every edge to a popular node. Checking the gate is
+    // therefore opt-in: edges created without their target record no gate, and
+    // readers relying on a gate check the one recorded in the edge data.
+    pub fn of_target(target_node: &str, target: Option<&EndpointNode>) -> Result<Self> {
+        match target {
+            Some(target) => {
+                require!(
+                    target.address() == target_node,
+                    ZkNftError::GateTargetMismatch
+                );
+                Ok(target.latest().token_gate)
+            }
+            None => Ok(TokenGate::None),
+        }
+    }
+
+    pub fn verify(
+        &self,
+        owner: &Pubkey,
+        token_account: Option<&TokenAccount>,
+        metadata: Option<&MetadataAccount>,
+    ) -> Result<()> {
+        match self {
+            TokenGate::None => Ok(()),
+            TokenGate::Mint { mint, min_amount } => {
+                let token_account = token_account.ok_or(ZkNftError::TokenGateNotSatisfied)?;
+                require!(
+                    token_account.owner == *owner
+                        && token_account.mint == *mint
+                        && token_account.amount >= *min_amount,
+                    ZkNftError::TokenGateNotSatisfied
+                );
+                Ok(())
+            }
+            TokenGate::Collection {
+                collection,
+                min_amount,
+            } => {
+                let token_account = token_account.ok_or(ZkNftError::TokenGateNotSatisfied)?;
+                let metadata = metadata.ok_or(ZkNftError::TokenGateNotSatisfied)?;
+                let in_collection = metadata
+                    .collection
+                    .as_ref()
+                    .map(|metadata_collection| {
+                        metadata_collection.verified && metadata_collection.key == *collection
+                    })
+                    .unwrap_or(false);
+                require!(
+                    token_account.owner == *owner
+                        && token_account.amount >= *min_amount
+                        && metadata.mint == token_account.mint
+                        && in_collection,
+                    ZkNftError::TokenGateNotSatisfied
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+impl AsByteVec for TokenGate {
+    fn as_byte_vec(&self) -> Vec<Vec<u8>> {
+        let token_gate_bytes = self.try_to_vec().unwrap();
+        let truncated_token_gate_bytes = hash_to_bn254_field_size_be(&token_gate_bytes.as_slice())
+            .unwrap()
+            .0;
+        vec![truncated_token_gate_bytes.to_vec()]
+    }
+}
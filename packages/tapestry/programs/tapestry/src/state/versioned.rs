@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use light_hasher::{DataHasher, Poseidon};
+use light_sdk::compressed_account::{
+    OutputCompressedAccountWithPackedContext, PackedCompressedAccountWithMerkleContext,
+};
+
+use crate::state::{AnchorCompressedAccountMeta, EdgeV1, EdgeV2, NodeV1, NodeV2};
+use crate::utils::{input_compressed_account, proven_compressed_accounts};
+
+// Any node layout that may still exist on chain. Instructions accept every
+// version as input and always write the latest one, so accounts are upgraded
+// as they are touched.
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub enum VersionedNode {
+    V1(NodeV1),
+    V2(NodeV2),
+}
+
+impl VersionedNode {
+    pub fn input_compressed_account(
+        &self,
+        meta: &AnchorCompressedAccountMeta,
+    ) -> Result<PackedCompressedAccountWithMerkleContext> {
+        let merkle_context = meta.merkle_context();
+        let mut compressed_account = match self {
+            VersionedNode::V1(node) => input_compressed_account(
+                node,
+                &meta.address,
+                &crate::ID,
+                &merkle_context,
+                meta.root_index,
+            ),
+            VersionedNode::V2(node) => input_compressed_account(
+                node,
+                &meta.address,
+                &crate::ID,
+                &merkle_context,
+                meta.root_index,
+            ),
+        }?;
+        compressed_account.compressed_account.lamports = meta.lamports;
+        Ok(compressed_account)
+    }
+
+    // Input and identical output proving the node exists, in its current layout
+    pub fn proven_compressed_accounts(
+        &self,
+        meta: &AnchorCompressedAccountMeta,
+    ) -> Result<(
+        PackedCompressedAccountWithMerkleContext,
+        OutputCompressedAccountWithPackedContext,
+    )> {
+        let merkle_context = meta.merkle_context();
+        let (mut input_compressed_account, mut output_compressed_account) = match self {
+            VersionedNode::V1(node) => proven_compressed_accounts(
+                node,
+                &meta.address,
+                &crate::ID,
+                &merkle_context,
+                meta.root_index,
+            ),
+            VersionedNode::V2(node) => proven_compressed_accounts(
+                node,
+                &meta.address,
+                &crate::ID,
+                &merkle_context,
+                meta.root_index,
+            ),
+        }?;
+        input_compressed_account.compressed_account.lamports = meta.lamports;
+        output_compressed_account.compressed_account.lamports = meta.lamports;
+        Ok((input_compressed_account, output_compressed_account))
+    }
+
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        let hash = match self {
+            VersionedNode::V1(node) => node.hash::<Poseidon>(),
+            VersionedNode::V2(node) => node.hash::<Poseidon>(),
+        }
+        .map_err(ProgramError::from)?;
+        Ok(hash)
+    }
+
+    pub fn into_latest(self) -> NodeV2 {
+        match self {
+            VersionedNode::V1(node) => node.into(),
+            VersionedNode::V2(node) => node,
+        }
+    }
+}
+
+// Any edge layout that may still exist on chain, see `VersionedNode`
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub enum VersionedEdge {
+    V1(EdgeV1),
+    V2(EdgeV2),
+}
+
+impl VersionedEdge {
+    pub fn input_compressed_account(
+        &self,
+        meta: &AnchorCompressedAccountMeta,
+    ) -> Result<PackedCompressedAccountWithMerkleContext> {
+        let merkle_context = meta.merkle_context();
+        let mut compressed_account = match self {
+            VersionedEdge::V1(edge) => input_compressed_account(
+                edge,
+                &meta.address,
+                &crate::ID,
+                &merkle_context,
+                meta.root_index,
+            ),
+            VersionedEdge::V2(edge) => input_compressed_account(
+                edge,
+                &meta.address,
+                &crate::ID,
+                &merkle_context,
+                meta.root_index,
+            ),
+        }?;
+        compressed_account.compressed_account.lamports = meta.lamports;
+        Ok(compressed_account)
+    }
+
+    // Input and identical output proving the edge exists, in its current layout
+    pub fn proven_compressed_accounts(
+        &self,
+        meta: &AnchorCompressedAccountMeta,
+    ) -> Result<(
+        PackedCompressedAccountWithMerkleContext,
+        OutputCompressedAccountWithPackedContext,
+    )> {
+        let merkle_context = meta.merkle_context();
+        let (mut input_compressed_account, mut output_compressed_account) = match self {
+            VersionedEdge::V1(edge) => proven_compressed_accounts(
+                edge,
+                &meta.address,
+                &crate::ID,
+                &merkle_context,
+                meta.root_index,
+            ),
+            VersionedEdge::V2(edge) => proven_compressed_accounts(
+                edge,
+                &meta.address,
+                &crate::ID,
+                &merkle_context,
+                meta.root_index,
+            ),
+        }?;
+        input_compressed_account.compressed_account.lamports = meta.lamports;
+        output_compressed_account.compressed_account.lamports = meta.lamports;
+        Ok((input_compressed_account, output_compressed_account))
+    }
+
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        let hash = match self {
+            VersionedEdge::V1(edge) => edge.hash::<Poseidon>(),
+            VersionedEdge::V2(edge) => edge.hash::<Poseidon>(),
+        }
+        .map_err(ProgramError::from)?;
+        Ok(hash)
+    }
+
+    pub fn into_latest(self) -> EdgeV2 {
+        match self {
+            VersionedEdge::V1(edge) => edge.into(),
+            VersionedEdge::V2(edge) => edge,
+        }
+    }
+}
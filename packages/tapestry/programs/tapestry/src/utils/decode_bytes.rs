@@ -0,0 +1,8 @@
+use crate::errors::ZkNftError;
+use anchor_lang::prelude::*;
+
+// Properties and creators are stored double-encoded as Borsh bytes. Trailing
+// bytes are rejected, so only the canonical encoding of a value decodes.
+pub fn decode_bytes<T: AnchorDeserialize>(bytes: &[u8]) -> Result<T> {
+    T::try_from_slice(bytes).map_err(|_| error!(ZkNftError::InvalidAccountData))
+}
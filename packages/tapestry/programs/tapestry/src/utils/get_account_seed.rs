@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use light_utils::hashv_to_bn254_field_size_be;
 
-use crate::AccountKey;
+use crate::{AccountKey, ExternalIdentity};
 
 pub fn get_account_seed(account_key: AccountKey, asset_id: &[u8; 32]) -> [u8; 32] {
     let account_key_bytes = account_key.try_to_vec().unwrap();
@@ -9,3 +9,110 @@ pub fn get_account_seed(account_key: AccountKey, asset_id: &[u8; 32]) -> [u8; 32
     let input = [&crate::ID.to_bytes(), account_type_bytes, asset_id].concat();
     hashv_to_bn254_field_size_be(&[&input])
 }
+
+// Seeds derived from account contents rather than bytes chosen by the caller.
+// The marker makes the hashed input longer than the one of `get_account_seed`,
+// so no `asset_id` can land on a derived address.
+const DERIVED_SEED_MARKER: &[u8] = b"derived";
+
+pub fn get_derived_account_seed(account_key: AccountKey, derived_hash: &[u8; 32]) -> [u8; 32] {
+    let account_key_bytes = account_key.try_to_vec().unwrap();
+    let account_type_bytes: &[u8] = &[account_key_bytes[0]];
+    let input = [
+        &crate::ID.to_bytes(),
+        account_type_bytes,
+        DERIVED_SEED_MARKER,
+        derived_hash,
+    ]
+    .concat();
+    hashv_to_bn254_field_size_be(&[&input])
+}
+
+const UNDIRECTED_EDGE_SEED_TAG: &str = "undirected_edge";
+
+// Undirected edges are addressed by their namespace and sorted endpoints, so the
+// same pair of nodes always maps to the same address regardless of which side
+// created it.
+pub fn get_undirected_edge_seed(
+    namespace: &str,
+    node_a: &str,
+    node_b: &str,
+    edge_type: &str,
+) -> [u8; 32] {
+    let (first, second) = if node_a <= node_b {
+        (node_a, node_b)
+    } else {
+        (node_b, node_a)
+    };
+    let pair_bytes = (
+        UNDIRECTED_EDGE_SEED_TAG,
+        namespace,
+        first,
+        second,
+        edge_type,
+    )
+        .try_to_vec()
+        .unwrap();
+    let pair_hash = hashv_to_bn254_field_size_be(&[&pair_bytes]);
+    get_derived_account_seed(AccountKey::EdgeV1, &pair_hash)
+}
+
+// One counter per node and edge type, so the address is derived from both
+pub fn get_node_counter_seed(node: &str, edge_type: &str) -> [u8; 32] {
+    let counter_bytes = (node, edge_type).try_to_vec().unwrap();
+    let counter_hash = hashv_to_bn254_field_size_be(&[&counter_bytes]);
+    get_account_seed(AccountKey::NodeCounterV1, &counter_hash)
+}
+
+// One identity link per external identity, so the same external account can't
+// be claimed by two nodes
+pub fn get_identity_link_seed(identity: &ExternalIdentity) -> [u8; 32] {
+    let identity_bytes = identity.try_to_vec().unwrap();
+    let identity_hash = hashv_to_bn254_field_size_be(&[&identity_bytes]);
+    get_account_seed(AccountKey::IdentityLinkV1, &identity_hash)
+}
+
+// Variants of `AddressSeed` other than `Random` live in the derived seed domain,
+// so no choice of random bytes can take the address of a label, edge pair or
+// external id. Within that domain each variant hashes its inputs behind a
+// distinct tag, so a label can't produce the same seed as an external id.
+const LABEL_SEED_TAG: &str = "label";
+const EDGE_PAIR_SEED_TAG: &str = "edge_pair";
+const EXTERNAL_ID_SEED_TAG: &str = "external_id";
+
+pub fn get_label_seed(namespace: &str, label: &str) -> [u8; 32] {
+    let label_bytes = (LABEL_SEED_TAG, namespace, label).try_to_vec().unwrap();
+    let label_hash = hashv_to_bn254_field_size_be(&[&label_bytes]);
+    get_derived_account_seed(AccountKey::NodeV1, &label_hash)
+}
+
+pub fn get_edge_pair_seed(
+    namespace: &str,
+    source_node: &str,
+    target_node: &str,
+    edge_type: &str,
+) -> [u8; 32] {
+    let pair_bytes = (
+        EDGE_PAIR_SEED_TAG,
+        namespace,
+        source_node,
+        target_node,
+        edge_type,
+    )
+        .try_to_vec()
+        .unwrap();
+    let pair_hash = hashv_to_bn254_field_size_be(&[&pair_bytes]);
+    get_derived_account_seed(AccountKey::EdgeV1, &pair_hash)
+}
+
+pub fn get_external_id_seed(
+    account_key: AccountKey,
+    namespace: &str,
+    external_id: &str,
+) -> [u8; 32] {
+    let external_id_bytes = (EXTERNAL_ID_SEED_TAG, namespace, external_id)
+        .try_to_vec()
+        .unwrap();
+    let external_id_hash = hashv_to_bn254_field_size_be(&[&external_id_bytes]);
+    get_derived_account_seed(account_key, &external_id_hash)
+}
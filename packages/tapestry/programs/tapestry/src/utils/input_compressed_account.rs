@@ -3,12 +3,13 @@ use borsh::BorshSerialize;
 use light_hasher::{DataHasher, Discriminator};
 use light_sdk::{
     compressed_account::{
-        CompressedAccount, CompressedAccountData, PackedCompressedAccountWithMerkleContext,
+        CompressedAccount, CompressedAccountData, OutputCompressedAccountWithPackedContext,
+        PackedCompressedAccountWithMerkleContext,
     },
     merkle_context::PackedMerkleContext,
 };
 
-use super::get_compressed_account;
+use super::{get_compressed_account, output_compressed_account};
 
 pub fn input_compressed_account<T>(
     account: &T,
@@ -30,6 +31,36 @@ where
     })
 }
 
+// Proves the account exists by consuming it and writing it back unchanged, for
+// instructions that only need to check state such as the existence of an edge.
+// The pinned light-system-program does not implement `read_only` inputs, so
+// this is the only way to prove an account without changing it.
+pub fn proven_compressed_accounts<T>(
+    account: &T,
+    address: &[u8; 32],
+    program_id: &Pubkey,
+    merkle_context: &PackedMerkleContext,
+    merkle_tree_root_index: u16,
+) -> Result<(
+    PackedCompressedAccountWithMerkleContext,
+    OutputCompressedAccountWithPackedContext,
+)>
+where
+    T: BorshSerialize + DataHasher + Discriminator,
+{
+    let input_compressed_account = input_compressed_account(
+        account,
+        address,
+        program_id,
+        merkle_context,
+        merkle_tree_root_index,
+    )?;
+    let output_compressed_account =
+        output_compressed_account(account, address, program_id, merkle_context)?;
+
+    Ok((input_compressed_account, output_compressed_account))
+}
+
 pub fn input_compressed_account_from_hash(
     discriminator: &[u8; 8],
     data_hash: &[u8; 32],
@@ -1,13 +1,23 @@
+pub use decode_bytes::*;
 pub use get_account_seed::*;
 pub use get_compressed_account::*;
 pub use input_compressed_account::*;
 pub use new_compressed_account::*;
 pub use output_compressed_account::*;
+pub use property_tree::*;
+pub use update_compressed_account::*;
 pub use validate_merkle_trees::*;
+pub use verify_ed25519_signature::*;
+pub use verify_inclusion::*;
 
+mod decode_bytes;
 mod get_account_seed;
 mod get_compressed_account;
 mod input_compressed_account;
 mod new_compressed_account;
 mod output_compressed_account;
+mod property_tree;
+mod update_compressed_account;
 mod validate_merkle_trees;
+mod verify_ed25519_signature;
+mod verify_inclusion;
@@ -0,0 +1,81 @@
+use light_hasher::bytes::AsByteVec;
+use light_hasher::{Hasher, Poseidon};
+
+use crate::state::Properties;
+
+// Properties are committed in a Poseidon merkle tree so a single property can
+// be disclosed with a proof instead of revealing the whole set. Leaves are the
+// field-sized hash of the Borsh encoded property, unused leaves are zero.
+// Trees hold at least 2^PROPERTY_TREE_DEPTH leaves and grow one level at a
+// time for larger property sets.
+pub const PROPERTY_TREE_DEPTH: usize = 4;
+
+pub fn property_leaf(property: &Properties) -> [u8; 32] {
+    property.as_byte_vec()[0].as_slice().try_into().unwrap()
+}
+
+pub fn property_tree_depth(leaf_count: usize) -> usize {
+    let mut depth = PROPERTY_TREE_DEPTH;
+    while (1 << depth) < leaf_count {
+        depth += 1;
+    }
+    depth
+}
+
+pub fn property_tree_root(properties: &[Properties]) -> [u8; 32] {
+    let depth = property_tree_depth(properties.len());
+    let mut level = leaves(properties, depth);
+    for _ in 0..depth {
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+    }
+    level[0]
+}
+
+// Sibling hashes from the leaf at `index` up to the root
+pub fn property_tree_proof(properties: &[Properties], index: usize) -> Vec<[u8; 32]> {
+    let depth = property_tree_depth(properties.len());
+    let mut level = leaves(properties, depth);
+    let mut index = index;
+    let mut proof = Vec::with_capacity(depth);
+    for _ in 0..depth {
+        proof.push(level[index ^ 1]);
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+        index /= 2;
+    }
+    proof
+}
+
+pub fn verify_property_proof(
+    root: &[u8; 32],
+    property: &Properties,
+    index: usize,
+    proof: &[[u8; 32]],
+) -> bool {
+    let mut index = index;
+    let mut node = property_leaf(property);
+    for sibling in proof {
+        node = if index % 2 == 0 {
+            hash_pair(&node, sibling)
+        } else {
+            hash_pair(sibling, &node)
+        };
+        index /= 2;
+    }
+    index == 0 && node == *root
+}
+
+fn leaves(properties: &[Properties], depth: usize) -> Vec<[u8; 32]> {
+    let mut leaves: Vec<[u8; 32]> = properties.iter().map(property_leaf).collect();
+    leaves.resize(1 << depth, [0u8; 32]);
+    leaves
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Poseidon::hashv(&[left, right]).unwrap()
+}
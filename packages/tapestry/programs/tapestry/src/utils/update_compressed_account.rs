@@ -0,0 +1,51 @@
+use crate::state::{AnchorCompressedAccountMeta, GroupAuthority};
+use crate::utils::output_compressed_account;
+use crate::AnchorCompressedProof;
+use anchor_lang::prelude::*;
+use light_hasher::{DataHasher, Discriminator};
+use light_sdk::compressed_account::PackedCompressedAccountWithMerkleContext;
+use light_sdk::proof::CompressedProof;
+use light_sdk::utils::create_cpi_inputs_for_account_update;
+use light_sdk::verify::InstructionDataInvokeCpi;
+
+// CPI inputs replacing a proven account with `updated_account` at the same
+// address and with the same lamports. The group node an update was authorized
+// by is proven alongside and written back unchanged.
+pub fn update_cpi_inputs<T>(
+    proof: AnchorCompressedProof,
+    input_compressed_account: PackedCompressedAccountWithMerkleContext,
+    updated_account: &T,
+    meta: &AnchorCompressedAccountMeta,
+    group: Option<&GroupAuthority>,
+) -> Result<InstructionDataInvokeCpi>
+where
+    T: AnchorSerialize + DataHasher + Discriminator,
+{
+    let mut output_compressed_account = output_compressed_account(
+        updated_account,
+        &meta.address,
+        &crate::ID,
+        &meta.merkle_context(),
+    )?;
+    output_compressed_account.compressed_account.lamports = meta.lamports;
+
+    let mut cpi_inputs = create_cpi_inputs_for_account_update(
+        CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        input_compressed_account,
+        output_compressed_account,
+        None,
+    );
+    if let Some(group) = group {
+        let (group_input, group_output) = group.proven_compressed_accounts()?;
+        cpi_inputs
+            .input_compressed_accounts_with_merkle_context
+            .push(group_input);
+        cpi_inputs.output_compressed_accounts.push(group_output);
+    }
+
+    Ok(cpi_inputs)
+}
@@ -0,0 +1,68 @@
+use crate::errors::ZkNftError;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+
+// Layout of the ed25519 program instruction data: a signature count and a
+// padding byte, followed by seven u16 offsets per signature
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_SIZE: usize = 14;
+const PUBKEY_SIZE: usize = 32;
+// Instruction index the offsets use to point into their own instruction
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+// The ed25519 program has checked the signature by the time this runs, so only
+// the signer and the message are left to check. Expects a single signature in
+// the instruction right before the current one, with all of its data inside
+// that instruction, as built by `new_ed25519_instruction`.
+pub fn verify_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, ZkNftError::MissingEd25519Signature);
+
+    let instruction =
+        load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+    require_keys_eq!(
+        instruction.program_id,
+        ed25519_program::ID,
+        ZkNftError::MissingEd25519Signature
+    );
+
+    let data = instruction.data.as_slice();
+    require!(
+        data.len() >= SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SIZE && data[0] == 1,
+        ZkNftError::InvalidEd25519Signature
+    );
+    let offsets = &data[SIGNATURE_OFFSETS_START..SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SIZE];
+    let offset = |index: usize| u16::from_le_bytes([offsets[2 * index], offsets[2 * index + 1]]);
+
+    // Offsets are signature, signature instruction, public key, public key
+    // instruction, message, message size and message instruction
+    require!(
+        offset(1) == CURRENT_INSTRUCTION
+            && offset(3) == CURRENT_INSTRUCTION
+            && offset(6) == CURRENT_INSTRUCTION,
+        ZkNftError::InvalidEd25519Signature
+    );
+    let public_key_offset = offset(2) as usize;
+    let message_offset = offset(4) as usize;
+    let message_size = offset(5) as usize;
+
+    let signed_public_key = data
+        .get(public_key_offset..public_key_offset + PUBKEY_SIZE)
+        .ok_or(ZkNftError::InvalidEd25519Signature)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(ZkNftError::InvalidEd25519Signature)?;
+    require!(
+        signed_public_key == signer.as_ref() && signed_message == message,
+        ZkNftError::InvalidEd25519Signature
+    );
+
+    Ok(())
+}
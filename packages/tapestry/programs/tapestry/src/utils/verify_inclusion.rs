@@ -0,0 +1,54 @@
+use crate::errors::ZkNftError;
+use crate::AnchorCompressedProof;
+use account_compression::{queue_from_bytes_zero_copy_mut, state_merkle_tree_from_bytes_zero_copy};
+use anchor_lang::prelude::*;
+use light_hasher::Poseidon;
+use light_sdk::compressed_account::PackedCompressedAccountWithMerkleContext;
+use light_verifier::{verify_merkle_proof_zkp, CompressedProof};
+use num_bigint::BigUint;
+
+// Verifies that `account` is in the state tree without consuming it, for
+// instructions that only read an account. The proof has to be against a root
+// in the tree's root history. Nullified leaves stay in older roots until the
+// nullifier queue moves past them, so the account must not be in the queue.
+pub fn verify_inclusion(
+    proof: &AnchorCompressedProof,
+    account: &PackedCompressedAccountWithMerkleContext,
+    merkle_tree: &AccountInfo,
+    nullifier_queue: &AccountInfo,
+) -> Result<()> {
+    let leaf = account
+        .compressed_account
+        .hash::<Poseidon>(&merkle_tree.key(), &account.merkle_context.leaf_index)
+        .map_err(|_| error!(ZkNftError::InvalidInclusionProof))?;
+
+    let root = {
+        let data = merkle_tree.try_borrow_data()?;
+        let tree = state_merkle_tree_from_bytes_zero_copy(&data)?;
+        *tree
+            .roots
+            .get(account.root_index as usize)
+            .ok_or(ZkNftError::InvalidInclusionProof)?
+    };
+
+    {
+        // The queue is only read, the borrow is mutable for the zero-copy view
+        let mut data = nullifier_queue.try_borrow_mut_data()?;
+        let queue = unsafe { queue_from_bytes_zero_copy_mut(&mut data)? };
+        let nullified = queue
+            .contains(&BigUint::from_bytes_be(&leaf), None)
+            .map_err(|_| error!(ZkNftError::InvalidInclusionProof))?;
+        require!(!nullified, ZkNftError::AccountNullified);
+    }
+
+    verify_merkle_proof_zkp(
+        &[root],
+        &[leaf],
+        &CompressedProof {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+    )
+    .map_err(|_| error!(ZkNftError::InvalidInclusionProof))
+}
@@ -0,0 +1,86 @@
+//! Fixtures shared by the integration tests, and by the client builder tests
+//! through `#[path]`. Each test crate only uses some of them.
+#![allow(dead_code)]
+
+use anchor_lang::prelude::Pubkey;
+use tapestry::state::{
+    AccountKey, Creator, EdgeArgs, EdgeData, EdgeDataV2, EdgeV1, EdgeV2, EdgeValidity, NodeArgs,
+    NodeData, NodeUpdateAuthority, NodeV1, NodeV2, Properties, TokenGate,
+};
+
+pub fn properties() -> Vec<Properties> {
+    vec![Properties {
+        key: "bio".to_string(),
+        value: "hello".to_string(),
+    }]
+}
+
+pub fn creators() -> Vec<Creator> {
+    vec![Creator {
+        address: Pubkey::new_unique(),
+        verified: false,
+        share: 100,
+    }]
+}
+
+pub fn node_args(label: &str) -> NodeArgs {
+    NodeArgs {
+        label: label.to_string(),
+        properties: properties(),
+        is_mutable: true,
+        creators: creators(),
+        namespace: "social".to_string(),
+        encrypted_properties: vec![],
+        token_gate: TokenGate::None,
+    }
+}
+
+pub fn edge_args(source_node: &str, target_node: &str) -> EdgeArgs {
+    EdgeArgs {
+        source_node: source_node.to_string(),
+        target_node: target_node.to_string(),
+        edge_type: "follow".to_string(),
+        properties: vec![],
+        is_mutable: true,
+        weight: 10,
+        validity: EdgeValidity::default(),
+        namespace: "social".to_string(),
+    }
+}
+
+// Ungated, uncounted accounts without an update authority, as `create_node`
+// and `create_edge` write them
+pub fn node(node_args: NodeArgs, owner: Pubkey) -> NodeV2 {
+    NodeV2::new(node_args, owner, NodeUpdateAuthority::None)
+}
+
+pub fn edge(edge_args: EdgeArgs, owner: Pubkey) -> EdgeV2 {
+    let edge_data = EdgeDataV2::new(&edge_args.properties, TokenGate::None, false);
+    EdgeV2::new(edge_args, edge_data, owner, NodeUpdateAuthority::None)
+}
+
+// Accounts in the layouts deployed before the V2 migration
+pub fn legacy_node(node_args: NodeArgs, owner: Pubkey) -> NodeV1 {
+    NodeV1 {
+        key: AccountKey::NodeV1,
+        owner,
+        update_authority: NodeUpdateAuthority::None,
+        label: node_args.label,
+        node_data: NodeData::new(&node_args.properties, &node_args.creators),
+        is_mutable: node_args.is_mutable,
+        initialized_plugins: 0,
+    }
+}
+
+pub fn legacy_edge(edge_args: EdgeArgs, owner: Pubkey) -> EdgeV1 {
+    EdgeV1 {
+        key: AccountKey::EdgeV1,
+        source_node: edge_args.source_node,
+        target_node: edge_args.target_node,
+        edge_data: EdgeData::new(&edge_args.properties),
+        is_mutable: edge_args.is_mutable,
+        owner,
+        update_authority: NodeUpdateAuthority::None,
+        initialized_plugins: 0,
+    }
+}
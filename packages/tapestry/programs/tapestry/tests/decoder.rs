@@ -0,0 +1,310 @@
+#![cfg(feature = "no-entrypoint")]
+
+//! The decoder is only compiled with `no-entrypoint`, run these tests with
+//! `cargo test -p tapestry --features no-entrypoint --test decoder`.
+
+mod common;
+
+use anchor_lang::prelude::{AnchorSerialize, Pubkey};
+use common::{edge_args, legacy_edge, legacy_node, node_args, properties};
+use light_hasher::{DataHasher, Discriminator, Poseidon};
+use tapestry::decoder::{decode_account, decode_and_verify_account, TapestryAccount};
+use tapestry::errors::ZkNftError;
+use tapestry::state::{
+    AccountKey, DecompressedNodeV1, EdgeArgs, EdgeDataV2, EdgeV2, EdgeValidity, EncryptedProperty,
+    ExternalIdentity, IdentityLinkV1, IdentityProvider, NodeArgs, NodeCounterV1,
+    NodeUpdateAuthority, NodeV1, NodeV2, PendingEdgeV1, TokenGate, VersionedEdge, VersionedNode,
+};
+
+// Fixtures with every optional part set, so decoding covers all of them
+fn full_node_args() -> NodeArgs {
+    NodeArgs {
+        encrypted_properties: vec![EncryptedProperty {
+            key: "email".to_string(),
+            recipient: Pubkey::new_from_array([4; 32]),
+            ephemeral_public_key: [1; 32],
+            nonce: [2; 24],
+            ciphertext: vec![3; 48],
+        }],
+        ..node_args("alice")
+    }
+}
+
+fn full_edge_args() -> EdgeArgs {
+    EdgeArgs {
+        properties: properties(),
+        validity: EdgeValidity {
+            valid_from: Some(1_700_000_000),
+            valid_until: None,
+        },
+        ..edge_args("alice", "bob")
+    }
+}
+
+fn node() -> NodeV2 {
+    NodeV2::new(
+        full_node_args(),
+        Pubkey::new_unique(),
+        NodeUpdateAuthority::Address(Pubkey::new_unique()),
+    )
+}
+
+fn edge() -> EdgeV2 {
+    let edge_args = full_edge_args();
+    let edge_data = EdgeDataV2::new(&edge_args.properties, TokenGate::None, true);
+    EdgeV2::new(
+        edge_args,
+        edge_data,
+        Pubkey::new_unique(),
+        NodeUpdateAuthority::None,
+    )
+}
+
+fn pending_edge() -> PendingEdgeV1 {
+    let edge_args = full_edge_args();
+    PendingEdgeV1 {
+        key: AccountKey::PendingEdgeV1,
+        source_node: edge_args.source_node,
+        target_node: edge_args.target_node,
+        edge_type: edge_args.edge_type,
+        namespace: edge_args.namespace,
+        edge_data: EdgeDataV2::new(&edge_args.properties, TokenGate::None, false),
+        weight: edge_args.weight,
+        validity: edge_args.validity,
+        is_mutable: edge_args.is_mutable,
+        owner: Pubkey::new_unique(),
+        target_owner: Pubkey::new_unique(),
+        update_authority: NodeUpdateAuthority::None,
+    }
+}
+
+// Decodes the serialized account under its own discriminator, checking it
+// against its data hash like an indexer would
+fn decode<T: AnchorSerialize + DataHasher + Discriminator>(account: &T) -> TapestryAccount {
+    decode_and_verify_account(
+        &T::discriminator(),
+        &account.try_to_vec().unwrap(),
+        &account.hash::<Poseidon>().unwrap(),
+    )
+    .unwrap()
+}
+
+fn assert_decoder_error<T: std::fmt::Debug>(result: anchor_lang::Result<T>, error: ZkNftError) {
+    match result {
+        Err(anchor_lang::error::Error::AnchorError(actual)) => {
+            assert_eq!(actual.error_code_number, u32::from(error))
+        }
+        other => panic!("expected error {:?}, got {:?}", error, other),
+    }
+}
+
+#[test]
+fn test_decode_node_v1() {
+    let node = legacy_node(full_node_args(), Pubkey::new_unique());
+    match decode(&node) {
+        TapestryAccount::Node(decoded) => {
+            match &decoded.node {
+                VersionedNode::V1(decoded_node) => {
+                    assert_eq!(
+                        decoded_node.try_to_vec().unwrap(),
+                        node.try_to_vec().unwrap()
+                    )
+                }
+                other => panic!("expected a V1 node, got {:?}", other),
+            }
+            assert_eq!(decoded.properties, properties());
+            assert_eq!(decoded.creators.len(), 1);
+            assert!(decoded.encrypted_properties.is_empty());
+        }
+        other => panic!("expected a node, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_node_v2() {
+    let node = node();
+    match decode(&node) {
+        TapestryAccount::Node(decoded) => {
+            match &decoded.node {
+                VersionedNode::V2(decoded_node) => {
+                    assert_eq!(
+                        decoded_node.try_to_vec().unwrap(),
+                        node.try_to_vec().unwrap()
+                    )
+                }
+                other => panic!("expected a V2 node, got {:?}", other),
+            }
+            assert_eq!(decoded.properties, properties());
+            assert_eq!(decoded.creators.len(), 1);
+            assert_eq!(
+                decoded.encrypted_properties,
+                full_node_args().encrypted_properties
+            );
+        }
+        other => panic!("expected a node, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_edge_v1() {
+    let edge = legacy_edge(full_edge_args(), Pubkey::new_unique());
+    match decode(&edge) {
+        TapestryAccount::Edge(decoded) => {
+            match &decoded.edge {
+                VersionedEdge::V1(decoded_edge) => {
+                    assert_eq!(
+                        decoded_edge.try_to_vec().unwrap(),
+                        edge.try_to_vec().unwrap()
+                    )
+                }
+                other => panic!("expected a V1 edge, got {:?}", other),
+            }
+            assert_eq!(decoded.properties, properties());
+        }
+        other => panic!("expected an edge, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_edge_v2() {
+    let edge = edge();
+    match decode(&edge) {
+        TapestryAccount::Edge(decoded) => {
+            match &decoded.edge {
+                VersionedEdge::V2(decoded_edge) => {
+                    assert_eq!(
+                        decoded_edge.try_to_vec().unwrap(),
+                        edge.try_to_vec().unwrap()
+                    )
+                }
+                other => panic!("expected a V2 edge, got {:?}", other),
+            }
+            assert_eq!(decoded.properties, properties());
+        }
+        other => panic!("expected an edge, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_pending_edge() {
+    let pending_edge = pending_edge();
+    match decode(&pending_edge) {
+        TapestryAccount::PendingEdge(decoded) => {
+            assert_eq!(
+                decoded.pending_edge.try_to_vec().unwrap(),
+                pending_edge.try_to_vec().unwrap()
+            );
+            assert_eq!(decoded.properties, properties());
+        }
+        other => panic!("expected a pending edge, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_node_counter() {
+    let counter = NodeCounterV1 {
+        key: AccountKey::NodeCounterV1,
+        node: "alice".to_string(),
+        edge_type: "follow".to_string(),
+        counted_in_edges: 3,
+        counted_out_edges: 5,
+    };
+    match decode(&counter) {
+        TapestryAccount::NodeCounter(decoded) => {
+            assert_eq!(decoded.try_to_vec().unwrap(), counter.try_to_vec().unwrap())
+        }
+        other => panic!("expected a node counter, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_decompressed_node() {
+    let placeholder = DecompressedNodeV1 {
+        key: AccountKey::DecompressedNodeV1,
+        node_account: Pubkey::new_unique(),
+    };
+    match decode(&placeholder) {
+        TapestryAccount::DecompressedNode(decoded) => {
+            assert_eq!(decoded.node_account, placeholder.node_account)
+        }
+        other => panic!("expected a decompressed node, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_identity_link() {
+    let identity_link = IdentityLinkV1 {
+        key: AccountKey::IdentityLinkV1,
+        node: "alice".to_string(),
+        identity: ExternalIdentity::new(IdentityProvider::Twitter, "alice"),
+        attestation_authority: Pubkey::new_unique(),
+    };
+    match decode(&identity_link) {
+        TapestryAccount::IdentityLink(decoded) => {
+            assert_eq!(
+                decoded.try_to_vec().unwrap(),
+                identity_link.try_to_vec().unwrap()
+            )
+        }
+        other => panic!("expected an identity link, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_unknown_discriminator() {
+    let node = node();
+    let data = node.try_to_vec().unwrap();
+
+    assert_decoder_error(
+        decode_account(&[0; 8], &data),
+        ZkNftError::UnknownAccountDiscriminator,
+    );
+
+    // Discriminators are matched exactly, not by prefix
+    let mut discriminator = NodeV2::discriminator();
+    discriminator[0] ^= 1;
+    assert_decoder_error(
+        decode_account(&discriminator, &data),
+        ZkNftError::UnknownAccountDiscriminator,
+    );
+}
+
+#[test]
+fn test_decode_invalid_nested_data() {
+    let mut node = legacy_node(full_node_args(), Pubkey::new_unique());
+    node.node_data.properties_bytes = vec![0xff; 3];
+
+    assert_decoder_error(
+        decode_account(&NodeV1::discriminator(), &node.try_to_vec().unwrap()),
+        ZkNftError::InvalidAccountData,
+    );
+}
+
+#[test]
+fn test_decode_data_hash_mismatch() {
+    let node = node();
+    let data_hash = node.hash::<Poseidon>().unwrap();
+
+    // Data changed after the hash was committed, e.g. by an untrusted indexer
+    let mut tampered = node.clone();
+    tampered.label = "mallory".to_string();
+    assert_decoder_error(
+        decode_and_verify_account(
+            &NodeV2::discriminator(),
+            &tampered.try_to_vec().unwrap(),
+            &data_hash,
+        ),
+        ZkNftError::DataHashMismatch,
+    );
+
+    // Hash of another account
+    let edge = edge();
+    assert_decoder_error(
+        decode_and_verify_account(
+            &EdgeV2::discriminator(),
+            &edge.try_to_vec().unwrap(),
+            &data_hash,
+        ),
+        ZkNftError::DataHashMismatch,
+    );
+}
@@ -0,0 +1,654 @@
+//! Hashing and seed derivation must stay byte-identical to the TS SDK, otherwise
+//! client-derived addresses diverge from the ones the program creates. Golden
+//! vectors in `fixtures/hash_vectors.json` are shared with `tests/hash-vectors.test.ts`.
+
+mod common;
+
+use anchor_lang::prelude::{AnchorDeserialize, AnchorSerialize, Pubkey};
+use common::{edge, legacy_edge, legacy_node, node};
+use light_hasher::bytes::AsByteVec;
+use light_hasher::{DataHasher, Poseidon};
+use light_sdk::address::derive_address;
+use light_sdk::merkle_context::AddressMerkleContext;
+use light_utils::hashv_to_bn254_field_size_be;
+use proptest::prelude::*;
+use serde_json::Value;
+use tapestry::state::{
+    AccountKey, Creator, EdgeArgs, EdgeDataV2, EdgeDirection, EdgeV1, EdgeV2, EdgeValidity,
+    EncryptedProperty, ExternalIdentity, IdentityLinkV1, IdentityProvider, NodeArgs, NodeDataV2,
+    NodeV1, NodeV2, Properties, SignedAction, TokenGate,
+};
+use tapestry::utils::{
+    get_account_seed, get_derived_account_seed, get_edge_pair_seed, get_external_id_seed,
+    get_identity_link_seed, get_label_seed, get_undirected_edge_seed, property_tree_proof,
+    property_tree_root, verify_property_proof, ADDRESS_MERKLE_TREE_PUBKEY, ADDRESS_QUEUE_PUBKEY,
+    PROPERTY_TREE_DEPTH,
+};
+
+const ACCOUNT_KEYS: [AccountKey; 8] = [
+    AccountKey::NodeV1,
+    AccountKey::EdgeV1,
+    AccountKey::PendingEdgeV1,
+    AccountKey::NodeV2,
+    AccountKey::EdgeV2,
+    AccountKey::NodeCounterV1,
+    AccountKey::DecompressedNodeV1,
+    AccountKey::IdentityLinkV1,
+];
+
+fn hash_vectors() -> Value {
+    serde_json::from_str(include_str!("fixtures/hash_vectors.json")).unwrap()
+}
+
+fn account_key(name: &str) -> AccountKey {
+    match name {
+        "NodeV1" => AccountKey::NodeV1,
+        "EdgeV1" => AccountKey::EdgeV1,
+        "PendingEdgeV1" => AccountKey::PendingEdgeV1,
+        "NodeV2" => AccountKey::NodeV2,
+        "EdgeV2" => AccountKey::EdgeV2,
+        "NodeCounterV1" => AccountKey::NodeCounterV1,
+        "DecompressedNodeV1" => AccountKey::DecompressedNodeV1,
+        "IdentityLinkV1" => AccountKey::IdentityLinkV1,
+        _ => panic!("unknown account key {}", name),
+    }
+}
+
+fn bytes32(value: &Value) -> [u8; 32] {
+    hex::decode(value.as_str().unwrap())
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+fn new_address(seed: &[u8; 32]) -> [u8; 32] {
+    derive_address(
+        seed,
+        &AddressMerkleContext {
+            address_merkle_tree_pubkey: ADDRESS_MERKLE_TREE_PUBKEY,
+            address_queue_pubkey: ADDRESS_QUEUE_PUBKEY,
+        },
+    )
+}
+
+fn arb_pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+}
+
+fn arb_property() -> impl Strategy<Value = Properties> {
+    (".{0,16}", ".{0,16}").prop_map(|(key, value)| Properties { key, value })
+}
+
+fn arb_properties() -> impl Strategy<Value = Vec<Properties>> {
+    prop::collection::vec(arb_property(), 0..4)
+}
+
+fn arb_encrypted_property() -> impl Strategy<Value = EncryptedProperty> {
+    (
+        "[a-z]{1,16}",
+        arb_pubkey(),
+        any::<[u8; 32]>(),
+        any::<[u8; 24]>(),
+        prop::collection::vec(any::<u8>(), 1..64),
+    )
+        .prop_map(
+            |(key, recipient, ephemeral_public_key, nonce, ciphertext)| EncryptedProperty {
+                key,
+                recipient,
+                ephemeral_public_key,
+                nonce,
+                ciphertext,
+            },
+        )
+}
+
+fn arb_token_gate() -> impl Strategy<Value = TokenGate> {
+    prop_oneof![
+        Just(TokenGate::None),
+        (arb_pubkey(), any::<u64>())
+            .prop_map(|(mint, min_amount)| TokenGate::Mint { mint, min_amount }),
+        (arb_pubkey(), any::<u64>()).prop_map(|(collection, min_amount)| {
+            TokenGate::Collection {
+                collection,
+                min_amount,
+            }
+        }),
+    ]
+}
+
+fn arb_node_args() -> impl Strategy<Value = NodeArgs> {
+    (
+        // The label is hashed without truncation, so it has to fit a field element
+        "[a-z0-9-]{0,31}",
+        arb_properties(),
+        any::<bool>(),
+        prop::collection::vec(
+            (arb_pubkey(), any::<bool>(), 0..=100u8).prop_map(|(address, verified, share)| {
+                Creator {
+                    address,
+                    verified,
+                    share,
+                }
+            }),
+            0..3,
+        ),
+        "[a-z]{0,32}",
+        prop::collection::vec(arb_encrypted_property(), 0..3),
+        arb_token_gate(),
+    )
+        .prop_map(
+            |(
+                label,
+                properties,
+                is_mutable,
+                creators,
+                namespace,
+                encrypted_properties,
+                token_gate,
+            )| NodeArgs {
+                label,
+                properties,
+                is_mutable,
+                creators,
+                namespace,
+                encrypted_properties,
+                token_gate,
+            },
+        )
+}
+
+fn arb_edge_args() -> impl Strategy<Value = EdgeArgs> {
+    (
+        "[a-z0-9-]{1,32}",
+        "[a-z0-9-]{1,32}",
+        "[a-z]{1,32}",
+        arb_properties(),
+        any::<bool>(),
+        any::<u64>(),
+        (any::<Option<i64>>(), any::<Option<i64>>()),
+        "[a-z]{0,32}",
+    )
+        .prop_map(
+            |(
+                source_node,
+                target_node,
+                edge_type,
+                properties,
+                is_mutable,
+                weight,
+                validity,
+                namespace,
+            )| {
+                EdgeArgs {
+                    source_node,
+                    target_node,
+                    edge_type,
+                    properties,
+                    is_mutable,
+                    weight,
+                    validity: EdgeValidity {
+                        valid_from: validity.0,
+                        valid_until: validity.1,
+                    },
+                    namespace,
+                }
+            },
+        )
+}
+
+#[test]
+fn test_account_seed_golden_vectors() {
+    let vectors = hash_vectors();
+    for vector in vectors["account_seeds"].as_array().unwrap() {
+        let key = account_key(vector["account_key"].as_str().unwrap());
+        let seed = get_account_seed(key, &bytes32(&vector["asset_id"]));
+        assert_eq!(seed, bytes32(&vector["seed"]));
+        assert_eq!(new_address(&seed), bytes32(&vector["address"]));
+    }
+}
+
+#[test]
+fn test_as_byte_vec_golden_vectors() {
+    let vectors = hash_vectors();
+    for vector in vectors["account_keys"].as_array().unwrap() {
+        let key = account_key(vector["account_key"].as_str().unwrap());
+        assert_eq!(key.as_byte_vec(), vec![bytes32(&vector["hash"]).to_vec()]);
+    }
+    for vector in vectors["properties"].as_array().unwrap() {
+        let properties = Properties {
+            key: vector["key"].as_str().unwrap().to_string(),
+            value: vector["value"].as_str().unwrap().to_string(),
+        };
+        assert_eq!(
+            properties.as_byte_vec(),
+            vec![bytes32(&vector["hash"]).to_vec()]
+        );
+    }
+}
+
+#[test]
+fn test_derived_seed_golden_vectors() {
+    let vectors = hash_vectors();
+    for vector in vectors["derived_seeds"].as_array().unwrap() {
+        let namespace = vector["namespace"].as_str().unwrap();
+        let inputs: Vec<&str> = vector["inputs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|input| input.as_str().unwrap())
+            .collect();
+        let seed = match (vector["kind"].as_str().unwrap(), inputs.as_slice()) {
+            ("label", [label]) => get_label_seed(namespace, label),
+            ("edge_pair", [source_node, target_node, edge_type]) => {
+                get_edge_pair_seed(namespace, source_node, target_node, edge_type)
+            }
+            ("external_id", [external_id]) => get_external_id_seed(
+                account_key(vector["account_key"].as_str().unwrap()),
+                namespace,
+                external_id,
+            ),
+            ("undirected_edge", [node_a, node_b, edge_type]) => {
+                get_undirected_edge_seed(namespace, node_a, node_b, edge_type)
+            }
+            (kind, _) => panic!("invalid derived seed vector {}", kind),
+        };
+        assert_eq!(seed, bytes32(&vector["seed"]));
+        assert_eq!(new_address(&seed), bytes32(&vector["address"]));
+    }
+}
+
+#[test]
+fn test_account_hash_golden_vectors() {
+    let vectors = hash_vectors();
+    for vector in vectors["account_hashes"].as_array().unwrap() {
+        let data = hex::decode(vector["data"].as_str().unwrap()).unwrap();
+        let (hash, serialized) = match account_key(vector["account_key"].as_str().unwrap()) {
+            AccountKey::NodeV1 => {
+                let node = NodeV1::deserialize(&mut data.as_slice()).unwrap();
+                (node.hash::<Poseidon>().unwrap(), node.try_to_vec().unwrap())
+            }
+            AccountKey::EdgeV1 => {
+                let edge = EdgeV1::deserialize(&mut data.as_slice()).unwrap();
+                (edge.hash::<Poseidon>().unwrap(), edge.try_to_vec().unwrap())
+            }
+            AccountKey::NodeV2 => {
+                let node = NodeV2::deserialize(&mut data.as_slice()).unwrap();
+                (node.hash::<Poseidon>().unwrap(), node.try_to_vec().unwrap())
+            }
+            AccountKey::EdgeV2 => {
+                let edge = EdgeV2::deserialize(&mut data.as_slice()).unwrap();
+                (edge.hash::<Poseidon>().unwrap(), edge.try_to_vec().unwrap())
+            }
+            key => panic!("no hash vector layout for {:?}", key),
+        };
+        assert_eq!(hash, bytes32(&vector["hash"]));
+        assert_eq!(serialized, data);
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_account_seed_is_deterministic_field_element(asset_id in any::<[u8; 32]>()) {
+        for key in ACCOUNT_KEYS {
+            let seed = get_account_seed(key, &asset_id);
+            prop_assert_eq!(seed, get_account_seed(key, &asset_id));
+            // Seeds are truncated to 31 bytes to fit the bn254 field
+            prop_assert_eq!(seed[0], 0);
+        }
+    }
+
+    #[test]
+    fn test_account_seed_differs_across_account_keys(asset_id in any::<[u8; 32]>()) {
+        for (i, key) in ACCOUNT_KEYS.iter().enumerate() {
+            for other_key in ACCOUNT_KEYS.iter().skip(i + 1) {
+                prop_assert_ne!(
+                    get_account_seed(*key, &asset_id),
+                    get_account_seed(*other_key, &asset_id)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_account_seed_differs_across_asset_ids(
+        asset_id in any::<[u8; 32]>(),
+        other_asset_id in any::<[u8; 32]>(),
+    ) {
+        prop_assume!(asset_id != other_asset_id);
+        prop_assert_ne!(
+            get_account_seed(AccountKey::NodeV1, &asset_id),
+            get_account_seed(AccountKey::NodeV1, &other_asset_id)
+        );
+    }
+
+    #[test]
+    fn test_derived_seeds_are_unreachable_from_account_seeds(hash in any::<[u8; 32]>()) {
+        for key in ACCOUNT_KEYS {
+            prop_assert_ne!(get_derived_account_seed(key, &hash), get_account_seed(key, &hash));
+        }
+    }
+
+    #[test]
+    fn test_random_seeds_cannot_reach_address_seed_variants(
+        namespace in "[a-z]{0,32}",
+        name in "[a-z0-9-]{1,32}",
+    ) {
+        // Random bytes equal to the inner hash of a label land in the plain
+        // domain, not on the label's address
+        let label_bytes = ("label", namespace.as_str(), name.as_str()).try_to_vec().unwrap();
+        let label_hash = hashv_to_bn254_field_size_be(&[&label_bytes]);
+        prop_assert_eq!(
+            get_derived_account_seed(AccountKey::NodeV1, &label_hash),
+            get_label_seed(&namespace, &name)
+        );
+        prop_assert_ne!(
+            get_account_seed(AccountKey::NodeV1, &label_hash),
+            get_label_seed(&namespace, &name)
+        );
+    }
+
+    #[test]
+    fn test_undirected_edge_seed_is_symmetric(
+        namespace in "[a-z]{0,32}",
+        node_a in "[a-z0-9-]{1,32}",
+        node_b in "[a-z0-9-]{1,32}",
+        edge_type in "[a-z]{1,32}",
+    ) {
+        prop_assert_eq!(
+            get_undirected_edge_seed(&namespace, &node_a, &node_b, &edge_type),
+            get_undirected_edge_seed(&namespace, &node_b, &node_a, &edge_type)
+        );
+        prop_assert_ne!(
+            get_undirected_edge_seed(&namespace, &node_a, &node_b, &edge_type),
+            get_undirected_edge_seed(&format!("{}-x", namespace), &node_a, &node_b, &edge_type)
+        );
+    }
+
+    #[test]
+    fn test_derived_seeds_are_domain_separated(
+        namespace in "[a-z]{0,32}",
+        name in "[a-z0-9-]{1,32}",
+    ) {
+        prop_assert_ne!(
+            get_label_seed(&namespace, &name),
+            get_external_id_seed(AccountKey::NodeV1, &namespace, &name)
+        );
+    }
+
+    #[test]
+    fn test_edge_pair_seed_is_directed(
+        node_a in "[a-z0-9-]{1,32}",
+        node_b in "[a-z0-9-]{1,32}",
+        edge_type in "[a-z]{1,32}",
+    ) {
+        prop_assume!(node_a != node_b);
+        prop_assert_ne!(
+            get_edge_pair_seed("social", &node_a, &node_b, &edge_type),
+            get_edge_pair_seed("social", &node_b, &node_a, &edge_type)
+        );
+    }
+
+    #[test]
+    fn test_identity_link_seed_differs_across_providers(external_id in "[a-z0-9_]{1,32}") {
+        let twitter = ExternalIdentity::new(IdentityProvider::Twitter, &external_id);
+        let ens = ExternalIdentity::new(IdentityProvider::Ens, &external_id);
+        prop_assert_eq!(twitter.external_id_hash, ens.external_id_hash);
+        prop_assert_ne!(get_identity_link_seed(&twitter), get_identity_link_seed(&ens));
+    }
+
+    #[test]
+    fn test_attestation_message_binds_node_and_identity(
+        node_a in "[a-z0-9-]{1,32}",
+        node_b in "[a-z0-9-]{1,32}",
+        external_id in "[a-z0-9_]{1,32}",
+    ) {
+        prop_assume!(node_a != node_b);
+        let identity = ExternalIdentity::new(IdentityProvider::Twitter, &external_id);
+        let other_identity =
+            ExternalIdentity::new(IdentityProvider::Twitter, &format!("{}_", external_id));
+        let message = IdentityLinkV1::attestation_message(&node_a, &identity);
+        prop_assert_ne!(&message, &IdentityLinkV1::attestation_message(&node_b, &identity));
+        prop_assert_ne!(&message, &IdentityLinkV1::attestation_message(&node_a, &other_identity));
+    }
+
+    #[test]
+    fn test_signed_action_message_binds_action_and_nonce(
+        signer in arb_pubkey(),
+        nonce in 0..u64::MAX,
+        expires_at in any::<i64>(),
+        args in proptest::collection::vec(any::<u8>(), 0..64),
+    ) {
+        let signed_action = SignedAction { nonce, expires_at };
+        let message = signed_action.message("create_node", &signer, &args);
+        prop_assert_ne!(&message, &signed_action.message("create_edge", &signer, &args));
+        let next = SignedAction { nonce: nonce + 1, ..signed_action };
+        prop_assert_ne!(&message, &next.message("create_node", &signer, &args));
+    }
+
+    #[test]
+    fn test_node_hash_is_stable(node_args in arb_node_args(), owner in arb_pubkey()) {
+        let node = node(node_args.clone(), owner);
+        let hash = node.hash::<Poseidon>().unwrap();
+        prop_assert_eq!(hash, node.clone().hash::<Poseidon>().unwrap());
+        prop_assert_eq!(hash, self::node(node_args, owner).hash::<Poseidon>().unwrap());
+    }
+
+    #[test]
+    fn test_node_hash_commits_to_account_key(node_args in arb_node_args(), owner in arb_pubkey()) {
+        let node = node(node_args, owner);
+        let hash = node.hash::<Poseidon>().unwrap();
+        for key in ACCOUNT_KEYS.iter().filter(|key| **key != AccountKey::NodeV2) {
+            let other = NodeV2 { key: *key, ..node.clone() };
+            prop_assert_ne!(hash, other.hash::<Poseidon>().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_node_hash_commits_to_truncated_owner(
+        node_args in arb_node_args(),
+        owner in arb_pubkey(),
+        other_owner in arb_pubkey(),
+    ) {
+        prop_assume!(owner != other_owner);
+        prop_assert_ne!(
+            node(node_args.clone(), owner).hash::<Poseidon>().unwrap(),
+            node(node_args, other_owner).hash::<Poseidon>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_edge_hash_is_stable(edge_args in arb_edge_args(), owner in arb_pubkey()) {
+        let edge = edge(edge_args.clone(), owner);
+        let hash = edge.hash::<Poseidon>().unwrap();
+        prop_assert_eq!(hash, self::edge(edge_args, owner).hash::<Poseidon>().unwrap());
+    }
+
+    #[test]
+    fn test_edge_hash_commits_to_fields(
+        edge_args in arb_edge_args(),
+        owner in arb_pubkey(),
+        weight in any::<u64>(),
+    ) {
+        let edge = edge(edge_args, owner);
+        let hash = edge.hash::<Poseidon>().unwrap();
+
+        prop_assume!(weight != edge.weight);
+        let reweighted = EdgeV2 { weight, ..edge.clone() };
+        prop_assert_ne!(hash, reweighted.hash::<Poseidon>().unwrap());
+
+        let retyped = EdgeV2 { edge_type: format!("{}-x", edge.edge_type), ..edge.clone() };
+        prop_assert_ne!(hash, retyped.hash::<Poseidon>().unwrap());
+
+        let renamespaced = EdgeV2 { namespace: format!("{}-x", edge.namespace), ..edge.clone() };
+        prop_assert_ne!(hash, renamespaced.hash::<Poseidon>().unwrap());
+
+        let undirected = EdgeV2 { direction: EdgeDirection::Undirected, ..edge.clone() };
+        prop_assert_ne!(hash, undirected.hash::<Poseidon>().unwrap());
+
+        let pending = EdgeV2 { key: AccountKey::PendingEdgeV1, ..edge };
+        prop_assert_ne!(hash, pending.hash::<Poseidon>().unwrap());
+    }
+
+    #[test]
+    fn test_node_migration_preserves_fields(node_args in arb_node_args(), owner in arb_pubkey()) {
+        let legacy = legacy_node(node_args, owner);
+        let migrated = NodeV2::from(legacy.clone());
+        prop_assert_eq!(migrated.key, AccountKey::NodeV2);
+        prop_assert_eq!(migrated.owner, legacy.owner);
+        prop_assert_eq!(&migrated.label, &legacy.label);
+        prop_assert_eq!(migrated.namespace.as_str(), "");
+        prop_assert_eq!(
+            migrated.node_data.get_properties().unwrap(),
+            legacy.node_data.get_properties().unwrap()
+        );
+        prop_assert_ne!(
+            migrated.hash::<Poseidon>().unwrap(),
+            legacy.hash::<Poseidon>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_edge_migration_preserves_fields(edge_args in arb_edge_args(), owner in arb_pubkey()) {
+        let legacy = legacy_edge(edge_args, owner);
+        let migrated = EdgeV2::from(legacy.clone());
+        prop_assert_eq!(migrated.key, AccountKey::EdgeV2);
+        prop_assert_eq!(&migrated.source_node, &legacy.source_node);
+        prop_assert_eq!(&migrated.target_node, &legacy.target_node);
+        prop_assert_eq!(migrated.edge_type.as_str(), "");
+        prop_assert_eq!(migrated.direction, EdgeDirection::Directed);
+        prop_assert_eq!(migrated.weight, 0);
+        prop_assert_eq!(migrated.validity, EdgeValidity::default());
+        prop_assert_eq!(
+            migrated.edge_data.get_properties().unwrap(),
+            legacy.edge_data.get_properties().unwrap()
+        );
+        prop_assert_eq!(migrated.owner, legacy.owner);
+        prop_assert_ne!(
+            migrated.hash::<Poseidon>().unwrap(),
+            legacy.hash::<Poseidon>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_property_proof_verifies_every_property(
+        properties in prop::collection::vec(arb_property(), 1..24),
+    ) {
+        let root = property_tree_root(&properties);
+        for (index, property) in properties.iter().enumerate() {
+            let proof = property_tree_proof(&properties, index);
+            prop_assert!(proof.len() >= PROPERTY_TREE_DEPTH);
+            prop_assert!(verify_property_proof(&root, property, index, &proof));
+        }
+    }
+
+    #[test]
+    fn test_property_proof_rejects_other_value(
+        properties in prop::collection::vec(arb_property(), 1..16),
+        index in any::<prop::sample::Index>(),
+    ) {
+        let index = index.index(properties.len());
+        let root = property_tree_root(&properties);
+        let proof = property_tree_proof(&properties, index);
+        let forged = Properties {
+            key: properties[index].key.clone(),
+            value: format!("{}-x", properties[index].value),
+        };
+        prop_assert!(!verify_property_proof(&root, &forged, index, &proof));
+    }
+
+    #[test]
+    fn test_node_hash_commits_to_property_root(
+        node_args in arb_node_args(),
+        owner in arb_pubkey(),
+        property in arb_property(),
+    ) {
+        let node = node(node_args.clone(), owner);
+        let mut properties = node_args.properties;
+        properties.push(property);
+        let extended = NodeV2 {
+            node_data: NodeDataV2::new(
+                &properties,
+                &node.node_data.get_creators().unwrap(),
+                &node.node_data.get_encrypted_properties().unwrap(),
+            ),
+            ..node.clone()
+        };
+        prop_assert_ne!(
+            node.hash::<Poseidon>().unwrap(),
+            extended.hash::<Poseidon>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_node_hash_commits_to_ciphertext(
+        node_args in arb_node_args(),
+        owner in arb_pubkey(),
+        encrypted_property in arb_encrypted_property(),
+    ) {
+        let node = node(node_args.clone(), owner);
+        let mut tampered_property = encrypted_property.clone();
+        tampered_property.ciphertext[0] ^= 1;
+        let with_encrypted_property = |encrypted_property: &EncryptedProperty| NodeV2 {
+            node_data: NodeDataV2::new(
+                &node_args.properties,
+                &node_args.creators,
+                &vec![encrypted_property.clone()],
+            ),
+            ..node.clone()
+        };
+        prop_assert_ne!(
+            with_encrypted_property(&encrypted_property).hash::<Poseidon>().unwrap(),
+            with_encrypted_property(&tampered_property).hash::<Poseidon>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_non_canonical_properties_rejected(
+        node_args in arb_node_args(),
+        edge_args in arb_edge_args(),
+        owner in arb_pubkey(),
+        trailing in any::<u8>(),
+    ) {
+        // Trailing bytes would decode to the same properties, and so the same
+        // root, as the canonical encoding
+        let mut node = node(node_args, owner);
+        node.node_data.properties_bytes.push(trailing);
+        prop_assert!(node.node_data.properties_root().is_err());
+        prop_assert!(node.node_data.validate().is_err());
+
+        let mut edge = edge(edge_args, owner);
+        edge.edge_data.properties_bytes.push(trailing);
+        prop_assert!(edge.edge_data.properties_root().is_err());
+        prop_assert!(edge.edge_data.validate().is_err());
+
+        // Bytes that do not decode at all
+        edge.edge_data.properties_bytes = vec![0xff; 3];
+        prop_assert!(edge.edge_data.validate().is_err());
+    }
+
+    #[test]
+    fn test_hashes_commit_to_token_gate(
+        node_args in arb_node_args(),
+        edge_args in arb_edge_args(),
+        owner in arb_pubkey(),
+        token_gate in arb_token_gate(),
+    ) {
+        let node = node(node_args, owner);
+        prop_assume!(token_gate != node.token_gate);
+        let regated_node = NodeV2 { token_gate, ..node.clone() };
+        prop_assert_ne!(
+            node.hash::<Poseidon>().unwrap(),
+            regated_node.hash::<Poseidon>().unwrap()
+        );
+
+        let edge = edge(edge_args.clone(), owner);
+        let gated_edge = EdgeV2 {
+            edge_data: EdgeDataV2::new(&edge_args.properties, token_gate, false),
+            ..edge.clone()
+        };
+        prop_assert_ne!(
+            edge.hash::<Poseidon>().unwrap(),
+            gated_edge.hash::<Poseidon>().unwrap()
+        );
+    }
+}
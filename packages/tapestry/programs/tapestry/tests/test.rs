@@ -0,0 +1,5138 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use account_compression::state_merkle_tree_from_bytes_zero_copy_mut;
+use anchor_lang::{AnchorSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::metadata::mpl_token_metadata;
+use anchor_spl::metadata::mpl_token_metadata::accounts::Metadata;
+use anchor_spl::metadata::mpl_token_metadata::types::{Collection, Key};
+use anchor_spl::token::spl_token;
+use common::{edge_args, node_args};
+use light_hasher::{DataHasher, Discriminator, Poseidon};
+use light_program_test::test_env::{setup_test_programs_with_accounts, EnvAccounts};
+use light_program_test::test_rpc::ProgramTestRpcConnection;
+use light_prover_client::gnark::helpers::{ProofType, ProverConfig};
+use light_sdk::address::derive_address;
+use light_sdk::compressed_account::{
+    CompressedAccount, CompressedAccountData, CompressedAccountWithMerkleContext,
+};
+use light_sdk::merkle_context::{AddressMerkleContext, MerkleContext};
+use light_system_program::sdk::event::PublicTransactionEvent;
+use light_system_program::utils::{
+    get_cpi_authority_pda, get_registered_program_pda, get_sol_pool_pda,
+};
+use light_test_utils::indexer::{Indexer, TestIndexer};
+use light_test_utils::{RpcConnection, RpcError};
+use solana_sdk::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::ed25519_instruction::new_ed25519_instruction;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{keypair_from_seed, Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+use tapestry::constants::{
+    ACTION_NONCE_SEED, CONFIG_SEED, CPI_AUTHORITY_SEED, MAX_CIPHERTEXT_LENGTH,
+    MAX_EDGE_TYPE_LENGTH, MAX_NAMESPACE_LENGTH, NODE_ACCOUNT_SEED,
+};
+use tapestry::errors::ZkNftError;
+use tapestry::state::{
+    AccountKey, ActionNonce, AddressSeed, AnchorCompressedAccountMeta, AnchorCompressedProof,
+    DecompressedNodeV1, EdgeArgs, EdgeCounters, EdgeDirection, EdgeV1, EdgeV2, EdgeValidity,
+    EncryptedProperty, ExternalIdentity, GroupAuthority, IdentityLinkV1, IdentityProvider,
+    NodeAccount, NodeArgs, NodeCounterV1, NodeUpdateAuthority, NodeV1, NodeV2, PendingEdgeV1,
+    ProvenNode, SignedAction, TapestryConfig, TokenGate, VersionedEdge, VersionedNode,
+};
+use tapestry::utils::{
+    get_account_seed, get_edge_pair_seed, get_external_id_seed, get_identity_link_seed,
+    get_label_seed, get_node_counter_seed, get_undirected_edge_seed,
+};
+
+const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("compr6CUsB5m2jS4Y3831ztGSTnDpnKJTKS95d64XVq");
+const NOOP_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+struct TestContext {
+    rpc: ProgramTestRpcConnection,
+    indexer: TestIndexer<ProgramTestRpcConnection>,
+    env: EnvAccounts,
+    payer: Keypair,
+}
+
+impl TestContext {
+    async fn new() -> Self {
+        let (rpc, env) = setup_test_programs_with_accounts(Some(vec![
+            (String::from("tapestry"), tapestry::ID),
+            (String::from("cpi_caller"), cpi_caller::ID),
+        ]))
+        .await;
+        let payer = rpc.get_payer().insecure_clone();
+        // Runs a local prover so proofs are generated without any remote service
+        let indexer = TestIndexer::init_from_env(
+            &payer,
+            &env,
+            Some(ProverConfig {
+                run_mode: None,
+                circuits: vec![
+                    ProofType::Inclusion,
+                    ProofType::NonInclusion,
+                    ProofType::Combined,
+                ],
+            }),
+        )
+        .await;
+
+        Self {
+            rpc,
+            indexer,
+            env,
+            payer,
+        }
+    }
+
+    fn new_address(&self, address_seed: &[u8; 32]) -> [u8; 32] {
+        derive_address(
+            address_seed,
+            &AddressMerkleContext {
+                address_merkle_tree_pubkey: self.env.address_merkle_tree_pubkey,
+                address_queue_pubkey: self.env.address_merkle_tree_queue_pubkey,
+            },
+        )
+    }
+
+    fn new_address_remaining_accounts(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.env.merkle_tree_pubkey, false),
+            AccountMeta::new(self.env.address_merkle_tree_pubkey, false),
+            AccountMeta::new(self.env.address_merkle_tree_queue_pubkey, false),
+        ]
+    }
+
+    fn input_remaining_accounts(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.env.merkle_tree_pubkey, false),
+            AccountMeta::new(self.env.nullifier_queue_pubkey, false),
+        ]
+    }
+
+    async fn new_address_proof(&mut self, address: [u8; 32]) -> (AnchorCompressedProof, u16) {
+        let rpc_result = self
+            .indexer
+            .create_proof_for_compressed_accounts(
+                None,
+                None,
+                Some(&[address]),
+                Some(vec![self.env.address_merkle_tree_pubkey]),
+                &mut self.rpc,
+            )
+            .await;
+        (
+            AnchorCompressedProof {
+                a: rpc_result.proof.a,
+                b: rpc_result.proof.b,
+                c: rpc_result.proof.c,
+            },
+            rpc_result.address_root_indices[0],
+        )
+    }
+
+    async fn inclusion_proof(
+        &mut self,
+        account: &CompressedAccountWithMerkleContext,
+    ) -> (AnchorCompressedProof, AnchorCompressedAccountMeta) {
+        let rpc_result = self
+            .indexer
+            .create_proof_for_compressed_accounts(
+                Some(&[account.hash().unwrap()]),
+                Some(&[account.merkle_context.merkle_tree_pubkey]),
+                None,
+                None,
+                &mut self.rpc,
+            )
+            .await;
+        (
+            AnchorCompressedProof {
+                a: rpc_result.proof.a,
+                b: rpc_result.proof.b,
+                c: rpc_result.proof.c,
+            },
+            AnchorCompressedAccountMeta {
+                address: account.compressed_account.address.unwrap(),
+                merkle_tree_pubkey_index: 0,
+                nullifier_queue_pubkey_index: 1,
+                leaf_index: account.merkle_context.leaf_index,
+                root_index: rpc_result.root_indices[0],
+                lamports: account.compressed_account.lamports,
+            },
+        )
+    }
+
+    // Proof for a new address together with the nodes at `node_addresses`,
+    // proven as inputs behind the new address remaining accounts and the
+    // nullifier queue
+    async fn new_address_proof_with_nodes(
+        &mut self,
+        address: [u8; 32],
+        node_addresses: &[[u8; 32]],
+    ) -> (AnchorCompressedProof, u16, Vec<ProvenNode>) {
+        let accounts: Vec<_> = node_addresses
+            .iter()
+            .map(|node_address| self.compressed_account(node_address))
+            .collect();
+        let hashes: Vec<[u8; 32]> = accounts
+            .iter()
+            .map(|account| account.hash().unwrap())
+            .collect();
+        let merkle_trees: Vec<Pubkey> = accounts
+            .iter()
+            .map(|account| account.merkle_context.merkle_tree_pubkey)
+            .collect();
+        let rpc_result = self
+            .indexer
+            .create_proof_for_compressed_accounts(
+                Some(&hashes),
+                Some(&merkle_trees),
+                Some(&[address]),
+                Some(vec![self.env.address_merkle_tree_pubkey]),
+                &mut self.rpc,
+            )
+            .await;
+        let nodes = accounts
+            .iter()
+            .zip(rpc_result.root_indices.iter())
+            .map(|(account, root_index)| ProvenNode {
+                node: VersionedNode::V2(decode_node(account)),
+                node_meta: AnchorCompressedAccountMeta {
+                    address: account.compressed_account.address.unwrap(),
+                    merkle_tree_pubkey_index: 0,
+                    nullifier_queue_pubkey_index: 3,
+                    leaf_index: account.merkle_context.leaf_index,
+                    root_index: *root_index,
+                    lamports: account.compressed_account.lamports,
+                },
+            })
+            .collect();
+        (
+            AnchorCompressedProof {
+                a: rpc_result.proof.a,
+                b: rpc_result.proof.b,
+                c: rpc_result.proof.c,
+            },
+            rpc_result.address_root_indices[0],
+            nodes,
+        )
+    }
+
+    fn new_address_with_input_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let mut accounts = self.new_address_remaining_accounts();
+        accounts.push(AccountMeta::new(self.env.nullifier_queue_pubkey, false));
+        accounts
+    }
+
+    // Writes an account directly into the bank, for fixtures owned by other
+    // programs
+    fn set_account(&mut self, address: &Pubkey, owner: &Pubkey, data: Vec<u8>) {
+        let account = solana_sdk::account::Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: *owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.rpc.context.set_account(address, &account.into());
+    }
+
+    fn set_token_account(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let address = Pubkey::new_unique();
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        let token_account = spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        spl_token::state::Account::pack(token_account, &mut data).unwrap();
+        self.set_account(&address, &spl_token::ID, data);
+        address
+    }
+
+    fn set_collection_metadata(&mut self, mint: &Pubkey, collection: &Pubkey) -> Pubkey {
+        let metadata = Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint: *mint,
+            name: String::from("member"),
+            symbol: String::new(),
+            uri: String::new(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: None,
+            collection: Some(Collection {
+                verified: true,
+                key: *collection,
+            }),
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        };
+        let address = Metadata::find_pda(mint).0;
+        self.set_account(
+            &address,
+            &mpl_token_metadata::ID,
+            metadata.try_to_vec().unwrap(),
+        );
+        address
+    }
+
+    fn compressed_account(&self, address: &[u8; 32]) -> CompressedAccountWithMerkleContext {
+        self.indexer
+            .get_compressed_accounts_by_owner(&tapestry::ID)
+            .into_iter()
+            .find(|account| account.compressed_account.address == Some(*address))
+            .unwrap()
+    }
+
+    async fn send(&mut self, ix: Instruction, signers: &[&Keypair]) -> Result<(), RpcError> {
+        self.send_all(vec![ix], signers).await
+    }
+
+    async fn send_all(
+        &mut self,
+        ixs: Vec<Instruction>,
+        signers: &[&Keypair],
+    ) -> Result<(), RpcError> {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)];
+        instructions.extend(ixs);
+        let event = self
+            .rpc
+            .create_and_send_transaction_with_event::<PublicTransactionEvent>(
+                &instructions,
+                &self.payer.pubkey(),
+                &all_signers,
+                None,
+            )
+            .await?;
+        if let Some((event, _, _)) = event {
+            self.indexer.add_compressed_accounts_with_token_data(&event);
+        }
+        Ok(())
+    }
+
+    async fn create_node(
+        &mut self,
+        owner: &Pubkey,
+        update_authority: Option<&Keypair>,
+        node_args: NodeArgs,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> Result<[u8; 32], RpcError> {
+        let address_seed = AddressSeed::Random(Pubkey::new_unique().to_bytes());
+        self.create_node_with_seed(
+            owner,
+            update_authority,
+            address_seed,
+            node_args,
+            remaining_accounts,
+        )
+        .await
+    }
+
+    async fn create_node_with_seed(
+        &mut self,
+        owner: &Pubkey,
+        update_authority: Option<&Keypair>,
+        address_seed: AddressSeed,
+        node_args: NodeArgs,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> Result<[u8; 32], RpcError> {
+        let address = self.new_address(&address_seed.node_seed(&node_args).unwrap());
+        let address_proof = self.new_address_proof(address).await;
+        self.create_node_with_proof(
+            owner,
+            update_authority,
+            address_seed,
+            node_args,
+            remaining_accounts,
+            address_proof,
+        )
+        .await?;
+        Ok(address)
+    }
+
+    // Creates a node with a given non-inclusion proof, which can be reused to
+    // try to create an address twice
+    async fn create_node_with_proof(
+        &mut self,
+        owner: &Pubkey,
+        update_authority: Option<&Keypair>,
+        address_seed: AddressSeed,
+        node_args: NodeArgs,
+        remaining_accounts: Vec<AccountMeta>,
+        (proof, address_merkle_tree_root_index): (AnchorCompressedProof, u16),
+    ) -> Result<(), RpcError> {
+        let mut accounts = tapestry::accounts::CreateNode {
+            payer: self.payer.pubkey(),
+            update_authority: update_authority.map(|authority| authority.pubkey()),
+            owner: *owner,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(remaining_accounts);
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateNode {
+                proof,
+                address_merkle_tree_root_index,
+                address_seed,
+                node_args,
+            }
+            .data(),
+        };
+        let signers: Vec<&Keypair> = update_authority.into_iter().collect();
+        self.send(ix, &signers).await
+    }
+
+    async fn create_edge(
+        &mut self,
+        owner: &Pubkey,
+        update_authority: Option<&Keypair>,
+        edge_args: EdgeArgs,
+    ) -> Result<[u8; 32], RpcError> {
+        let address_seed = AddressSeed::Random(Pubkey::new_unique().to_bytes());
+        self.create_edge_with_seed(owner, update_authority, address_seed, edge_args)
+            .await
+    }
+
+    async fn create_edge_with_seed(
+        &mut self,
+        owner: &Pubkey,
+        update_authority: Option<&Keypair>,
+        address_seed: AddressSeed,
+        edge_args: EdgeArgs,
+    ) -> Result<[u8; 32], RpcError> {
+        let address = self.new_address(&address_seed.edge_seed(&edge_args).unwrap());
+        let address_proof = self.new_address_proof(address).await;
+        self.create_edge_with_proof(
+            owner,
+            update_authority,
+            address_seed,
+            edge_args,
+            address_proof,
+        )
+        .await?;
+        Ok(address)
+    }
+
+    // See `create_node_with_proof`
+    async fn create_edge_with_proof(
+        &mut self,
+        owner: &Pubkey,
+        update_authority: Option<&Keypair>,
+        address_seed: AddressSeed,
+        edge_args: EdgeArgs,
+        (proof, address_merkle_tree_root_index): (AnchorCompressedProof, u16),
+    ) -> Result<(), RpcError> {
+        let mut accounts = tapestry::accounts::CreateEdge {
+            payer: self.payer.pubkey(),
+            update_authority: update_authority.map(|authority| authority.pubkey()),
+            owner: *owner,
+            gate_token_account: None,
+            gate_metadata: None,
+            source_node_account: None,
+            target_node_account: None,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.new_address_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateEdge {
+                proof,
+                address_merkle_tree_root_index,
+                address_seed,
+                edge_args,
+                source: None,
+                target: None,
+                counters: None,
+            }
+            .data(),
+        };
+        let signers: Vec<&Keypair> = update_authority.into_iter().collect();
+        self.send(ix, &signers).await
+    }
+
+    // Creates an edge to a decompressed node, read from its node account
+    async fn create_edge_to_node_account(
+        &mut self,
+        owner: &Pubkey,
+        node_account: &Pubkey,
+        edge_args: EdgeArgs,
+    ) -> Result<[u8; 32], RpcError> {
+        let random_bytes = Pubkey::new_unique().to_bytes();
+        let address = self.new_address(&get_account_seed(AccountKey::EdgeV1, &random_bytes));
+        let (proof, address_merkle_tree_root_index) = self.new_address_proof(address).await;
+
+        let mut accounts = tapestry::accounts::CreateEdge {
+            payer: self.payer.pubkey(),
+            update_authority: None,
+            owner: *owner,
+            gate_token_account: None,
+            gate_metadata: None,
+            source_node_account: None,
+            target_node_account: Some(*node_account),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.new_address_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateEdge {
+                proof,
+                address_merkle_tree_root_index,
+                address_seed: AddressSeed::Random(random_bytes),
+                edge_args,
+                source: None,
+                target: None,
+                counters: None,
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await?;
+        Ok(address)
+    }
+
+    // Calls `create_edge_cpi` through the test caller program, which signs as
+    // its authority PDA, or directly when `program_authority` is given
+    async fn create_edge_cpi(
+        &mut self,
+        program_authority: Option<&Keypair>,
+        calling_program: &Pubkey,
+        authority_seeds: Vec<Vec<u8>>,
+        edge_args: EdgeArgs,
+    ) -> Result<[u8; 32], RpcError> {
+        let random_bytes = Pubkey::new_unique().to_bytes();
+        let address = self.new_address(&get_account_seed(AccountKey::EdgeV1, &random_bytes));
+        let (proof, address_merkle_tree_root_index) = self.new_address_proof(address).await;
+
+        let mut accounts = tapestry::accounts::CreateEdgeCpi {
+            payer: self.payer.pubkey(),
+            program_authority: program_authority
+                .map(|authority| authority.pubkey())
+                .unwrap_or_else(|| cpi_caller::authority().0),
+            calling_program: *calling_program,
+            gate_token_account: None,
+            gate_metadata: None,
+            target_node_account: None,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.new_address_remaining_accounts());
+        let data = tapestry::instruction::CreateEdgeCpi {
+            proof,
+            address_merkle_tree_root_index,
+            address_seed: AddressSeed::Random(random_bytes),
+            edge_args,
+            authority_seeds,
+            target: None,
+        }
+        .data();
+
+        match program_authority {
+            Some(program_authority) => {
+                let ix = Instruction {
+                    program_id: tapestry::ID,
+                    accounts,
+                    data,
+                };
+                self.send(ix, &[program_authority]).await?;
+            }
+            None => {
+                // The caller signs for its PDA, which cannot sign the transaction
+                let authority = cpi_caller::authority().0;
+                for account in accounts.iter_mut().filter(|a| a.pubkey == authority) {
+                    account.is_signer = false;
+                }
+                let mut caller_accounts = vec![AccountMeta::new_readonly(tapestry::ID, false)];
+                caller_accounts.extend(accounts);
+                let ix = Instruction {
+                    program_id: cpi_caller::ID,
+                    accounts: caller_accounts,
+                    data,
+                };
+                self.send(ix, &[]).await?;
+            }
+        }
+        Ok(address)
+    }
+
+    // Creates an edge proving the node at `target_address` and its token gate
+    async fn create_gated_edge(
+        &mut self,
+        owner: &Keypair,
+        target_address: &[u8; 32],
+        gate_token_account: Option<Pubkey>,
+        gate_metadata: Option<Pubkey>,
+        edge_args: EdgeArgs,
+    ) -> Result<[u8; 32], RpcError> {
+        let random_bytes = Pubkey::new_unique().to_bytes();
+        let address = self.new_address(&get_account_seed(AccountKey::EdgeV1, &random_bytes));
+        let (proof, address_merkle_tree_root_index, mut nodes) = self
+            .new_address_proof_with_nodes(address, &[*target_address])
+            .await;
+
+        let mut accounts = tapestry::accounts::CreateEdge {
+            payer: self.payer.pubkey(),
+            update_authority: None,
+            owner: owner.pubkey(),
+            gate_token_account,
+            gate_metadata,
+            source_node_account: None,
+            target_node_account: None,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        for account in accounts.iter_mut().filter(|a| a.pubkey == owner.pubkey()) {
+            account.is_signer = true;
+        }
+        accounts.extend(self.new_address_with_input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateEdge {
+                proof,
+                address_merkle_tree_root_index,
+                address_seed: AddressSeed::Random(random_bytes),
+                edge_args,
+                source: None,
+                target: nodes.pop(),
+                counters: None,
+            }
+            .data(),
+        };
+        self.send(ix, &[owner]).await?;
+        Ok(address)
+    }
+
+    async fn create_undirected_edge(
+        &mut self,
+        owner: &Keypair,
+        counterparty: &Keypair,
+        source_address: &[u8; 32],
+        target_address: &[u8; 32],
+        edge_args: EdgeArgs,
+    ) -> Result<[u8; 32], RpcError> {
+        self.create_undirected_edge_with_gates(
+            owner,
+            counterparty,
+            [source_address, target_address],
+            edge_args,
+            (None, None),
+        )
+        .await
+    }
+
+    // Creates an undirected edge between the proven `nodes`, with the holdings
+    // of the owner and the counterparty for the target's and the source's gate
+    async fn create_undirected_edge_with_gates(
+        &mut self,
+        owner: &Keypair,
+        counterparty: &Keypair,
+        [source_address, target_address]: [&[u8; 32]; 2],
+        edge_args: EdgeArgs,
+        (gate_token_account, counterparty_gate_token_account): (Option<Pubkey>, Option<Pubkey>),
+    ) -> Result<[u8; 32], RpcError> {
+        let address = self.new_address(&get_undirected_edge_seed(
+            &edge_args.namespace,
+            &edge_args.source_node,
+            &edge_args.target_node,
+            &edge_args.edge_type,
+        ));
+        let (proof, address_merkle_tree_root_index, mut nodes) = self
+            .new_address_proof_with_nodes(address, &[*source_address, *target_address])
+            .await;
+        let target = nodes.pop().unwrap();
+        let source = nodes.pop().unwrap();
+
+        let mut accounts = tapestry::accounts::CreateUndirectedEdge {
+            payer: self.payer.pubkey(),
+            update_authority: None,
+            owner: owner.pubkey(),
+            counterparty: counterparty.pubkey(),
+            gate_token_account,
+            gate_metadata: None,
+            counterparty_gate_token_account,
+            counterparty_gate_metadata: None,
+            source_node_account: None,
+            target_node_account: None,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.new_address_with_input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateUndirectedEdge {
+                proof,
+                address_merkle_tree_root_index,
+                edge_args,
+                source: Some(source),
+                target: Some(target),
+            }
+            .data(),
+        };
+        self.send(ix, &[owner, counterparty]).await?;
+        Ok(address)
+    }
+
+    // Creates an undirected edge between decompressed nodes, which are read
+    // rather than proven, so `address_proof` only covers the edge address and
+    // can be reused to try to create it twice
+    async fn create_undirected_edge_between_node_accounts(
+        &mut self,
+        owner: &Keypair,
+        counterparty: &Keypair,
+        [source_node_account, target_node_account]: [Pubkey; 2],
+        edge_args: EdgeArgs,
+        (proof, address_merkle_tree_root_index): (AnchorCompressedProof, u16),
+    ) -> Result<(), RpcError> {
+        let mut accounts = tapestry::accounts::CreateUndirectedEdge {
+            payer: self.payer.pubkey(),
+            update_authority: None,
+            owner: owner.pubkey(),
+            counterparty: counterparty.pubkey(),
+            gate_token_account: None,
+            gate_metadata: None,
+            counterparty_gate_token_account: None,
+            counterparty_gate_metadata: None,
+            source_node_account: Some(source_node_account),
+            target_node_account: Some(target_node_account),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.new_address_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateUndirectedEdge {
+                proof,
+                address_merkle_tree_root_index,
+                edge_args,
+                source: None,
+                target: None,
+            }
+            .data(),
+        };
+        self.send(ix, &[owner, counterparty]).await
+    }
+
+    async fn request_edge(
+        &mut self,
+        owner: &Keypair,
+        source_address: &[u8; 32],
+        target_address: &[u8; 32],
+        edge_args: EdgeArgs,
+    ) -> Result<[u8; 32], RpcError> {
+        let (ix, address) = self
+            .request_edge_instruction(
+                &owner.pubkey(),
+                source_address,
+                target_address,
+                None,
+                edge_args,
+            )
+            .await;
+        self.send(ix, &[owner]).await?;
+        Ok(address)
+    }
+
+    async fn request_edge_instruction(
+        &mut self,
+        owner: &Pubkey,
+        source_address: &[u8; 32],
+        target_address: &[u8; 32],
+        gate_token_account: Option<Pubkey>,
+        edge_args: EdgeArgs,
+    ) -> (Instruction, [u8; 32]) {
+        let random_bytes = Pubkey::new_unique().to_bytes();
+        let address = self.new_address(&get_account_seed(AccountKey::EdgeV1, &random_bytes));
+        let (proof, address_merkle_tree_root_index, mut nodes) = self
+            .new_address_proof_with_nodes(address, &[*source_address, *target_address])
+            .await;
+        let target = nodes.pop().unwrap();
+        let source = nodes.pop().unwrap();
+
+        let mut accounts = tapestry::accounts::RequestEdge {
+            payer: self.payer.pubkey(),
+            update_authority: None,
+            owner: *owner,
+            gate_token_account,
+            gate_metadata: None,
+            source_node_account: None,
+            target_node_account: None,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.new_address_with_input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::RequestEdge {
+                proof,
+                address_merkle_tree_root_index,
+                address_seed: AddressSeed::Random(random_bytes),
+                edge_args,
+                source: Some(source),
+                target: Some(target),
+            }
+            .data(),
+        };
+        (ix, address)
+    }
+
+    async fn accept_edge(
+        &mut self,
+        address: &[u8; 32],
+        target_owner: &Keypair,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let pending_edge = decode_pending_edge(&compressed_account);
+        let (proof, pending_edge_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = tapestry::accounts::AcceptEdge {
+            payer: self.payer.pubkey(),
+            target_owner: target_owner.pubkey(),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::AcceptEdge {
+                proof,
+                pending_edge,
+                pending_edge_meta,
+            }
+            .data(),
+        };
+        self.send(ix, &[target_owner]).await
+    }
+
+    async fn reject_edge(
+        &mut self,
+        address: &[u8; 32],
+        target_owner: &Keypair,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let pending_edge = decode_pending_edge(&compressed_account);
+        let (proof, pending_edge_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = tapestry::accounts::RejectEdge {
+            payer: self.payer.pubkey(),
+            target_owner: target_owner.pubkey(),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::RejectEdge {
+                proof,
+                pending_edge,
+                pending_edge_meta,
+            }
+            .data(),
+        };
+        self.send(ix, &[target_owner]).await
+    }
+
+    async fn cancel_edge_request(
+        &mut self,
+        address: &[u8; 32],
+        owner: &Keypair,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let pending_edge = decode_pending_edge(&compressed_account);
+        let (proof, pending_edge_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = tapestry::accounts::CancelEdgeRequest {
+            payer: self.payer.pubkey(),
+            owner: owner.pubkey(),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CancelEdgeRequest {
+                proof,
+                pending_edge,
+                pending_edge_meta,
+            }
+            .data(),
+        };
+        self.send(ix, &[owner]).await
+    }
+
+    fn has_compressed_account(&self, address: &[u8; 32]) -> bool {
+        self.indexer
+            .get_compressed_accounts_by_owner(&tapestry::ID)
+            .iter()
+            .any(|account| account.compressed_account.address == Some(*address))
+    }
+
+    async fn create_node_counter(&mut self, node: &str) -> Result<[u8; 32], RpcError> {
+        let address = self.new_address(&get_node_counter_seed(node, "follow"));
+        let (proof, address_merkle_tree_root_index) = self.new_address_proof(address).await;
+
+        let mut accounts = tapestry::accounts::CreateNodeCounter {
+            payer: self.payer.pubkey(),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.new_address_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateNodeCounter {
+                proof,
+                address_merkle_tree_root_index,
+                node: node.to_string(),
+                edge_type: "follow".to_string(),
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await?;
+        Ok(address)
+    }
+
+    // Creates an edge from the node at `source_address`, owned by `owner`,
+    // counted on the counters at `source_counter` and `target_counter`
+    async fn create_counted_edge(
+        &mut self,
+        owner: &Keypair,
+        source_address: &[u8; 32],
+        source_counter: &[u8; 32],
+        target_counter: &[u8; 32],
+        edge_args: EdgeArgs,
+    ) -> Result<[u8; 32], RpcError> {
+        let random_bytes = Pubkey::new_unique().to_bytes();
+        let address = self.new_address(&get_account_seed(AccountKey::EdgeV1, &random_bytes));
+        let source_account = self.compressed_account(source_address);
+        let source_counter_account = self.compressed_account(source_counter);
+        let target_counter_account = self.compressed_account(target_counter);
+
+        let rpc_result = self
+            .indexer
+            .create_proof_for_compressed_accounts(
+                Some(&[
+                    source_account.hash().unwrap(),
+                    source_counter_account.hash().unwrap(),
+                    target_counter_account.hash().unwrap(),
+                ]),
+                Some(&[
+                    source_account.merkle_context.merkle_tree_pubkey,
+                    source_counter_account.merkle_context.merkle_tree_pubkey,
+                    target_counter_account.merkle_context.merkle_tree_pubkey,
+                ]),
+                Some(&[address]),
+                Some(vec![self.env.address_merkle_tree_pubkey]),
+                &mut self.rpc,
+            )
+            .await;
+        let proof = AnchorCompressedProof {
+            a: rpc_result.proof.a,
+            b: rpc_result.proof.b,
+            c: rpc_result.proof.c,
+        };
+        let input_meta = |account: &CompressedAccountWithMerkleContext, root_index: u16| {
+            AnchorCompressedAccountMeta {
+                address: account.compressed_account.address.unwrap(),
+                merkle_tree_pubkey_index: 0,
+                nullifier_queue_pubkey_index: 3,
+                leaf_index: account.merkle_context.leaf_index,
+                root_index,
+                lamports: account.compressed_account.lamports,
+            }
+        };
+        let source = ProvenNode {
+            node: VersionedNode::V2(decode_node(&source_account)),
+            node_meta: input_meta(&source_account, rpc_result.root_indices[0]),
+        };
+        let counters = EdgeCounters {
+            source: decode_counter(&source_counter_account),
+            source_meta: input_meta(&source_counter_account, rpc_result.root_indices[1]),
+            target: decode_counter(&target_counter_account),
+            target_meta: input_meta(&target_counter_account, rpc_result.root_indices[2]),
+        };
+
+        let mut accounts = tapestry::accounts::CreateEdge {
+            payer: self.payer.pubkey(),
+            update_authority: None,
+            owner: owner.pubkey(),
+            gate_token_account: None,
+            gate_metadata: None,
+            source_node_account: None,
+            target_node_account: None,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.new_address_remaining_accounts());
+        accounts.push(AccountMeta::new(self.env.nullifier_queue_pubkey, false));
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateEdge {
+                proof,
+                address_merkle_tree_root_index: rpc_result.address_root_indices[0],
+                address_seed: AddressSeed::Random(random_bytes),
+                edge_args,
+                source: Some(source),
+                target: None,
+                counters: Some(counters),
+            }
+            .data(),
+        };
+        self.send(ix, &[owner]).await?;
+        Ok(address)
+    }
+
+    // Prunes the expired edge at `address`, uncounting it from the counters at
+    // `counter_addresses` if given
+    async fn prune_expired_edge(
+        &mut self,
+        address: &[u8; 32],
+        counter_addresses: Option<([u8; 32], [u8; 32])>,
+    ) -> Result<(), RpcError> {
+        let mut accounts = vec![self.compressed_account(address)];
+        if let Some((source_counter, target_counter)) = counter_addresses {
+            accounts.push(self.compressed_account(&source_counter));
+            accounts.push(self.compressed_account(&target_counter));
+        }
+        let hashes: Vec<[u8; 32]> = accounts
+            .iter()
+            .map(|account| account.hash().unwrap())
+            .collect();
+        let merkle_trees: Vec<Pubkey> = accounts
+            .iter()
+            .map(|account| account.merkle_context.merkle_tree_pubkey)
+            .collect();
+        let rpc_result = self
+            .indexer
+            .create_proof_for_compressed_accounts(
+                Some(&hashes),
+                Some(&merkle_trees),
+                None,
+                None,
+                &mut self.rpc,
+            )
+            .await;
+        let metas: Vec<AnchorCompressedAccountMeta> = accounts
+            .iter()
+            .zip(rpc_result.root_indices.iter())
+            .map(|(account, root_index)| AnchorCompressedAccountMeta {
+                address: account.compressed_account.address.unwrap(),
+                merkle_tree_pubkey_index: 0,
+                nullifier_queue_pubkey_index: 1,
+                leaf_index: account.merkle_context.leaf_index,
+                root_index: *root_index,
+                lamports: account.compressed_account.lamports,
+            })
+            .collect();
+        let counters = counter_addresses.map(|_| EdgeCounters {
+            source: decode_counter(&accounts[1]),
+            source_meta: metas[1],
+            target: decode_counter(&accounts[2]),
+            target_meta: metas[2],
+        });
+
+        let mut ix_accounts = tapestry::accounts::PruneExpiredEdge {
+            payer: self.payer.pubkey(),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        ix_accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts: ix_accounts,
+            data: tapestry::instruction::PruneExpiredEdge {
+                proof: AnchorCompressedProof {
+                    a: rpc_result.proof.a,
+                    b: rpc_result.proof.b,
+                    c: rpc_result.proof.c,
+                },
+                edge: VersionedEdge::V2(decode_edge(&accounts[0])),
+                edge_meta: metas[0],
+                counters,
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    async fn adjust_edge_weight(
+        &mut self,
+        address: &[u8; 32],
+        authority: &Keypair,
+        delta: i64,
+    ) -> Result<(), RpcError> {
+        self.adjust_edge_weight_with_signers(address, authority, &[], delta)
+            .await
+    }
+
+    // `co_signers` are the other signers of a threshold update authority
+    async fn adjust_edge_weight_with_signers(
+        &mut self,
+        address: &[u8; 32],
+        authority: &Keypair,
+        co_signers: &[&Keypair],
+        delta: i64,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let edge = decode_edge(&compressed_account);
+        let (proof, edge_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = tapestry::accounts::AdjustEdgeWeight {
+            payer: self.payer.pubkey(),
+            authority: authority.pubkey(),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+        accounts.extend(
+            co_signers
+                .iter()
+                .map(|signer| AccountMeta::new_readonly(signer.pubkey(), true)),
+        );
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::AdjustEdgeWeight {
+                proof,
+                edge: VersionedEdge::V2(edge),
+                edge_meta,
+                delta,
+                group: None,
+            }
+            .data(),
+        };
+        let mut signers = vec![authority];
+        signers.extend_from_slice(co_signers);
+        self.send(ix, &signers).await
+    }
+
+    // Adjusts the weight of an edge whose update authority is the group node at
+    // `group_address`, proving the group node alongside the edge
+    async fn adjust_edge_weight_as_group(
+        &mut self,
+        address: &[u8; 32],
+        group_address: &[u8; 32],
+        authority: &Keypair,
+        delta: i64,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let edge = decode_edge(&compressed_account);
+        let group_account = self.compressed_account(group_address);
+        let group_node: NodeV2 = anchor_lang::AnchorDeserialize::deserialize(
+            &mut group_account
+                .compressed_account
+                .data
+                .as_ref()
+                .unwrap()
+                .data
+                .as_slice(),
+        )
+        .unwrap();
+
+        let rpc_result = self
+            .indexer
+            .create_proof_for_compressed_accounts(
+                Some(&[
+                    compressed_account.hash().unwrap(),
+                    group_account.hash().unwrap(),
+                ]),
+                Some(&[
+                    compressed_account.merkle_context.merkle_tree_pubkey,
+                    group_account.merkle_context.merkle_tree_pubkey,
+                ]),
+                None,
+                None,
+                &mut self.rpc,
+            )
+            .await;
+        let proof = AnchorCompressedProof {
+            a: rpc_result.proof.a,
+            b: rpc_result.proof.b,
+            c: rpc_result.proof.c,
+        };
+        let edge_meta = AnchorCompressedAccountMeta {
+            address: *address,
+            merkle_tree_pubkey_index: 0,
+            nullifier_queue_pubkey_index: 1,
+            leaf_index: compressed_account.merkle_context.leaf_index,
+            root_index: rpc_result.root_indices[0],
+            lamports: compressed_account.compressed_account.lamports,
+        };
+        let group = GroupAuthority {
+            group: VersionedNode::V2(group_node),
+            group_meta: AnchorCompressedAccountMeta {
+                address: *group_address,
+                merkle_tree_pubkey_index: 0,
+                nullifier_queue_pubkey_index: 1,
+                leaf_index: group_account.merkle_context.leaf_index,
+                root_index: rpc_result.root_indices[1],
+                lamports: group_account.compressed_account.lamports,
+            },
+        };
+
+        let mut accounts = tapestry::accounts::AdjustEdgeWeight {
+            payer: self.payer.pubkey(),
+            authority: authority.pubkey(),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::AdjustEdgeWeight {
+                proof,
+                edge: VersionedEdge::V2(edge),
+                edge_meta,
+                delta,
+                group: Some(group),
+            }
+            .data(),
+        };
+        self.send(ix, &[authority]).await
+    }
+
+    // Moves the program under the upgradeable loader with `upgrade_authority`,
+    // which `initialize_config` reads from the program data account
+    async fn make_upgradeable(&mut self, upgrade_authority: &Pubkey) -> Pubkey {
+        let program = self.rpc.get_account(tapestry::ID).await.unwrap().unwrap();
+        let program_data = bpf_loader_upgradeable::get_program_data_address(&tapestry::ID);
+        let mut data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(*upgrade_authority),
+        })
+        .unwrap();
+        data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+        data.extend(program.data);
+        self.set_account(&program_data, &bpf_loader_upgradeable::ID, data);
+
+        let program_account = solana_sdk::account::Account {
+            lamports: program.lamports,
+            data: bincode::serialize(&UpgradeableLoaderState::Program {
+                programdata_address: program_data,
+            })
+            .unwrap(),
+            owner: bpf_loader_upgradeable::ID,
+            executable: true,
+            rent_epoch: 0,
+        };
+        self.rpc
+            .context
+            .set_account(&tapestry::ID, &program_account.into());
+        program_data
+    }
+
+    async fn initialize_config(
+        &mut self,
+        admin: &Keypair,
+        program_data: &Pubkey,
+        attestation_authority: &Pubkey,
+    ) -> Result<(), RpcError> {
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts: tapestry::accounts::InitializeConfig {
+                payer: self.payer.pubkey(),
+                admin: admin.pubkey(),
+                config: config_pda(),
+                program: tapestry::ID,
+                program_data: *program_data,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: tapestry::instruction::InitializeConfig {
+                attestation_authority: *attestation_authority,
+            }
+            .data(),
+        };
+        self.send(ix, &[admin]).await
+    }
+
+    async fn set_attestation_authority(
+        &mut self,
+        admin: &Keypair,
+        attestation_authority: &Pubkey,
+    ) -> Result<(), RpcError> {
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts: tapestry::accounts::SetAttestationAuthority {
+                admin: admin.pubkey(),
+                config: config_pda(),
+            }
+            .to_account_metas(None),
+            data: tapestry::instruction::SetAttestationAuthority {
+                attestation_authority: *attestation_authority,
+            }
+            .data(),
+        };
+        self.send(ix, &[admin]).await
+    }
+
+    // Initializes the config through an upgradeable deployment, returning the
+    // attestation authority
+    async fn setup_config(&mut self) -> Keypair {
+        let admin = Keypair::new();
+        let attestation_authority = Keypair::new();
+        let program_data = self.make_upgradeable(&admin.pubkey()).await;
+        self.initialize_config(&admin, &program_data, &attestation_authority.pubkey())
+            .await
+            .unwrap();
+        attestation_authority
+    }
+
+    // Sends `create_identity_link` for the node at `node_address` after
+    // `pre_instructions`, which carry the attestation in the valid case
+    async fn create_identity_link(
+        &mut self,
+        node_address: &[u8; 32],
+        identity: ExternalIdentity,
+        pre_instructions: Vec<Instruction>,
+    ) -> Result<[u8; 32], RpcError> {
+        let address = self.new_address(&get_identity_link_seed(&identity));
+        let (proof, address_merkle_tree_root_index, mut nodes) = self
+            .new_address_proof_with_nodes(address, &[*node_address])
+            .await;
+
+        let mut accounts = tapestry::accounts::CreateIdentityLink {
+            payer: self.payer.pubkey(),
+            config: config_pda(),
+            node_account: None,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.new_address_remaining_accounts());
+        accounts.push(AccountMeta::new(self.env.nullifier_queue_pubkey, false));
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateIdentityLink {
+                proof,
+                address_merkle_tree_root_index,
+                node: nodes.pop(),
+                identity,
+            }
+            .data(),
+        };
+        let mut instructions = pre_instructions;
+        instructions.push(ix);
+        self.send_all(instructions, &[]).await?;
+        Ok(address)
+    }
+
+    async fn revoke_identity_link(
+        &mut self,
+        address: &[u8; 32],
+        attestation_authority: &Keypair,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let identity_link = decode_identity_link(&compressed_account);
+        let (proof, identity_link_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = tapestry::accounts::RevokeIdentityLink {
+            payer: self.payer.pubkey(),
+            attestation_authority: attestation_authority.pubkey(),
+            config: config_pda(),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::RevokeIdentityLink {
+                proof,
+                identity_link,
+                identity_link_meta,
+            }
+            .data(),
+        };
+        self.send(ix, &[attestation_authority]).await
+    }
+
+    // Appends a compressed account straight to the state tree, for layouts no
+    // instruction writes anymore. The leaf is added to the on-chain tree and to
+    // the indexer's copy so proofs against either root agree. The address is
+    // not inserted into the address tree, which only matters for creating it.
+    async fn seed_compressed_account<T: AnchorSerialize + DataHasher + Discriminator>(
+        &mut self,
+        account_key: AccountKey,
+        account: &T,
+    ) -> [u8; 32] {
+        let address_seed = get_account_seed(account_key, &Pubkey::new_unique().to_bytes());
+        let address = self.new_address(&address_seed);
+        let merkle_tree_pubkey = self.env.merkle_tree_pubkey;
+        let compressed_account = CompressedAccount {
+            owner: tapestry::ID,
+            lamports: 0,
+            address: Some(address),
+            data: Some(CompressedAccountData {
+                discriminator: T::discriminator(),
+                data: account.try_to_vec().unwrap(),
+                data_hash: account.hash::<Poseidon>().unwrap(),
+            }),
+        };
+
+        let mut tree_account = self
+            .rpc
+            .get_account(merkle_tree_pubkey)
+            .await
+            .unwrap()
+            .unwrap();
+        let (leaf_index, leaf) = {
+            let mut merkle_tree =
+                state_merkle_tree_from_bytes_zero_copy_mut(&mut tree_account.data).unwrap();
+            let leaf_index = merkle_tree.next_index() as u32;
+            let leaf = compressed_account
+                .hash::<Poseidon>(&merkle_tree_pubkey, &leaf_index)
+                .unwrap();
+            merkle_tree.append(&leaf).unwrap();
+            (leaf_index, leaf)
+        };
+        self.rpc
+            .context
+            .set_account(&merkle_tree_pubkey, &tree_account.into());
+
+        self.indexer
+            .state_merkle_trees
+            .iter_mut()
+            .find(|bundle| bundle.accounts.merkle_tree == merkle_tree_pubkey)
+            .unwrap()
+            .merkle_tree
+            .append(&leaf)
+            .unwrap();
+        self.indexer
+            .compressed_accounts
+            .push(CompressedAccountWithMerkleContext {
+                compressed_account,
+                merkle_context: MerkleContext {
+                    merkle_tree_pubkey,
+                    nullifier_queue_pubkey: self.env.nullifier_queue_pubkey,
+                    leaf_index,
+                    queue_index: None,
+                },
+            });
+        address
+    }
+
+    async fn migrate_node(&mut self, address: &[u8; 32]) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let node = decode_node_v1(&compressed_account);
+        let (proof, node_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = self.migrate_accounts();
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::MigrateNodeV1ToV2 {
+                proof,
+                node,
+                node_meta,
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    async fn migrate_edge(&mut self, address: &[u8; 32]) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let edge = decode_edge_v1(&compressed_account);
+        let (proof, edge_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = self.migrate_accounts();
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::MigrateEdgeV1ToV2 {
+                proof,
+                edge,
+                edge_meta,
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    fn migrate_accounts(&self) -> Vec<AccountMeta> {
+        tapestry::accounts::Migrate {
+            payer: self.payer.pubkey(),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None)
+    }
+
+    // Compressed accounts of the program at `address`, spent ones excluded
+    fn compressed_accounts_at(&self, address: &[u8; 32]) -> usize {
+        self.indexer
+            .get_compressed_accounts_by_owner(&tapestry::ID)
+            .into_iter()
+            .filter(|account| account.compressed_account.address == Some(*address))
+            .count()
+    }
+
+    async fn initialize_action_nonce(&mut self, signer: &Pubkey) -> Result<(), RpcError> {
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts: tapestry::accounts::InitializeActionNonce {
+                payer: self.payer.pubkey(),
+                signer: *signer,
+                action_nonce: action_nonce_pda(signer),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: tapestry::instruction::InitializeActionNonce {}.data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    // Relays `create_node_signed` with the action signed by `signer`, which
+    // differs from `owner` only to test rejected signatures. `relayed_node_args`
+    // replace the signed arguments in the instruction.
+    async fn create_node_signed(
+        &mut self,
+        owner: &Pubkey,
+        signer: &Keypair,
+        node_args: NodeArgs,
+        signed_action: SignedAction,
+        relayed_node_args: Option<NodeArgs>,
+    ) -> Result<[u8; 32], RpcError> {
+        let address_seed = AddressSeed::Random(Pubkey::new_unique().to_bytes());
+        let address = self.new_address(&address_seed.node_seed(&node_args).unwrap());
+        let (proof, address_merkle_tree_root_index) = self.new_address_proof(address).await;
+
+        let args = (&address_seed, &node_args, None::<Pubkey>)
+            .try_to_vec()
+            .unwrap();
+        let signature_ix =
+            signed_action_instruction(signer, &signed_action, "create_node", owner, &args);
+
+        let mut accounts = tapestry::accounts::CreateNodeSigned {
+            payer: self.payer.pubkey(),
+            update_authority: None,
+            owner: *owner,
+            action_nonce: action_nonce_pda(owner),
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.new_address_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateNodeSigned {
+                proof,
+                address_merkle_tree_root_index,
+                address_seed,
+                node_args: relayed_node_args.unwrap_or(node_args),
+                signed_action,
+            }
+            .data(),
+        };
+        self.send_all(vec![signature_ix, ix], &[]).await?;
+        Ok(address)
+    }
+
+    // Relays `create_edge_signed`, proving the node at `target_address` when
+    // given, see `create_node_signed`
+    async fn create_edge_signed(
+        &mut self,
+        owner: &Pubkey,
+        signer: &Keypair,
+        target_address: Option<&[u8; 32]>,
+        edge_args: EdgeArgs,
+        signed_action: SignedAction,
+        relayed_edge_args: Option<EdgeArgs>,
+    ) -> Result<[u8; 32], RpcError> {
+        let random_bytes = Pubkey::new_unique().to_bytes();
+        let address = self.new_address(&get_account_seed(AccountKey::EdgeV1, &random_bytes));
+        let (proof, address_merkle_tree_root_index, target, remaining_accounts) =
+            match target_address {
+                Some(target_address) => {
+                    let (proof, root_index, mut nodes) = self
+                        .new_address_proof_with_nodes(address, &[*target_address])
+                        .await;
+                    (
+                        proof,
+                        root_index,
+                        nodes.pop(),
+                        self.new_address_with_input_remaining_accounts(),
+                    )
+                }
+                None => {
+                    let (proof, root_index) = self.new_address_proof(address).await;
+                    (
+                        proof,
+                        root_index,
+                        None,
+                        self.new_address_remaining_accounts(),
+                    )
+                }
+            };
+
+        let address_seed = AddressSeed::Random(random_bytes);
+        let args = (&address_seed, &edge_args, None::<Pubkey>)
+            .try_to_vec()
+            .unwrap();
+        let signature_ix =
+            signed_action_instruction(signer, &signed_action, "create_edge", owner, &args);
+
+        let mut accounts = tapestry::accounts::CreateEdgeSigned {
+            payer: self.payer.pubkey(),
+            update_authority: None,
+            owner: *owner,
+            gate_token_account: None,
+            gate_metadata: None,
+            target_node_account: None,
+            action_nonce: action_nonce_pda(owner),
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(remaining_accounts);
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::CreateEdgeSigned {
+                proof,
+                address_merkle_tree_root_index,
+                address_seed,
+                edge_args: relayed_edge_args.unwrap_or(edge_args),
+                signed_action,
+                target,
+            }
+            .data(),
+        };
+        self.send_all(vec![signature_ix, ix], &[]).await?;
+        Ok(address)
+    }
+
+    // Relays `adjust_edge_weight_signed` for `authority`, see
+    // `create_node_signed`
+    async fn adjust_edge_weight_signed(
+        &mut self,
+        address: &[u8; 32],
+        authority: &Pubkey,
+        signer: &Keypair,
+        delta: i64,
+        signed_action: SignedAction,
+        relayed_delta: Option<i64>,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let edge = decode_edge(&compressed_account);
+        let (proof, edge_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let args = (*address, delta).try_to_vec().unwrap();
+        let signature_ix = signed_action_instruction(
+            signer,
+            &signed_action,
+            "adjust_edge_weight",
+            authority,
+            &args,
+        );
+
+        let mut accounts = tapestry::accounts::AdjustEdgeWeightSigned {
+            payer: self.payer.pubkey(),
+            authority: *authority,
+            action_nonce: action_nonce_pda(authority),
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::AdjustEdgeWeightSigned {
+                proof,
+                edge: VersionedEdge::V2(edge),
+                edge_meta,
+                delta: relayed_delta.unwrap_or(delta),
+                group: None,
+                signed_action,
+            }
+            .data(),
+        };
+        self.send_all(vec![signature_ix, ix], &[]).await
+    }
+
+    // Relays `set_node_update_authority_signed` for `authority`, see
+    // `create_node_signed`
+    async fn set_node_update_authority_signed(
+        &mut self,
+        address: &[u8; 32],
+        authority: &Pubkey,
+        signer: &Keypair,
+        new_update_authority: NodeUpdateAuthority,
+        signed_action: SignedAction,
+        relayed_update_authority: Option<NodeUpdateAuthority>,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let node = decode_node(&compressed_account);
+        let (proof, node_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let args = (*address, &new_update_authority).try_to_vec().unwrap();
+        let signature_ix = signed_action_instruction(
+            signer,
+            &signed_action,
+            "set_node_update_authority",
+            authority,
+            &args,
+        );
+
+        let mut accounts = tapestry::accounts::SetUpdateAuthoritySigned {
+            payer: self.payer.pubkey(),
+            authority: *authority,
+            action_nonce: action_nonce_pda(authority),
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::SetNodeUpdateAuthoritySigned {
+                proof,
+                node: VersionedNode::V2(node),
+                node_meta,
+                new_update_authority: relayed_update_authority.unwrap_or(new_update_authority),
+                group: None,
+                signed_action,
+            }
+            .data(),
+        };
+        self.send_all(vec![signature_ix, ix], &[]).await
+    }
+
+    // Relays `make_node_immutable_signed` for `authority`, signing the address
+    // at `signed_address`, see `create_node_signed`
+    async fn make_node_immutable_signed(
+        &mut self,
+        address: &[u8; 32],
+        authority: &Pubkey,
+        signer: &Keypair,
+        signed_address: &[u8; 32],
+        signed_action: SignedAction,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let node = decode_node(&compressed_account);
+        let (proof, node_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let args = signed_address.try_to_vec().unwrap();
+        let signature_ix = signed_action_instruction(
+            signer,
+            &signed_action,
+            "make_node_immutable",
+            authority,
+            &args,
+        );
+
+        let mut accounts = tapestry::accounts::MakeImmutableSigned {
+            payer: self.payer.pubkey(),
+            authority: *authority,
+            action_nonce: action_nonce_pda(authority),
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::MakeNodeImmutableSigned {
+                proof,
+                node: VersionedNode::V2(node),
+                node_meta,
+                group: None,
+                signed_action,
+            }
+            .data(),
+        };
+        self.send_all(vec![signature_ix, ix], &[]).await
+    }
+
+    async fn action_nonce(&mut self, signer: &Pubkey) -> u64 {
+        self.rpc
+            .get_anchor_account::<ActionNonce>(&action_nonce_pda(signer))
+            .await
+            .unwrap()
+            .unwrap()
+            .nonce
+    }
+
+    async fn tip_node(&mut self, address: &[u8; 32], lamports: u64) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let node = decode_node(&compressed_account);
+        let (proof, node_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = tapestry::accounts::TipNode {
+            payer: self.payer.pubkey(),
+            sol_pool_pda: Some(get_sol_pool_pda()),
+            node_account: None,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::TipNode {
+                proof,
+                node: Some(VersionedNode::V2(node)),
+                node_meta,
+                lamports,
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    // Tips a decompressed node through the placeholder at its address
+    async fn tip_decompressed_node(
+        &mut self,
+        address: &[u8; 32],
+        lamports: u64,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let (proof, node_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = tapestry::accounts::TipNode {
+            payer: self.payer.pubkey(),
+            sol_pool_pda: Some(get_sol_pool_pda()),
+            node_account: Some(node_account_pda(address)),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::TipNode {
+                proof,
+                node: None,
+                node_meta,
+                lamports,
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await
+    }
+
+    async fn withdraw_from_node(
+        &mut self,
+        address: &[u8; 32],
+        owner: &Keypair,
+        recipient: &Pubkey,
+        lamports: u64,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let node = decode_node(&compressed_account);
+        let (proof, node_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = tapestry::accounts::WithdrawFromNode {
+            payer: self.payer.pubkey(),
+            owner: owner.pubkey(),
+            sol_pool_pda: Some(get_sol_pool_pda()),
+            decompression_recipient: Some(*recipient),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::WithdrawFromNode {
+                proof,
+                node: VersionedNode::V2(node),
+                node_meta,
+                lamports,
+            }
+            .data(),
+        };
+        self.send(ix, &[owner]).await
+    }
+
+    // Decompresses the node at `address`, with the rent paid by `rent_payer`
+    // or the test payer
+    async fn decompress_node(
+        &mut self,
+        address: &[u8; 32],
+        authority: &Keypair,
+        rent_payer: Option<&Keypair>,
+    ) -> Result<Pubkey, RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let data = compressed_account.compressed_account.data.as_ref().unwrap();
+        let node: NodeV2 =
+            anchor_lang::AnchorDeserialize::deserialize(&mut data.data.as_slice()).unwrap();
+        let (proof, node_meta) = self.inclusion_proof(&compressed_account).await;
+        let node_account = node_account_pda(address);
+
+        let mut accounts = tapestry::accounts::DecompressNode {
+            payer: rent_payer.map_or(self.payer.pubkey(), Keypair::pubkey),
+            authority: authority.pubkey(),
+            node_account,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::DecompressNode {
+                proof,
+                node: VersionedNode::V2(node),
+                node_meta,
+                group: None,
+            }
+            .data(),
+        };
+        let mut signers = vec![authority];
+        signers.extend(rent_payer);
+        self.send(ix, &signers).await?;
+        Ok(node_account)
+    }
+
+    async fn recompress_node(
+        &mut self,
+        address: &[u8; 32],
+        authority: &Keypair,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let (proof, placeholder_meta) = self.inclusion_proof(&compressed_account).await;
+        let node_account = node_account_pda(address);
+        let rent_payer = self
+            .rpc
+            .get_anchor_account::<NodeAccount>(&node_account)
+            .await?
+            .unwrap()
+            .rent_payer;
+
+        let mut accounts = tapestry::accounts::RecompressNode {
+            payer: self.payer.pubkey(),
+            authority: authority.pubkey(),
+            node_account,
+            rent_payer,
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::RecompressNode {
+                proof,
+                placeholder_meta,
+                group: None,
+            }
+            .data(),
+        };
+        self.send(ix, &[authority]).await
+    }
+
+    // Rotates the update authority of an edge, or revokes it when `None`
+    async fn set_edge_update_authority(
+        &mut self,
+        address: &[u8; 32],
+        authority: &Keypair,
+        new_update_authority: Option<NodeUpdateAuthority>,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let edge = VersionedEdge::V2(decode_edge(&compressed_account));
+        let (proof, edge_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = tapestry::accounts::SetUpdateAuthority {
+            payer: self.payer.pubkey(),
+            authority: authority.pubkey(),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let data = match new_update_authority {
+            Some(new_update_authority) => tapestry::instruction::SetEdgeUpdateAuthority {
+                proof,
+                edge,
+                edge_meta,
+                new_update_authority,
+                group: None,
+            }
+            .data(),
+            None => tapestry::instruction::RevokeEdgeUpdateAuthority {
+                proof,
+                edge,
+                edge_meta,
+                group: None,
+            }
+            .data(),
+        };
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data,
+        };
+        self.send(ix, &[authority]).await
+    }
+
+    async fn make_edge_immutable(
+        &mut self,
+        address: &[u8; 32],
+        authority: &Keypair,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        let edge = decode_edge(&compressed_account);
+        let (proof, edge_meta) = self.inclusion_proof(&compressed_account).await;
+
+        let mut accounts = tapestry::accounts::MakeImmutable {
+            payer: self.payer.pubkey(),
+            authority: authority.pubkey(),
+            cpi_authority_pda: cpi_authority_pda(),
+            self_program: tapestry::ID,
+            light_system_program: light_system_program::ID,
+            registered_program_pda: get_registered_program_pda(&light_system_program::ID),
+            noop_program: NOOP_PROGRAM_ID,
+            account_compression_authority: get_cpi_authority_pda(&light_system_program::ID),
+            account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.input_remaining_accounts());
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::MakeEdgeImmutable {
+                proof,
+                edge: VersionedEdge::V2(edge),
+                edge_meta,
+                group: None,
+            }
+            .data(),
+        };
+        self.send(ix, &[authority]).await
+    }
+
+    async fn assert_edge(
+        &mut self,
+        address: &[u8; 32],
+        source_node: &str,
+        target_node: &str,
+    ) -> Result<(), RpcError> {
+        let compressed_account = self.compressed_account(address);
+        self.assert_edge_account(&compressed_account, source_node, target_node)
+            .await
+    }
+
+    // Asserts the given edge account, which may no longer be in the indexer
+    async fn assert_edge_account(
+        &mut self,
+        compressed_account: &CompressedAccountWithMerkleContext,
+        source_node: &str,
+        target_node: &str,
+    ) -> Result<(), RpcError> {
+        let edge = decode_edge(compressed_account);
+        let (proof, edge_meta) = self.inclusion_proof(compressed_account).await;
+
+        let accounts = tapestry::accounts::AssertEdge {
+            merkle_tree: self.env.merkle_tree_pubkey,
+            nullifier_queue: self.env.nullifier_queue_pubkey,
+        }
+        .to_account_metas(None);
+
+        let ix = Instruction {
+            program_id: tapestry::ID,
+            accounts,
+            data: tapestry::instruction::AssertEdge {
+                proof,
+                edge: VersionedEdge::V2(edge),
+                edge_meta,
+                source_node: source_node.to_string(),
+                target_node: target_node.to_string(),
+                edge_type: "follow".to_string(),
+            }
+            .data(),
+        };
+        self.send(ix, &[]).await
+    }
+}
+
+fn cpi_authority_pda() -> Pubkey {
+    Pubkey::find_program_address(&[CPI_AUTHORITY_SEED.as_bytes()], &tapestry::ID).0
+}
+
+fn node_account_pda(address: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[NODE_ACCOUNT_SEED.as_bytes(), address], &tapestry::ID).0
+}
+
+fn config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_SEED.as_bytes()], &tapestry::ID).0
+}
+
+// Ed25519 instruction attesting that `node` controls `identity`
+fn attestation_instruction(
+    authority: &Keypair,
+    node: &str,
+    identity: &ExternalIdentity,
+) -> Instruction {
+    new_ed25519_instruction(
+        &ed25519_dalek::Keypair::from_bytes(&authority.to_bytes()).unwrap(),
+        &IdentityLinkV1::attestation_message(node, identity),
+    )
+}
+
+// Ed25519 instruction signing `action` with `args` on behalf of `authority`
+fn signed_action_instruction(
+    signer: &Keypair,
+    signed_action: &SignedAction,
+    action: &str,
+    authority: &Pubkey,
+    args: &[u8],
+) -> Instruction {
+    new_ed25519_instruction(
+        &ed25519_dalek::Keypair::from_bytes(&signer.to_bytes()).unwrap(),
+        &signed_action.message(action, authority, args),
+    )
+}
+
+fn action_nonce_pda(signer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[ACTION_NONCE_SEED.as_bytes(), signer.as_ref()],
+        &tapestry::ID,
+    )
+    .0
+}
+
+fn decode_node(compressed_account: &CompressedAccountWithMerkleContext) -> NodeV2 {
+    let data = compressed_account.compressed_account.data.as_ref().unwrap();
+    anchor_lang::AnchorDeserialize::deserialize(&mut data.data.as_slice()).unwrap()
+}
+
+fn decode_node_v1(compressed_account: &CompressedAccountWithMerkleContext) -> NodeV1 {
+    let data = compressed_account.compressed_account.data.as_ref().unwrap();
+    anchor_lang::AnchorDeserialize::deserialize(&mut data.data.as_slice()).unwrap()
+}
+
+// Accounts in the layouts the deployed program wrote before V2, captured in
+// the hash vectors shared with tests/hashing.rs
+fn legacy_account_data(account_key: &str) -> Vec<Vec<u8>> {
+    let vectors: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/hash_vectors.json")).unwrap();
+    vectors["account_hashes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|vector| vector["account_key"] == account_key)
+        .map(|vector| hex::decode(vector["data"].as_str().unwrap()).unwrap())
+        .collect()
+}
+
+fn decode_edge_v1(compressed_account: &CompressedAccountWithMerkleContext) -> EdgeV1 {
+    let data = compressed_account.compressed_account.data.as_ref().unwrap();
+    anchor_lang::AnchorDeserialize::deserialize(&mut data.data.as_slice()).unwrap()
+}
+
+fn discriminator(compressed_account: &CompressedAccountWithMerkleContext) -> [u8; 8] {
+    compressed_account
+        .compressed_account
+        .data
+        .as_ref()
+        .unwrap()
+        .discriminator
+}
+
+fn decode_edge(compressed_account: &CompressedAccountWithMerkleContext) -> EdgeV2 {
+    let data = compressed_account.compressed_account.data.as_ref().unwrap();
+    anchor_lang::AnchorDeserialize::deserialize(&mut data.data.as_slice()).unwrap()
+}
+
+fn decode_pending_edge(compressed_account: &CompressedAccountWithMerkleContext) -> PendingEdgeV1 {
+    let data = compressed_account.compressed_account.data.as_ref().unwrap();
+    anchor_lang::AnchorDeserialize::deserialize(&mut data.data.as_slice()).unwrap()
+}
+
+fn decode_identity_link(compressed_account: &CompressedAccountWithMerkleContext) -> IdentityLinkV1 {
+    let data = compressed_account.compressed_account.data.as_ref().unwrap();
+    anchor_lang::AnchorDeserialize::deserialize(&mut data.data.as_slice()).unwrap()
+}
+
+fn decode_counter(compressed_account: &CompressedAccountWithMerkleContext) -> NodeCounterV1 {
+    let data = compressed_account.compressed_account.data.as_ref().unwrap();
+    anchor_lang::AnchorDeserialize::deserialize(&mut data.data.as_slice()).unwrap()
+}
+
+// Edges refer to nodes by their address in base58
+fn node_id(address: &[u8; 32]) -> String {
+    Pubkey::new_from_array(*address).to_string()
+}
+
+// Signed action that does not expire
+fn signed_action(nonce: u64) -> SignedAction {
+    SignedAction {
+        nonce,
+        expires_at: i64::MAX,
+    }
+}
+
+fn assert_tapestry_error<T: std::fmt::Debug>(result: Result<T, RpcError>, error: ZkNftError) {
+    assert_error_code(result, error.into());
+}
+
+fn assert_error_code<T: std::fmt::Debug>(result: Result<T, RpcError>, code: u32) {
+    match result {
+        Err(RpcError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(actual),
+        ))) => assert_eq!(actual, code),
+        other => panic!("expected error {}, got {:?}", code, other),
+    }
+}
+
+#[tokio::test]
+async fn test_create_node() {
+    let mut context = TestContext::new().await;
+    let owner = Pubkey::new_unique();
+    let update_authority = Keypair::new();
+    let remaining_accounts = context.new_address_remaining_accounts();
+
+    let address = context
+        .create_node(
+            &owner,
+            Some(&update_authority),
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+
+    let compressed_account = context.compressed_account(&address);
+    let data = compressed_account.compressed_account.data.unwrap();
+    let node: tapestry::state::NodeV2 =
+        anchor_lang::AnchorDeserialize::deserialize(&mut data.data.as_slice()).unwrap();
+    assert_eq!(node.key, AccountKey::NodeV2);
+    assert_eq!(node.owner, owner);
+    assert_eq!(node.namespace, "social");
+    assert_eq!(node.label, "alice");
+    assert_eq!(
+        node.node_data.get_properties().unwrap(),
+        node_args("alice").properties
+    );
+}
+
+#[tokio::test]
+async fn test_create_node_invalid_merkle_trees() {
+    let mut context = TestContext::new().await;
+    let mut remaining_accounts = context.new_address_remaining_accounts();
+    remaining_accounts[0] = AccountMeta::new(Pubkey::new_unique(), false);
+
+    let result = context
+        .create_node(
+            &Pubkey::new_unique(),
+            None,
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidMerkleTrees);
+}
+
+#[tokio::test]
+async fn test_create_node_namespace_too_long() {
+    let mut context = TestContext::new().await;
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let mut node_args = node_args("alice");
+    node_args.namespace = "n".repeat(MAX_NAMESPACE_LENGTH + 1);
+
+    let result = context
+        .create_node(&Pubkey::new_unique(), None, node_args, remaining_accounts)
+        .await;
+    assert_tapestry_error(result, ZkNftError::NamespaceTooLong);
+}
+
+#[tokio::test]
+async fn test_create_edge_edge_type_length() {
+    let mut context = TestContext::new().await;
+
+    let mut empty_edge_args = edge_args("alice", "bob");
+    empty_edge_args.edge_type = String::new();
+    let result = context
+        .create_edge(&Pubkey::new_unique(), None, empty_edge_args)
+        .await;
+    assert_tapestry_error(result, ZkNftError::EdgeTypeEmpty);
+
+    let mut long_edge_args = edge_args("alice", "bob");
+    long_edge_args.edge_type = "t".repeat(MAX_EDGE_TYPE_LENGTH + 1);
+    let result = context
+        .create_edge(&Pubkey::new_unique(), None, long_edge_args)
+        .await;
+    assert_tapestry_error(result, ZkNftError::EdgeTypeTooLong);
+
+    // The maximum length itself is accepted
+    let mut max_edge_args = edge_args("alice", "bob");
+    max_edge_args.edge_type = "t".repeat(MAX_EDGE_TYPE_LENGTH);
+    let address = context
+        .create_edge(&Pubkey::new_unique(), None, max_edge_args)
+        .await
+        .unwrap();
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.edge_type, "t".repeat(MAX_EDGE_TYPE_LENGTH));
+}
+
+fn encrypted_property(ciphertext_length: usize) -> EncryptedProperty {
+    EncryptedProperty {
+        key: "email".to_string(),
+        recipient: Pubkey::new_unique(),
+        ephemeral_public_key: [7u8; 32],
+        nonce: [9u8; 24],
+        ciphertext: vec![1u8; ciphertext_length],
+    }
+}
+
+#[tokio::test]
+async fn test_create_node_with_encrypted_property() {
+    let mut context = TestContext::new().await;
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let encrypted_property = encrypted_property(48);
+    let mut node_args = node_args("alice");
+    node_args.encrypted_properties = vec![encrypted_property.clone()];
+
+    let address = context
+        .create_node(&Pubkey::new_unique(), None, node_args, remaining_accounts)
+        .await
+        .unwrap();
+
+    let data = context
+        .compressed_account(&address)
+        .compressed_account
+        .data
+        .unwrap();
+    let node: tapestry::state::NodeV2 =
+        anchor_lang::AnchorDeserialize::deserialize(&mut data.data.as_slice()).unwrap();
+    assert_eq!(
+        node.node_data.get_encrypted_properties().unwrap(),
+        vec![encrypted_property]
+    );
+}
+
+#[tokio::test]
+async fn test_create_node_ciphertext_too_long() {
+    let mut context = TestContext::new().await;
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let mut node_args = node_args("alice");
+    node_args.encrypted_properties = vec![encrypted_property(MAX_CIPHERTEXT_LENGTH + 1)];
+
+    let result = context
+        .create_node(&Pubkey::new_unique(), None, node_args, remaining_accounts)
+        .await;
+    assert_tapestry_error(result, ZkNftError::CiphertextTooLong);
+}
+
+#[tokio::test]
+async fn test_create_edge() {
+    let mut context = TestContext::new().await;
+    let owner = Pubkey::new_unique();
+
+    let address = context
+        .create_edge(&owner, None, edge_args("alice", "bob"))
+        .await
+        .unwrap();
+
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.key, AccountKey::EdgeV2);
+    assert_eq!(edge.namespace, "social");
+    assert_eq!(edge.source_node, "alice");
+    assert_eq!(edge.target_node, "bob");
+    assert_eq!(edge.edge_type, "follow");
+    assert_eq!(edge.owner, owner);
+    assert_eq!(edge.weight, 10);
+}
+
+#[tokio::test]
+async fn test_create_edge_self_reference() {
+    let mut context = TestContext::new().await;
+
+    let result = context
+        .create_edge(&Pubkey::new_unique(), None, edge_args("alice", "alice"))
+        .await;
+    assert_tapestry_error(result, ZkNftError::SelfReferenceNotAllowed);
+}
+
+#[tokio::test]
+async fn test_create_edge_cpi() {
+    let mut context = TestContext::new().await;
+    let (authority, bump) = cpi_caller::authority();
+
+    let address = context
+        .create_edge_cpi(
+            None,
+            &cpi_caller::ID,
+            vec![cpi_caller::AUTHORITY_SEED.to_vec(), vec![bump]],
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    // The program PDA owns the edge and is recorded with its program
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.owner, authority);
+    assert_eq!(edge.edge_data.caller_program, Some(cpi_caller::ID));
+    assert_eq!(
+        edge.update_authority,
+        NodeUpdateAuthority::Program {
+            program_id: cpi_caller::ID,
+            authority,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_create_edge_cpi_authority_seeds_mismatch() {
+    let mut context = TestContext::new().await;
+    let (_, bump) = cpi_caller::authority();
+
+    let result = context
+        .create_edge_cpi(
+            None,
+            &cpi_caller::ID,
+            vec![b"other".to_vec(), vec![bump]],
+            edge_args("alice", "bob"),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidProgramAuthority);
+}
+
+#[tokio::test]
+async fn test_create_edge_cpi_calling_program_mismatch() {
+    let mut context = TestContext::new().await;
+    let (_, bump) = cpi_caller::authority();
+
+    // The PDA signs, but is claimed for another program
+    let result = context
+        .create_edge_cpi(
+            None,
+            &light_system_program::ID,
+            vec![cpi_caller::AUTHORITY_SEED.to_vec(), vec![bump]],
+            edge_args("alice", "bob"),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidProgramAuthority);
+}
+
+#[tokio::test]
+async fn test_create_edge_cpi_keypair_authority() {
+    let mut context = TestContext::new().await;
+    let (_, bump) = cpi_caller::authority();
+
+    // A keypair cannot pass for the program's PDA
+    let result = context
+        .create_edge_cpi(
+            Some(&Keypair::new()),
+            &cpi_caller::ID,
+            vec![cpi_caller::AUTHORITY_SEED.to_vec(), vec![bump]],
+            edge_args("alice", "bob"),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidProgramAuthority);
+}
+
+#[tokio::test]
+async fn test_adjust_edge_weight() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    context
+        .adjust_edge_weight(&address, &update_authority, -3)
+        .await
+        .unwrap();
+
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.weight, 7);
+}
+
+#[tokio::test]
+async fn test_adjust_edge_weight_invalid_authority() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    let result = context
+        .adjust_edge_weight(&address, &Keypair::new(), 1)
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidAuthority);
+}
+
+#[tokio::test]
+async fn test_adjust_edge_weight_overflow() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    let result = context
+        .adjust_edge_weight(&address, &update_authority, -11)
+        .await;
+    assert_tapestry_error(result, ZkNftError::EdgeWeightOverflow);
+}
+
+#[tokio::test]
+async fn test_assert_edge() {
+    let mut context = TestContext::new().await;
+    let address = context
+        .create_edge(&Pubkey::new_unique(), None, edge_args("alice", "bob"))
+        .await
+        .unwrap();
+
+    let edge = context.compressed_account(&address);
+    context.assert_edge(&address, "alice", "bob").await.unwrap();
+
+    // The edge is only read, so it stays at its leaf
+    let asserted_edge = context.compressed_account(&address);
+    assert_eq!(asserted_edge.compressed_account, edge.compressed_account);
+    assert_eq!(
+        asserted_edge.merkle_context.leaf_index,
+        edge.merkle_context.leaf_index
+    );
+    context.assert_edge(&address, "alice", "bob").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_assert_nullified_edge() {
+    let mut context = TestContext::new().await;
+    let mut expired_edge_args = edge_args("alice", "bob");
+    expired_edge_args.validity = EdgeValidity {
+        valid_from: None,
+        valid_until: Some(1),
+    };
+    let address = context
+        .create_edge(&Pubkey::new_unique(), None, expired_edge_args)
+        .await
+        .unwrap();
+    let edge = context.compressed_account(&address);
+    context.prune_expired_edge(&address, None).await.unwrap();
+
+    // The pruned edge is still in older roots, but waits in the nullifier queue
+    let result = context.assert_edge_account(&edge, "alice", "bob").await;
+    assert_tapestry_error(result, ZkNftError::AccountNullified);
+}
+
+#[tokio::test]
+async fn test_assert_edge_mismatch() {
+    let mut context = TestContext::new().await;
+    let address = context
+        .create_edge(&Pubkey::new_unique(), None, edge_args("alice", "bob"))
+        .await
+        .unwrap();
+
+    let result = context.assert_edge(&address, "bob", "alice").await;
+    assert_tapestry_error(result, ZkNftError::EdgeMismatch);
+}
+
+// Edge args valid from `valid_from` until `valid_until`
+fn edge_args_valid(valid_from: Option<i64>, valid_until: Option<i64>) -> EdgeArgs {
+    EdgeArgs {
+        validity: EdgeValidity {
+            valid_from,
+            valid_until,
+        },
+        ..edge_args("alice", "bob")
+    }
+}
+
+#[tokio::test]
+async fn test_assert_inactive_edge() {
+    let mut context = TestContext::new().await;
+
+    // Expired
+    let address = context
+        .create_edge(&Pubkey::new_unique(), None, edge_args_valid(None, Some(1)))
+        .await
+        .unwrap();
+    let result = context.assert_edge(&address, "alice", "bob").await;
+    assert_tapestry_error(result, ZkNftError::EdgeNotActive);
+
+    // Not valid yet
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            None,
+            edge_args_valid(Some(i64::MAX), None),
+        )
+        .await
+        .unwrap();
+    let result = context.assert_edge(&address, "alice", "bob").await;
+    assert_tapestry_error(result, ZkNftError::EdgeNotActive);
+}
+
+#[tokio::test]
+async fn test_prune_unexpired_edge() {
+    let mut context = TestContext::new().await;
+
+    // Without a window, and before the window started
+    for edge_args in [
+        edge_args_valid(None, None),
+        edge_args_valid(Some(i64::MAX - 1), Some(i64::MAX)),
+    ] {
+        let address = context
+            .create_edge(&Pubkey::new_unique(), None, edge_args)
+            .await
+            .unwrap();
+        let result = context.prune_expired_edge(&address, None).await;
+        assert_tapestry_error(result, ZkNftError::EdgeNotExpired);
+        assert!(context.has_compressed_account(&address));
+    }
+}
+
+#[tokio::test]
+async fn test_create_edge_invalid_validity() {
+    let mut context = TestContext::new().await;
+
+    // The window must not be empty
+    for edge_args in [
+        edge_args_valid(Some(10), Some(10)),
+        edge_args_valid(Some(11), Some(10)),
+    ] {
+        let result = context
+            .create_edge(&Pubkey::new_unique(), None, edge_args)
+            .await;
+        assert_tapestry_error(result, ZkNftError::InvalidEdgeValidity);
+    }
+}
+
+#[tokio::test]
+async fn test_create_gated_edge() {
+    let mut context = TestContext::new().await;
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let target_address = context
+        .create_node(
+            &Pubkey::new_unique(),
+            None,
+            node_args("club"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let owner = Keypair::new();
+    let target_node = Pubkey::new_from_array(target_address).to_string();
+
+    // Ungated nodes accept any owner, the edge records that no gate applied
+    let address = context
+        .create_gated_edge(
+            &owner,
+            &target_address,
+            None,
+            None,
+            edge_args("alice", &target_node),
+        )
+        .await
+        .unwrap();
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.edge_data.token_gate, TokenGate::None);
+}
+
+#[tokio::test]
+async fn test_create_gated_edge_target_mismatch() {
+    let mut context = TestContext::new().await;
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let target_address = context
+        .create_node(
+            &Pubkey::new_unique(),
+            None,
+            node_args("club"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+
+    let result = context
+        .create_gated_edge(
+            &Keypair::new(),
+            &target_address,
+            None,
+            None,
+            edge_args("alice", "bob"),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::GateTargetMismatch);
+}
+
+#[tokio::test]
+async fn test_create_gated_edge_missing_token_account() {
+    let mut context = TestContext::new().await;
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let mut gated_node_args = node_args("club");
+    gated_node_args.token_gate = TokenGate::Mint {
+        mint: Pubkey::new_unique(),
+        min_amount: 1,
+    };
+    let target_address = context
+        .create_node(
+            &Pubkey::new_unique(),
+            None,
+            gated_node_args,
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let target_node = Pubkey::new_from_array(target_address).to_string();
+
+    let result = context
+        .create_gated_edge(
+            &Keypair::new(),
+            &target_address,
+            None,
+            None,
+            edge_args("alice", &target_node),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::TokenGateNotSatisfied);
+}
+
+#[tokio::test]
+async fn test_create_gated_edge_mint() {
+    let mut context = TestContext::new().await;
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let mint = Pubkey::new_unique();
+    let token_gate = TokenGate::Mint {
+        mint,
+        min_amount: 2,
+    };
+    let mut gated_node_args = node_args("club");
+    gated_node_args.token_gate = token_gate;
+    let target_address = context
+        .create_node(
+            &Pubkey::new_unique(),
+            None,
+            gated_node_args,
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let target_node = Pubkey::new_from_array(target_address).to_string();
+    let owner = Keypair::new();
+
+    // Holding less than the minimum doesn't satisfy the gate
+    let token_account = context.set_token_account(&owner.pubkey(), &mint, 1);
+    let result = context
+        .create_gated_edge(
+            &owner,
+            &target_address,
+            Some(token_account),
+            None,
+            edge_args("alice", &target_node),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::TokenGateNotSatisfied);
+
+    let token_account = context.set_token_account(&owner.pubkey(), &mint, 2);
+    let address = context
+        .create_gated_edge(
+            &owner,
+            &target_address,
+            Some(token_account),
+            None,
+            edge_args("alice", &target_node),
+        )
+        .await
+        .unwrap();
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.edge_data.token_gate, token_gate);
+}
+
+#[tokio::test]
+async fn test_create_gated_edge_collection() {
+    let mut context = TestContext::new().await;
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let collection = Pubkey::new_unique();
+    let token_gate = TokenGate::Collection {
+        collection,
+        min_amount: 1,
+    };
+    let mut gated_node_args = node_args("club");
+    gated_node_args.token_gate = token_gate;
+    let target_address = context
+        .create_node(
+            &Pubkey::new_unique(),
+            None,
+            gated_node_args,
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let target_node = Pubkey::new_from_array(target_address).to_string();
+    let owner = Keypair::new();
+    let nft_mint = Pubkey::new_unique();
+    let token_account = context.set_token_account(&owner.pubkey(), &nft_mint, 1);
+
+    // An NFT from a different collection doesn't satisfy the gate
+    let other_metadata = context.set_collection_metadata(&nft_mint, &Pubkey::new_unique());
+    let result = context
+        .create_gated_edge(
+            &owner,
+            &target_address,
+            Some(token_account),
+            Some(other_metadata),
+            edge_args("alice", &target_node),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::TokenGateNotSatisfied);
+
+    let metadata = context.set_collection_metadata(&nft_mint, &collection);
+    let address = context
+        .create_gated_edge(
+            &owner,
+            &target_address,
+            Some(token_account),
+            Some(metadata),
+            edge_args("alice", &target_node),
+        )
+        .await
+        .unwrap();
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.edge_data.token_gate, token_gate);
+}
+
+#[tokio::test]
+async fn test_create_edge_without_target_skips_gate() {
+    let mut context = TestContext::new().await;
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let mut gated_node_args = node_args("club");
+    gated_node_args.token_gate = TokenGate::Mint {
+        mint: Pubkey::new_unique(),
+        min_amount: 1,
+    };
+    let target_address = context
+        .create_node(
+            &Pubkey::new_unique(),
+            None,
+            gated_node_args,
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let target_leaf_index = context
+        .compressed_account(&target_address)
+        .merkle_context
+        .leaf_index;
+
+    // Leaving the target out leaves it untouched, and the edge records that no
+    // gate was checked
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            None,
+            edge_args("alice", &node_id(&target_address)),
+        )
+        .await
+        .unwrap();
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.edge_data.token_gate, TokenGate::None);
+    assert_eq!(
+        context
+            .compressed_account(&target_address)
+            .merkle_context
+            .leaf_index,
+        target_leaf_index
+    );
+}
+
+// Creates a source node owned by `owner` and counters for it and "bob"
+async fn setup_counted_nodes(
+    context: &mut TestContext,
+    owner: &Keypair,
+) -> ([u8; 32], [u8; 32], [u8; 32]) {
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let source_address = context
+        .create_node(
+            &owner.pubkey(),
+            None,
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let source_counter = context
+        .create_node_counter(&node_id(&source_address))
+        .await
+        .unwrap();
+    let target_counter = context.create_node_counter("bob").await.unwrap();
+    (source_address, source_counter, target_counter)
+}
+
+#[tokio::test]
+async fn test_create_counted_edge() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    let (source_address, alice_counter, bob_counter) =
+        setup_counted_nodes(&mut context, &owner).await;
+
+    let address = context
+        .create_counted_edge(
+            &owner,
+            &source_address,
+            &alice_counter,
+            &bob_counter,
+            edge_args(&node_id(&source_address), "bob"),
+        )
+        .await
+        .unwrap();
+
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert!(edge.edge_data.counted);
+    let alice = decode_counter(&context.compressed_account(&alice_counter));
+    assert_eq!((alice.counted_in_edges, alice.counted_out_edges), (0, 1));
+    let bob = decode_counter(&context.compressed_account(&bob_counter));
+    assert_eq!((bob.counted_in_edges, bob.counted_out_edges), (1, 0));
+}
+
+#[tokio::test]
+async fn test_create_counted_edge_counter_mismatch() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    let (source_address, alice_counter, bob_counter) =
+        setup_counted_nodes(&mut context, &owner).await;
+
+    let result = context
+        .create_counted_edge(
+            &owner,
+            &source_address,
+            &bob_counter,
+            &alice_counter,
+            edge_args(&node_id(&source_address), "bob"),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::CounterMismatch);
+}
+
+#[tokio::test]
+async fn test_create_counted_edge_requires_source_owner() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    let (source_address, alice_counter, bob_counter) =
+        setup_counted_nodes(&mut context, &owner).await;
+
+    // Only the owner of the source node may count edges from it
+    let result = context
+        .create_counted_edge(
+            &Keypair::new(),
+            &source_address,
+            &alice_counter,
+            &bob_counter,
+            edge_args(&node_id(&source_address), "bob"),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidAuthority);
+
+    // The source node has to be the edge's source
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let other_address = context
+        .create_node(
+            &owner.pubkey(),
+            None,
+            node_args("carol"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let result = context
+        .create_counted_edge(
+            &owner,
+            &other_address,
+            &alice_counter,
+            &bob_counter,
+            edge_args(&node_id(&source_address), "bob"),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::EndpointMismatch);
+}
+
+#[tokio::test]
+async fn test_prune_expired_counted_edge() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    let (source_address, alice_counter, bob_counter) =
+        setup_counted_nodes(&mut context, &owner).await;
+    let mut expired_edge_args = edge_args(&node_id(&source_address), "bob");
+    expired_edge_args.validity = EdgeValidity {
+        valid_from: None,
+        valid_until: Some(1),
+    };
+    let address = context
+        .create_counted_edge(
+            &owner,
+            &source_address,
+            &alice_counter,
+            &bob_counter,
+            expired_edge_args,
+        )
+        .await
+        .unwrap();
+
+    // A counted edge can't be pruned without uncounting it
+    let result = context.prune_expired_edge(&address, None).await;
+    assert_tapestry_error(result, ZkNftError::MissingCounters);
+
+    context
+        .prune_expired_edge(&address, Some((alice_counter, bob_counter)))
+        .await
+        .unwrap();
+    assert!(!context.has_compressed_account(&address));
+    let alice = decode_counter(&context.compressed_account(&alice_counter));
+    assert_eq!((alice.counted_in_edges, alice.counted_out_edges), (0, 0));
+    let bob = decode_counter(&context.compressed_account(&bob_counter));
+    assert_eq!((bob.counted_in_edges, bob.counted_out_edges), (0, 0));
+}
+
+#[tokio::test]
+async fn test_adjust_edge_weight_by_owner() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    let address = context
+        .create_edge(&owner.pubkey(), None, edge_args("alice", "bob"))
+        .await
+        .unwrap();
+
+    context
+        .adjust_edge_weight(&address, &owner, 5)
+        .await
+        .unwrap();
+
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.weight, 15);
+}
+
+#[tokio::test]
+async fn test_rotate_edge_update_authority() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    let new_update_authority = Keypair::new();
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    context
+        .set_edge_update_authority(
+            &address,
+            &update_authority,
+            Some(NodeUpdateAuthority::Address(new_update_authority.pubkey())),
+        )
+        .await
+        .unwrap();
+
+    let result = context
+        .adjust_edge_weight(&address, &update_authority, 1)
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidAuthority);
+    context
+        .adjust_edge_weight(&address, &new_update_authority, 1)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_revoke_edge_update_authority() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    context
+        .set_edge_update_authority(&address, &update_authority, None)
+        .await
+        .unwrap();
+
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.update_authority, NodeUpdateAuthority::None);
+    assert!(!edge.is_mutable);
+    let result = context
+        .adjust_edge_weight(&address, &update_authority, 1)
+        .await;
+    assert_tapestry_error(result, ZkNftError::EdgeNotMutable);
+}
+
+#[tokio::test]
+async fn test_make_edge_immutable() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    context
+        .make_edge_immutable(&address, &update_authority)
+        .await
+        .unwrap();
+
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert!(!edge.is_mutable);
+    assert_eq!(
+        edge.update_authority,
+        NodeUpdateAuthority::Address(update_authority.pubkey())
+    );
+    let result = context
+        .make_edge_immutable(&address, &update_authority)
+        .await;
+    assert_tapestry_error(result, ZkNftError::EdgeNotMutable);
+}
+
+#[tokio::test]
+async fn test_threshold_update_authority() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    let signers = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    context
+        .set_edge_update_authority(
+            &address,
+            &update_authority,
+            Some(NodeUpdateAuthority::Threshold {
+                threshold: 2,
+                signers: signers.iter().map(|signer| signer.pubkey()).collect(),
+            }),
+        )
+        .await
+        .unwrap();
+
+    let result = context.adjust_edge_weight(&address, &signers[0], 1).await;
+    assert_tapestry_error(result, ZkNftError::ThresholdNotMet);
+    context
+        .adjust_edge_weight_with_signers(&address, &signers[0], &[&signers[2]], 1)
+        .await
+        .unwrap();
+
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.weight, 11);
+}
+
+#[tokio::test]
+async fn test_threshold_update_authority_unreachable() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    let result = context
+        .set_edge_update_authority(
+            &address,
+            &update_authority,
+            Some(NodeUpdateAuthority::Threshold {
+                threshold: 3,
+                signers: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            }),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidThreshold);
+}
+
+#[tokio::test]
+async fn test_group_update_authority() {
+    let mut context = TestContext::new().await;
+    let group_authority = Keypair::new();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let group_address = context
+        .create_node(
+            &Pubkey::new_unique(),
+            Some(&group_authority),
+            node_args("brand"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let update_authority = Keypair::new();
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    context
+        .set_edge_update_authority(
+            &address,
+            &update_authority,
+            Some(NodeUpdateAuthority::Group(group_address)),
+        )
+        .await
+        .unwrap();
+
+    let result = context
+        .adjust_edge_weight(&address, &group_authority, 1)
+        .await;
+    assert_tapestry_error(result, ZkNftError::MissingGroupAuthority);
+    let result = context
+        .adjust_edge_weight_as_group(&address, &group_address, &update_authority, 1)
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidAuthority);
+    context
+        .adjust_edge_weight_as_group(&address, &group_address, &group_authority, 1)
+        .await
+        .unwrap();
+
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.weight, 11);
+}
+
+#[tokio::test]
+async fn test_decompress_and_recompress_node() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let address = context
+        .create_node(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let compressed_node = context.compressed_account(&address).compressed_account.data;
+
+    let node_account = context
+        .decompress_node(&address, &update_authority, None)
+        .await
+        .unwrap();
+
+    let decompressed: NodeAccount = context
+        .rpc
+        .get_anchor_account(&node_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(decompressed.address, address);
+    assert_eq!(decompressed.node.label, "alice");
+    let placeholder = context.compressed_account(&address).compressed_account.data;
+    assert_ne!(placeholder, compressed_node);
+
+    context
+        .recompress_node(&address, &update_authority)
+        .await
+        .unwrap();
+
+    assert!(context
+        .rpc
+        .get_anchor_account::<NodeAccount>(&node_account)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        context.compressed_account(&address).compressed_account.data,
+        compressed_node
+    );
+}
+
+#[tokio::test]
+async fn test_decompress_and_recompress_tipped_node() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    let rent_payer = Keypair::new();
+    context
+        .rpc
+        .airdrop_lamports(&rent_payer.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let address = context
+        .create_node(
+            &owner.pubkey(),
+            None,
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    context.tip_node(&address, 1_000_000).await.unwrap();
+    let compressed_node = context.compressed_account(&address).compressed_account;
+    let rent_payer_balance = context.rpc.get_balance(&rent_payer.pubkey()).await.unwrap();
+
+    // The placeholder keeps the node's address and lamports
+    let node_account = context
+        .decompress_node(&address, &owner, Some(&rent_payer))
+        .await
+        .unwrap();
+    let decompressed: NodeAccount = context
+        .rpc
+        .get_anchor_account(&node_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(decompressed.rent_payer, rent_payer.pubkey());
+    let placeholder = context.compressed_account(&address).compressed_account;
+    assert_eq!(placeholder.lamports, 1_000_000);
+
+    // Recompressing through another payer still refunds the rent payer
+    context.recompress_node(&address, &owner).await.unwrap();
+    let recompressed_node = context.compressed_account(&address).compressed_account;
+    assert_eq!(recompressed_node.address, Some(address));
+    assert_eq!(recompressed_node.lamports, 1_000_000);
+    assert_eq!(recompressed_node.data, compressed_node.data);
+    assert_eq!(
+        context.rpc.get_balance(&rent_payer.pubkey()).await.unwrap(),
+        rent_payer_balance
+    );
+}
+
+#[tokio::test]
+async fn test_decompressed_node_as_endpoint() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let address = context
+        .create_node(
+            &owner.pubkey(),
+            None,
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let node_account = context
+        .decompress_node(&address, &owner, None)
+        .await
+        .unwrap();
+    let placeholder = context.compressed_account(&address);
+
+    // The node account stands in for the proven node and is only read
+    let edge_address = context
+        .create_edge_to_node_account(
+            &Pubkey::new_unique(),
+            &node_account,
+            edge_args("carol", &node_id(&address)),
+        )
+        .await
+        .unwrap();
+    let edge = decode_edge(&context.compressed_account(&edge_address));
+    assert_eq!(edge.target_node, node_id(&address));
+    assert_eq!(edge.edge_data.token_gate, TokenGate::None);
+    assert_eq!(
+        context
+            .compressed_account(&address)
+            .merkle_context
+            .leaf_index,
+        placeholder.merkle_context.leaf_index
+    );
+
+    // A node account of another node is rejected
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let other_address = context
+        .create_node(&owner.pubkey(), None, node_args("bob"), remaining_accounts)
+        .await
+        .unwrap();
+    let result = context
+        .create_edge_to_node_account(
+            &Pubkey::new_unique(),
+            &node_account,
+            edge_args("carol", &node_id(&other_address)),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::GateTargetMismatch);
+
+    // Tips go to the placeholder and come back with the node
+    context
+        .tip_decompressed_node(&address, 1_000)
+        .await
+        .unwrap();
+    let placeholder = context.compressed_account(&address).compressed_account;
+    assert_eq!(placeholder.lamports, 1_000);
+    assert_eq!(
+        discriminator(&context.compressed_account(&address)),
+        DecompressedNodeV1::discriminator()
+    );
+    context.recompress_node(&address, &owner).await.unwrap();
+    assert_eq!(
+        context
+            .compressed_account(&address)
+            .compressed_account
+            .lamports,
+        1_000
+    );
+}
+
+#[tokio::test]
+async fn test_tip_and_withdraw_from_node() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    let recipient = Pubkey::new_unique();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let address = context
+        .create_node(
+            &owner.pubkey(),
+            None,
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+
+    context.tip_node(&address, 1_000_000).await.unwrap();
+    context.tip_node(&address, 500_000).await.unwrap();
+    assert_eq!(
+        context
+            .compressed_account(&address)
+            .compressed_account
+            .lamports,
+        1_500_000
+    );
+
+    let result = context
+        .withdraw_from_node(&address, &Keypair::new(), &recipient, 1_000_000)
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidAuthority);
+    let result = context
+        .withdraw_from_node(&address, &owner, &recipient, 2_000_000)
+        .await;
+    assert_tapestry_error(result, ZkNftError::InsufficientLamports);
+    context
+        .withdraw_from_node(&address, &owner, &recipient, 1_000_000)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .compressed_account(&address)
+            .compressed_account
+            .lamports,
+        500_000
+    );
+    let recipient_account = context.rpc.get_account(recipient).await.unwrap().unwrap();
+    assert_eq!(recipient_account.lamports, 1_000_000);
+}
+
+#[tokio::test]
+async fn test_migrate_node_v1_to_v2() {
+    let mut context = TestContext::new().await;
+    let data = legacy_account_data("NodeV1").remove(0);
+    let legacy_node: NodeV1 =
+        anchor_lang::AnchorDeserialize::deserialize(&mut data.as_slice()).unwrap();
+    let address = context
+        .seed_compressed_account(AccountKey::NodeV1, &legacy_node)
+        .await;
+
+    let compressed_account = context.compressed_account(&address);
+    assert_eq!(discriminator(&compressed_account), NodeV1::discriminator());
+    assert_eq!(
+        compressed_account
+            .compressed_account
+            .data
+            .as_ref()
+            .unwrap()
+            .data,
+        data
+    );
+
+    context.migrate_node(&address).await.unwrap();
+
+    // The V1 account is spent and replaced at the same address
+    assert_eq!(context.compressed_accounts_at(&address), 1);
+    let compressed_account = context.compressed_account(&address);
+    assert_eq!(discriminator(&compressed_account), NodeV2::discriminator());
+    let node = decode_node(&compressed_account);
+    assert_eq!(node.key, AccountKey::NodeV2);
+    assert_eq!(node.owner, legacy_node.owner);
+    assert_eq!(node.update_authority, legacy_node.update_authority);
+    assert_eq!(node.namespace, "");
+    assert_eq!(node.label, legacy_node.label);
+    assert_eq!(
+        node.node_data.get_properties().unwrap(),
+        legacy_node.node_data.get_properties().unwrap()
+    );
+    assert_eq!(
+        node.node_data.get_creators().unwrap(),
+        legacy_node.node_data.get_creators().unwrap()
+    );
+    assert!(node
+        .node_data
+        .get_encrypted_properties()
+        .unwrap()
+        .is_empty());
+    assert_eq!(node.is_mutable, legacy_node.is_mutable);
+    assert_eq!(node.token_gate, TokenGate::None);
+
+    // Instructions reading V2 nodes accept the migrated one
+    context.tip_node(&address, 1_000).await.unwrap();
+    assert_eq!(
+        context
+            .compressed_account(&address)
+            .compressed_account
+            .lamports,
+        1_000
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_edge_v1_to_v2() {
+    let mut context = TestContext::new().await;
+    // Signs as the update authority recorded in the captured edge
+    let update_authority = keypair_from_seed(&[9; 32]).unwrap();
+    let data = legacy_account_data("EdgeV1").remove(1);
+    let legacy_edge: EdgeV1 =
+        anchor_lang::AnchorDeserialize::deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(
+        legacy_edge.update_authority,
+        NodeUpdateAuthority::Address(update_authority.pubkey())
+    );
+    let address = context
+        .seed_compressed_account(AccountKey::EdgeV1, &legacy_edge)
+        .await;
+
+    let compressed_account = context.compressed_account(&address);
+    assert_eq!(discriminator(&compressed_account), EdgeV1::discriminator());
+    assert_eq!(
+        compressed_account
+            .compressed_account
+            .data
+            .as_ref()
+            .unwrap()
+            .data,
+        data
+    );
+
+    context.migrate_edge(&address).await.unwrap();
+
+    // Edges from before edge types existed migrate untyped and unweighted
+    assert_eq!(context.compressed_accounts_at(&address), 1);
+    let compressed_account = context.compressed_account(&address);
+    assert_eq!(discriminator(&compressed_account), EdgeV2::discriminator());
+    let edge = decode_edge(&compressed_account);
+    assert_eq!(edge.key, AccountKey::EdgeV2);
+    assert_eq!(edge.source_node, legacy_edge.source_node);
+    assert_eq!(edge.target_node, legacy_edge.target_node);
+    assert_eq!(edge.edge_type, "");
+    assert_eq!(edge.namespace, "");
+    assert_eq!(edge.direction, EdgeDirection::Directed);
+    assert_eq!(
+        edge.edge_data.get_properties().unwrap(),
+        legacy_edge.edge_data.get_properties().unwrap()
+    );
+    assert_eq!(edge.edge_data.token_gate, TokenGate::None);
+    assert!(!edge.edge_data.counted);
+    assert_eq!(edge.edge_data.counterparty, None);
+    assert_eq!(edge.edge_data.caller_program, None);
+    assert_eq!(edge.weight, 0);
+    assert_eq!(edge.validity, EdgeValidity::default());
+    assert!(edge.is_mutable);
+    assert_eq!(edge.owner, legacy_edge.owner);
+    assert_eq!(edge.update_authority, legacy_edge.update_authority);
+
+    // Instructions reading V2 edges accept the migrated one
+    context
+        .adjust_edge_weight(&address, &update_authority, 1)
+        .await
+        .unwrap();
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.weight, 1);
+}
+
+#[tokio::test]
+async fn test_migrate_rejects_non_canonical_properties() {
+    let mut context = TestContext::new().await;
+    let data = legacy_account_data("EdgeV1").remove(0);
+    let mut legacy_edge: EdgeV1 =
+        anchor_lang::AnchorDeserialize::deserialize(&mut data.as_slice()).unwrap();
+    // V1 hashed the raw bytes, V2 commits the decoded properties by their root
+    legacy_edge.edge_data.properties_bytes.push(0);
+    let address = context
+        .seed_compressed_account(AccountKey::EdgeV1, &legacy_edge)
+        .await;
+
+    let result = context.migrate_edge(&address).await;
+    assert_tapestry_error(result, ZkNftError::InvalidAccountData);
+    assert_eq!(
+        discriminator(&context.compressed_account(&address)),
+        EdgeV1::discriminator()
+    );
+}
+
+#[tokio::test]
+async fn test_create_node_with_label_seed() {
+    let mut context = TestContext::new().await;
+    let remaining_accounts = context.new_address_remaining_accounts();
+
+    let address = context
+        .create_node_with_seed(
+            &Pubkey::new_unique(),
+            None,
+            AddressSeed::Label,
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        address,
+        context.new_address(&get_label_seed("social", "alice"))
+    );
+}
+
+#[tokio::test]
+async fn test_create_node_with_external_id_seed_collides() {
+    let mut context = TestContext::new().await;
+    let address_seed = AddressSeed::ExternalId("user-1".to_string());
+    let address = context.new_address(&get_external_id_seed(
+        AccountKey::NodeV1,
+        "social",
+        "user-1",
+    ));
+    let address_proof = context.new_address_proof(address).await;
+
+    let remaining_accounts = context.new_address_remaining_accounts();
+    context
+        .create_node_with_proof(
+            &Pubkey::new_unique(),
+            None,
+            address_seed.clone(),
+            node_args("alice"),
+            remaining_accounts,
+            address_proof.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        decode_node(&context.compressed_account(&address)).label,
+        "alice"
+    );
+
+    // The label isn't part of the seed, so another node with the same external
+    // id lands on the same address
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let result = context
+        .create_node_with_proof(
+            &Pubkey::new_unique(),
+            None,
+            address_seed,
+            node_args("bob"),
+            remaining_accounts,
+            address_proof,
+        )
+        .await;
+    assert!(result.is_err());
+    assert_eq!(
+        decode_node(&context.compressed_account(&address)).label,
+        "alice"
+    );
+}
+
+#[tokio::test]
+async fn test_create_edge_with_edge_pair_seed_collides() {
+    let mut context = TestContext::new().await;
+    let address = context.new_address(&get_edge_pair_seed("social", "alice", "bob", "follow"));
+    let address_proof = context.new_address_proof(address).await;
+
+    context
+        .create_edge_with_proof(
+            &Pubkey::new_unique(),
+            None,
+            AddressSeed::EdgePair,
+            edge_args("alice", "bob"),
+            address_proof.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        decode_edge(&context.compressed_account(&address)).weight,
+        10
+    );
+
+    // The weight isn't part of the seed, a second edge between the same nodes
+    // can't be created
+    let mut reweighted_edge_args = edge_args("alice", "bob");
+    reweighted_edge_args.weight = 11;
+    let result = context
+        .create_edge_with_proof(
+            &Pubkey::new_unique(),
+            None,
+            AddressSeed::EdgePair,
+            reweighted_edge_args,
+            address_proof,
+        )
+        .await;
+    assert!(result.is_err());
+    assert_eq!(
+        decode_edge(&context.compressed_account(&address)).weight,
+        10
+    );
+
+    // Another edge type between the same nodes is a different edge
+    let mut like_edge_args = edge_args("alice", "bob");
+    like_edge_args.edge_type = "like".to_string();
+    let like_address = context
+        .create_edge_with_seed(
+            &Pubkey::new_unique(),
+            None,
+            AddressSeed::EdgePair,
+            like_edge_args,
+        )
+        .await
+        .unwrap();
+    assert_ne!(like_address, address);
+}
+
+#[tokio::test]
+async fn test_create_edge_with_external_id_seed_collides() {
+    let mut context = TestContext::new().await;
+    let address_seed = AddressSeed::ExternalId("follow-1".to_string());
+    let address = context.new_address(&get_external_id_seed(
+        AccountKey::EdgeV1,
+        "social",
+        "follow-1",
+    ));
+    let address_proof = context.new_address_proof(address).await;
+
+    context
+        .create_edge_with_proof(
+            &Pubkey::new_unique(),
+            None,
+            address_seed.clone(),
+            edge_args("alice", "bob"),
+            address_proof.clone(),
+        )
+        .await
+        .unwrap();
+
+    // Only the external id counts, not the endpoints
+    let result = context
+        .create_edge_with_proof(
+            &Pubkey::new_unique(),
+            None,
+            address_seed,
+            edge_args("carol", "dave"),
+            address_proof,
+        )
+        .await;
+    assert!(result.is_err());
+    assert_eq!(
+        decode_edge(&context.compressed_account(&address)).source_node,
+        "alice"
+    );
+}
+
+#[tokio::test]
+async fn test_create_with_invalid_address_seed() {
+    let mut context = TestContext::new().await;
+
+    // Nodes have no endpoints to derive an edge pair from
+    let address = context.new_address(&Pubkey::new_unique().to_bytes());
+    let address_proof = context.new_address_proof(address).await;
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let result = context
+        .create_node_with_proof(
+            &Pubkey::new_unique(),
+            None,
+            AddressSeed::EdgePair,
+            node_args("alice"),
+            remaining_accounts,
+            address_proof,
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidAddressSeed);
+
+    // Edges have no label
+    let address = context.new_address(&Pubkey::new_unique().to_bytes());
+    let address_proof = context.new_address_proof(address).await;
+    let result = context
+        .create_edge_with_proof(
+            &Pubkey::new_unique(),
+            None,
+            AddressSeed::Label,
+            edge_args("alice", "bob"),
+            address_proof,
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidAddressSeed);
+}
+
+#[tokio::test]
+async fn test_create_node_signed() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    context
+        .initialize_action_nonce(&owner.pubkey())
+        .await
+        .unwrap();
+
+    // The relayer pays, the node belongs to the signer
+    let address = context
+        .create_node_signed(
+            &owner.pubkey(),
+            &owner,
+            node_args("alice"),
+            signed_action(0),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        decode_node(&context.compressed_account(&address)).owner,
+        owner.pubkey()
+    );
+    assert_eq!(context.action_nonce(&owner.pubkey()).await, 1);
+}
+
+#[tokio::test]
+async fn test_create_node_signed_reused_nonce() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    context
+        .initialize_action_nonce(&owner.pubkey())
+        .await
+        .unwrap();
+    context
+        .create_node_signed(
+            &owner.pubkey(),
+            &owner,
+            node_args("alice"),
+            signed_action(0),
+            None,
+        )
+        .await
+        .unwrap();
+
+    // Replaying the signed action is rejected, and so is skipping a nonce
+    let result = context
+        .create_node_signed(
+            &owner.pubkey(),
+            &owner,
+            node_args("alice"),
+            signed_action(0),
+            None,
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidActionNonce);
+    let result = context
+        .create_node_signed(
+            &owner.pubkey(),
+            &owner,
+            node_args("alice"),
+            signed_action(2),
+            None,
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidActionNonce);
+    assert_eq!(context.action_nonce(&owner.pubkey()).await, 1);
+}
+
+#[tokio::test]
+async fn test_create_node_signed_expired() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    context
+        .initialize_action_nonce(&owner.pubkey())
+        .await
+        .unwrap();
+
+    let expired = SignedAction {
+        nonce: 0,
+        expires_at: 0,
+    };
+    let result = context
+        .create_node_signed(&owner.pubkey(), &owner, node_args("alice"), expired, None)
+        .await;
+    assert_tapestry_error(result, ZkNftError::SignedActionExpired);
+    assert_eq!(context.action_nonce(&owner.pubkey()).await, 0);
+}
+
+#[tokio::test]
+async fn test_create_node_signed_wrong_signer() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    context
+        .initialize_action_nonce(&owner.pubkey())
+        .await
+        .unwrap();
+
+    let result = context
+        .create_node_signed(
+            &owner.pubkey(),
+            &Keypair::new(),
+            node_args("alice"),
+            signed_action(0),
+            None,
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidEd25519Signature);
+}
+
+#[tokio::test]
+async fn test_create_node_signed_relayer_changed_args() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    context
+        .initialize_action_nonce(&owner.pubkey())
+        .await
+        .unwrap();
+
+    let relayed_node_args = NodeArgs {
+        is_mutable: false,
+        ..node_args("alice")
+    };
+    let result = context
+        .create_node_signed(
+            &owner.pubkey(),
+            &owner,
+            node_args("alice"),
+            signed_action(0),
+            Some(relayed_node_args),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidEd25519Signature);
+}
+
+#[tokio::test]
+async fn test_create_edge_signed() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    context
+        .initialize_action_nonce(&owner.pubkey())
+        .await
+        .unwrap();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let target = context
+        .create_node(
+            &Pubkey::new_unique(),
+            None,
+            node_args("bob"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+
+    let address = context
+        .create_edge_signed(
+            &owner.pubkey(),
+            &owner,
+            Some(&target),
+            edge_args("alice", &node_id(&target)),
+            signed_action(0),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.owner, owner.pubkey());
+    assert_eq!(edge.target_node, node_id(&target));
+    assert_eq!(context.action_nonce(&owner.pubkey()).await, 1);
+
+    // Without the target the edge records no gate
+    let address = context
+        .create_edge_signed(
+            &owner.pubkey(),
+            &owner,
+            None,
+            edge_args("carol", &node_id(&target)),
+            signed_action(1),
+            None,
+        )
+        .await
+        .unwrap();
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.edge_data.token_gate, TokenGate::None);
+}
+
+#[tokio::test]
+async fn test_create_edge_signed_relayer_changed_args() {
+    let mut context = TestContext::new().await;
+    let owner = Keypair::new();
+    context
+        .initialize_action_nonce(&owner.pubkey())
+        .await
+        .unwrap();
+
+    let relayed_edge_args = EdgeArgs {
+        weight: 1_000,
+        ..edge_args("alice", "bob")
+    };
+    let result = context
+        .create_edge_signed(
+            &owner.pubkey(),
+            &owner,
+            None,
+            edge_args("alice", "bob"),
+            signed_action(0),
+            Some(relayed_edge_args),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidEd25519Signature);
+}
+
+#[tokio::test]
+async fn test_adjust_edge_weight_signed() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    context
+        .initialize_action_nonce(&update_authority.pubkey())
+        .await
+        .unwrap();
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    context
+        .adjust_edge_weight_signed(
+            &address,
+            &update_authority.pubkey(),
+            &update_authority,
+            -3,
+            signed_action(0),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.weight, 7);
+
+    // Replaying the signed delta is rejected
+    let result = context
+        .adjust_edge_weight_signed(
+            &address,
+            &update_authority.pubkey(),
+            &update_authority,
+            -3,
+            signed_action(0),
+            None,
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidActionNonce);
+}
+
+#[tokio::test]
+async fn test_adjust_edge_weight_signed_wrong_signer() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    let other = Keypair::new();
+    context
+        .initialize_action_nonce(&update_authority.pubkey())
+        .await
+        .unwrap();
+    context
+        .initialize_action_nonce(&other.pubkey())
+        .await
+        .unwrap();
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    // A signature by someone else for the update authority
+    let result = context
+        .adjust_edge_weight_signed(
+            &address,
+            &update_authority.pubkey(),
+            &other,
+            1,
+            signed_action(0),
+            None,
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidEd25519Signature);
+    // A valid signature by someone who is not the update authority
+    let result = context
+        .adjust_edge_weight_signed(&address, &other.pubkey(), &other, 1, signed_action(0), None)
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidAuthority);
+}
+
+#[tokio::test]
+async fn test_adjust_edge_weight_signed_relayer_changed_delta() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    context
+        .initialize_action_nonce(&update_authority.pubkey())
+        .await
+        .unwrap();
+    let address = context
+        .create_edge(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            edge_args("alice", "bob"),
+        )
+        .await
+        .unwrap();
+
+    let result = context
+        .adjust_edge_weight_signed(
+            &address,
+            &update_authority.pubkey(),
+            &update_authority,
+            1,
+            signed_action(0),
+            Some(-10),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidEd25519Signature);
+}
+
+#[tokio::test]
+async fn test_set_node_update_authority_signed() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    let new_update_authority = NodeUpdateAuthority::Address(Pubkey::new_unique());
+    context
+        .initialize_action_nonce(&update_authority.pubkey())
+        .await
+        .unwrap();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let address = context
+        .create_node(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+
+    // The relayer cannot swap in its own authority
+    let result = context
+        .set_node_update_authority_signed(
+            &address,
+            &update_authority.pubkey(),
+            &update_authority,
+            new_update_authority.clone(),
+            signed_action(0),
+            Some(NodeUpdateAuthority::Address(context.payer.pubkey())),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidEd25519Signature);
+
+    context
+        .set_node_update_authority_signed(
+            &address,
+            &update_authority.pubkey(),
+            &update_authority,
+            new_update_authority.clone(),
+            signed_action(0),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let node = decode_node(&context.compressed_account(&address));
+    assert_eq!(node.update_authority, new_update_authority);
+    assert_eq!(context.action_nonce(&update_authority.pubkey()).await, 1);
+}
+
+#[tokio::test]
+async fn test_make_node_immutable_signed() {
+    let mut context = TestContext::new().await;
+    let update_authority = Keypair::new();
+    context
+        .initialize_action_nonce(&update_authority.pubkey())
+        .await
+        .unwrap();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let address = context
+        .create_node(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            node_args("alice"),
+            remaining_accounts.clone(),
+        )
+        .await
+        .unwrap();
+    let other_address = context
+        .create_node(
+            &Pubkey::new_unique(),
+            Some(&update_authority),
+            node_args("bob"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+
+    // A signature for another node of the same authority is rejected
+    let result = context
+        .make_node_immutable_signed(
+            &address,
+            &update_authority.pubkey(),
+            &update_authority,
+            &other_address,
+            signed_action(0),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidEd25519Signature);
+
+    context
+        .make_node_immutable_signed(
+            &address,
+            &update_authority.pubkey(),
+            &update_authority,
+            &address,
+            signed_action(0),
+        )
+        .await
+        .unwrap();
+
+    let node = decode_node(&context.compressed_account(&address));
+    assert!(!node.is_mutable);
+    assert!(decode_node(&context.compressed_account(&other_address)).is_mutable);
+    let result = context
+        .make_node_immutable_signed(
+            &address,
+            &update_authority.pubkey(),
+            &update_authority,
+            &address,
+            signed_action(1),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::NodeNotMutable);
+}
+
+#[tokio::test]
+async fn test_create_undirected_edge() {
+    let mut context = TestContext::new().await;
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let alice_node = context
+        .create_node(
+            &alice.pubkey(),
+            None,
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let bob_node = context
+        .create_node(&bob.pubkey(), None, node_args("bob"), remaining_accounts)
+        .await
+        .unwrap();
+    let alice_id = node_id(&alice_node);
+    let bob_id = node_id(&bob_node);
+
+    // Each endpoint must be signed for by its own owner
+    let result = context
+        .create_undirected_edge(
+            &alice,
+            &Keypair::new(),
+            &alice_node,
+            &bob_node,
+            edge_args(&alice_id, &bob_id),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidAuthority);
+    let result = context
+        .create_undirected_edge(
+            &alice,
+            &bob,
+            &bob_node,
+            &alice_node,
+            edge_args(&alice_id, &bob_id),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::EndpointMismatch);
+
+    let address = context
+        .create_undirected_edge(
+            &alice,
+            &bob,
+            &alice_node,
+            &bob_node,
+            edge_args(&alice_id, &bob_id),
+        )
+        .await
+        .unwrap();
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.owner, alice.pubkey());
+    assert_eq!(edge.edge_data.counterparty, Some(bob.pubkey()));
+}
+
+// Creates nodes owned by `alice` and `bob`, the first one gated by
+// `source_gate`
+async fn setup_undirected_nodes(
+    context: &mut TestContext,
+    alice: &Keypair,
+    bob: &Keypair,
+    source_gate: TokenGate,
+) -> ([u8; 32], [u8; 32]) {
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let mut alice_node_args = node_args("alice");
+    alice_node_args.token_gate = source_gate;
+    let alice_node = context
+        .create_node(&alice.pubkey(), None, alice_node_args, remaining_accounts)
+        .await
+        .unwrap();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let bob_node = context
+        .create_node(&bob.pubkey(), None, node_args("bob"), remaining_accounts)
+        .await
+        .unwrap();
+    (alice_node, bob_node)
+}
+
+#[tokio::test]
+async fn test_create_undirected_edge_collides() {
+    let mut context = TestContext::new().await;
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let (alice_node, bob_node) =
+        setup_undirected_nodes(&mut context, &alice, &bob, TokenGate::None).await;
+    let alice_node_account = context
+        .decompress_node(&alice_node, &alice, None)
+        .await
+        .unwrap();
+    let bob_node_account = context
+        .decompress_node(&bob_node, &bob, None)
+        .await
+        .unwrap();
+    let alice_id = node_id(&alice_node);
+    let bob_id = node_id(&bob_node);
+
+    let address = context.new_address(&get_undirected_edge_seed(
+        "social", &alice_id, &bob_id, "follow",
+    ));
+    let address_proof = context.new_address_proof(address).await;
+    context
+        .create_undirected_edge_between_node_accounts(
+            &alice,
+            &bob,
+            [alice_node_account, bob_node_account],
+            edge_args(&alice_id, &bob_id),
+            address_proof.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        decode_edge(&context.compressed_account(&address)).owner,
+        alice.pubkey()
+    );
+
+    // The reversed pair maps to the same address, so it can't be created twice
+    let result = context
+        .create_undirected_edge_between_node_accounts(
+            &bob,
+            &alice,
+            [bob_node_account, alice_node_account],
+            edge_args(&bob_id, &alice_id),
+            address_proof,
+        )
+        .await;
+    assert!(result.is_err());
+    assert_eq!(
+        decode_edge(&context.compressed_account(&address)).owner,
+        alice.pubkey()
+    );
+
+    // In another namespace the same pair is another edge
+    let mut other_edge_args = edge_args(&bob_id, &alice_id);
+    other_edge_args.namespace = "work".to_string();
+    let other_address = context.new_address(&get_undirected_edge_seed(
+        "work", &alice_id, &bob_id, "follow",
+    ));
+    assert_ne!(other_address, address);
+    let address_proof = context.new_address_proof(other_address).await;
+    context
+        .create_undirected_edge_between_node_accounts(
+            &bob,
+            &alice,
+            [bob_node_account, alice_node_account],
+            other_edge_args,
+            address_proof,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        decode_edge(&context.compressed_account(&other_address)).owner,
+        bob.pubkey()
+    );
+}
+
+#[tokio::test]
+async fn test_create_undirected_edge_source_gate() {
+    let mut context = TestContext::new().await;
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let (alice_node, bob_node) = setup_undirected_nodes(
+        &mut context,
+        &alice,
+        &bob,
+        TokenGate::Mint {
+            mint,
+            min_amount: 1,
+        },
+    )
+    .await;
+    let edge_args = edge_args(&node_id(&alice_node), &node_id(&bob_node));
+
+    // The counterparty connects to the source node, so it has to hold the
+    // source's gate, the owner's holding doesn't count
+    let owner_token_account = context.set_token_account(&alice.pubkey(), &mint, 1);
+    let result = context
+        .create_undirected_edge_with_gates(
+            &alice,
+            &bob,
+            [&alice_node, &bob_node],
+            edge_args.clone(),
+            (None, Some(owner_token_account)),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::TokenGateNotSatisfied);
+
+    let counterparty_token_account = context.set_token_account(&bob.pubkey(), &mint, 1);
+    let address = context
+        .create_undirected_edge_with_gates(
+            &alice,
+            &bob,
+            [&alice_node, &bob_node],
+            edge_args,
+            (None, Some(counterparty_token_account)),
+        )
+        .await
+        .unwrap();
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.edge_data.counterparty, Some(bob.pubkey()));
+}
+
+// Creates nodes owned by `requester` and `target_owner` and requests an edge
+// between them
+async fn setup_edge_request(
+    context: &mut TestContext,
+    requester: &Keypair,
+    target_owner: &Keypair,
+) -> [u8; 32] {
+    let (source_node, target_node) = setup_request_nodes(context, requester, target_owner).await;
+    let address = context
+        .request_edge(
+            requester,
+            &source_node,
+            &target_node,
+            edge_args(&node_id(&source_node), &node_id(&target_node)),
+        )
+        .await
+        .unwrap();
+
+    // The approver is taken from the proven target node, not from the requester
+    let pending_edge = decode_pending_edge(&context.compressed_account(&address));
+    assert_eq!(pending_edge.owner, requester.pubkey());
+    assert_eq!(pending_edge.target_owner, target_owner.pubkey());
+    address
+}
+
+// Creates nodes owned by `requester` and `target_owner`, returning their
+// addresses
+async fn setup_request_nodes(
+    context: &mut TestContext,
+    requester: &Keypair,
+    target_owner: &Keypair,
+) -> ([u8; 32], [u8; 32]) {
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let source_node = context
+        .create_node(
+            &requester.pubkey(),
+            None,
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let target_node = context
+        .create_node(
+            &target_owner.pubkey(),
+            None,
+            node_args("bob"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    (source_node, target_node)
+}
+
+#[tokio::test]
+async fn test_request_edge_target_mismatch() {
+    let mut context = TestContext::new().await;
+    let requester = Keypair::new();
+    let target_owner = Keypair::new();
+    let (source_node, target_node) =
+        setup_request_nodes(&mut context, &requester, &target_owner).await;
+
+    // Proving a different node than the edge targets is rejected
+    let result = context
+        .request_edge(
+            &requester,
+            &source_node,
+            &target_node,
+            edge_args(&node_id(&source_node), "bob"),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::EndpointMismatch);
+}
+
+#[tokio::test]
+async fn test_request_edge_requires_source_owner() {
+    let mut context = TestContext::new().await;
+    let requester = Keypair::new();
+    let target_owner = Keypair::new();
+    let (source_node, target_node) =
+        setup_request_nodes(&mut context, &requester, &target_owner).await;
+
+    // Someone else cannot request an edge from the requester's node
+    let result = context
+        .request_edge(
+            &Keypair::new(),
+            &source_node,
+            &target_node,
+            edge_args(&node_id(&source_node), &node_id(&target_node)),
+        )
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidAuthority);
+
+    // Nor name the requester as owner without their signature
+    let (mut ix, _) = context
+        .request_edge_instruction(
+            &requester.pubkey(),
+            &source_node,
+            &target_node,
+            None,
+            edge_args(&node_id(&source_node), &node_id(&target_node)),
+        )
+        .await;
+    for account in ix
+        .accounts
+        .iter_mut()
+        .filter(|a| a.pubkey == requester.pubkey())
+    {
+        account.is_signer = false;
+    }
+    let result = context.send(ix, &[]).await;
+    assert_error_code(
+        result,
+        anchor_lang::error::ErrorCode::AccountNotSigner.into(),
+    );
+}
+
+#[tokio::test]
+async fn test_accept_edge() {
+    let mut context = TestContext::new().await;
+    let requester = Keypair::new();
+    let target_owner = Keypair::new();
+    let address = setup_edge_request(&mut context, &requester, &target_owner).await;
+
+    // The requester cannot approve their own request
+    let result = context.accept_edge(&address, &requester).await;
+    assert_tapestry_error(result, ZkNftError::InvalidAuthority);
+
+    context.accept_edge(&address, &target_owner).await.unwrap();
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.key, AccountKey::EdgeV2);
+    assert_eq!(edge.owner, requester.pubkey());
+}
+
+#[tokio::test]
+async fn test_accept_gated_edge_request() {
+    let mut context = TestContext::new().await;
+    let requester = Keypair::new();
+    let target_owner = Keypair::new();
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let source_node = context
+        .create_node(
+            &requester.pubkey(),
+            None,
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+    let mint = Pubkey::new_unique();
+    let token_gate = TokenGate::Mint {
+        mint,
+        min_amount: 1,
+    };
+    let mut gated_node_args = node_args("club");
+    gated_node_args.token_gate = token_gate;
+    let remaining_accounts = context.new_address_remaining_accounts();
+    let target_node = context
+        .create_node(
+            &target_owner.pubkey(),
+            None,
+            gated_node_args,
+            remaining_accounts,
+        )
+        .await
+        .unwrap();
+
+    let token_account = context.set_token_account(&requester.pubkey(), &mint, 1);
+    let (ix, address) = context
+        .request_edge_instruction(
+            &requester.pubkey(),
+            &source_node,
+            &target_node,
+            Some(token_account),
+            edge_args(&node_id(&source_node), &node_id(&target_node)),
+        )
+        .await;
+    context.send(ix, &[&requester]).await.unwrap();
+    let pending_edge = decode_pending_edge(&context.compressed_account(&address));
+    assert_eq!(pending_edge.edge_data.token_gate, token_gate);
+
+    // The gate satisfied by the request is kept on the accepted edge
+    context.accept_edge(&address, &target_owner).await.unwrap();
+    let edge = decode_edge(&context.compressed_account(&address));
+    assert_eq!(edge.edge_data.token_gate, token_gate);
+}
+
+#[tokio::test]
+async fn test_reject_edge() {
+    let mut context = TestContext::new().await;
+    let requester = Keypair::new();
+    let target_owner = Keypair::new();
+    let address = setup_edge_request(&mut context, &requester, &target_owner).await;
+
+    let result = context.reject_edge(&address, &requester).await;
+    assert_tapestry_error(result, ZkNftError::InvalidAuthority);
+
+    context.reject_edge(&address, &target_owner).await.unwrap();
+    assert!(!context.has_compressed_account(&address));
+}
+
+#[tokio::test]
+async fn test_cancel_edge_request() {
+    let mut context = TestContext::new().await;
+    let requester = Keypair::new();
+    let target_owner = Keypair::new();
+    let address = setup_edge_request(&mut context, &requester, &target_owner).await;
+
+    // Only the requester can withdraw the request
+    let result = context.cancel_edge_request(&address, &target_owner).await;
+    assert_tapestry_error(result, ZkNftError::InvalidAuthority);
+
+    context
+        .cancel_edge_request(&address, &requester)
+        .await
+        .unwrap();
+    assert!(!context.has_compressed_account(&address));
+}
+
+#[tokio::test]
+async fn test_initialize_config_not_upgrade_authority() {
+    let mut context = TestContext::new().await;
+    let upgrade_authority = Keypair::new();
+    let program_data = context.make_upgradeable(&upgrade_authority.pubkey()).await;
+
+    // Anyone but the upgrade authority is rejected, so the config can't be
+    // front-run after deployment
+    let result = context
+        .initialize_config(&Keypair::new(), &program_data, &Pubkey::new_unique())
+        .await;
+    assert_tapestry_error(result, ZkNftError::NotUpgradeAuthority);
+
+    let attestation_authority = Pubkey::new_unique();
+    context
+        .initialize_config(&upgrade_authority, &program_data, &attestation_authority)
+        .await
+        .unwrap();
+    let config: TapestryConfig = context
+        .rpc
+        .get_anchor_account(&config_pda())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(config.admin, upgrade_authority.pubkey());
+    assert_eq!(config.attestation_authority, attestation_authority);
+}
+
+#[tokio::test]
+async fn test_set_attestation_authority() {
+    let mut context = TestContext::new().await;
+    let admin = Keypair::new();
+    let program_data = context.make_upgradeable(&admin.pubkey()).await;
+    context
+        .initialize_config(&admin, &program_data, &Pubkey::new_unique())
+        .await
+        .unwrap();
+
+    let new_authority = Pubkey::new_unique();
+    let result = context
+        .set_attestation_authority(&Keypair::new(), &new_authority)
+        .await;
+    assert_error_code(
+        result,
+        anchor_lang::error::ErrorCode::ConstraintHasOne.into(),
+    );
+
+    context
+        .set_attestation_authority(&admin, &new_authority)
+        .await
+        .unwrap();
+    let config: TapestryConfig = context
+        .rpc
+        .get_anchor_account(&config_pda())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(config.attestation_authority, new_authority);
+}
+
+async fn create_linked_node(context: &mut TestContext) -> [u8; 32] {
+    let remaining_accounts = context.new_address_remaining_accounts();
+    context
+        .create_node(
+            &Pubkey::new_unique(),
+            None,
+            node_args("alice"),
+            remaining_accounts,
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_create_identity_link() {
+    let mut context = TestContext::new().await;
+    let attestation_authority = context.setup_config().await;
+    let node_address = create_linked_node(&mut context).await;
+    let node = node_id(&node_address);
+    let identity = ExternalIdentity::new(IdentityProvider::Twitter, "alice");
+
+    let attestation = attestation_instruction(&attestation_authority, &node, &identity);
+    let address = context
+        .create_identity_link(&node_address, identity, vec![attestation])
+        .await
+        .unwrap();
+    let identity_link = decode_identity_link(&context.compressed_account(&address));
+    assert_eq!(identity_link.node, node);
+    assert_eq!(identity_link.identity, identity);
+    assert_eq!(
+        identity_link.attestation_authority,
+        attestation_authority.pubkey()
+    );
+}
+
+#[tokio::test]
+async fn test_create_identity_link_wrong_signer() {
+    let mut context = TestContext::new().await;
+    context.setup_config().await;
+    let node_address = create_linked_node(&mut context).await;
+    let node = node_id(&node_address);
+    let identity = ExternalIdentity::new(IdentityProvider::Twitter, "alice");
+
+    let attestation = attestation_instruction(&Keypair::new(), &node, &identity);
+    let result = context
+        .create_identity_link(&node_address, identity, vec![attestation])
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidEd25519Signature);
+}
+
+#[tokio::test]
+async fn test_create_identity_link_tampered_message() {
+    let mut context = TestContext::new().await;
+    let attestation_authority = context.setup_config().await;
+    let node_address = create_linked_node(&mut context).await;
+    let node = node_id(&node_address);
+    let identity = ExternalIdentity::new(IdentityProvider::Twitter, "alice");
+
+    // An attestation for another node can't be used to link this one
+    let attestation = attestation_instruction(
+        &attestation_authority,
+        &Pubkey::new_unique().to_string(),
+        &identity,
+    );
+    let result = context
+        .create_identity_link(&node_address, identity, vec![attestation])
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidEd25519Signature);
+}
+
+#[tokio::test]
+async fn test_create_identity_link_missing_signature() {
+    let mut context = TestContext::new().await;
+    let attestation_authority = context.setup_config().await;
+    let node_address = create_linked_node(&mut context).await;
+    let node = node_id(&node_address);
+    let identity = ExternalIdentity::new(IdentityProvider::Twitter, "alice");
+
+    let result = context
+        .create_identity_link(&node_address, identity, vec![])
+        .await;
+    assert_tapestry_error(result, ZkNftError::MissingEd25519Signature);
+
+    // The attestation has to come right before the instruction
+    let attestation = attestation_instruction(&attestation_authority, &node, &identity);
+    let unrelated =
+        solana_sdk::system_instruction::transfer(&context.payer.pubkey(), &Pubkey::new_unique(), 0);
+    let result = context
+        .create_identity_link(&node_address, identity, vec![attestation, unrelated])
+        .await;
+    assert_tapestry_error(result, ZkNftError::MissingEd25519Signature);
+}
+
+#[tokio::test]
+async fn test_create_identity_link_offsets_to_other_instruction() {
+    let mut context = TestContext::new().await;
+    let attestation_authority = context.setup_config().await;
+    let node_address = create_linked_node(&mut context).await;
+    let node = node_id(&node_address);
+    let identity = ExternalIdentity::new(IdentityProvider::Twitter, "alice");
+
+    // The second signature instruction verifies by pointing all of its offsets
+    // at the first one (index 1, after the compute budget instruction), which
+    // the program must not accept as a signature of its own
+    let attestation = attestation_instruction(&attestation_authority, &node, &identity);
+    let mut redirected = attestation.clone();
+    for offset_index in [1, 3, 6] {
+        let start = 2 + 2 * offset_index;
+        redirected.data[start..start + 2].copy_from_slice(&1u16.to_le_bytes());
+    }
+    let result = context
+        .create_identity_link(&node_address, identity, vec![attestation, redirected])
+        .await;
+    assert_tapestry_error(result, ZkNftError::InvalidEd25519Signature);
+}
+
+#[tokio::test]
+async fn test_revoke_identity_link() {
+    let mut context = TestContext::new().await;
+    let attestation_authority = context.setup_config().await;
+    let node_address = create_linked_node(&mut context).await;
+    let node = node_id(&node_address);
+    let identity = ExternalIdentity::new(IdentityProvider::Twitter, "alice");
+    let attestation = attestation_instruction(&attestation_authority, &node, &identity);
+    let address = context
+        .create_identity_link(&node_address, identity, vec![attestation])
+        .await
+        .unwrap();
+
+    // Only the attestation authority may revoke a link
+    let result = context
+        .revoke_identity_link(&address, &Keypair::new())
+        .await;
+    assert_error_code(
+        result,
+        anchor_lang::error::ErrorCode::ConstraintHasOne.into(),
+    );
+
+    context
+        .revoke_identity_link(&address, &attestation_authority)
+        .await
+        .unwrap();
+    assert!(!context.has_compressed_account(&address));
+}
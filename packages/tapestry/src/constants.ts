@@ -0,0 +1,13 @@
+export const PROGRAM_ID = "GraphUyqhPmEAckWzi7zAvbvUTXf8kqX7JtuvdGYRDRh";
+
+// Discriminant of `AccountKey`, the first byte of every account
+export enum AccountKey {
+  NodeV1 = 0,
+  EdgeV1 = 1,
+  PendingEdgeV1 = 2,
+  NodeV2 = 3,
+  EdgeV2 = 4,
+  NodeCounterV1 = 5,
+  DecompressedNodeV1 = 6,
+  IdentityLinkV1 = 7,
+}
@@ -0,0 +1,209 @@
+import { BN } from "@coral-xyz/anchor";
+import { keccak_256 } from "@noble/hashes/sha3";
+import { PublicKey } from "@solana/web3.js";
+import * as borsh from "borsh";
+import { AccountKey, PROGRAM_ID } from "./constants";
+import {
+  edgeValiditySchema,
+  propertiesSchema,
+  tokenGateSchema,
+  updateAuthoritySchema,
+} from "./schemas";
+
+// Ports of the program's seed derivations and `NodeV2`/`EdgeV2` data hashes,
+// see `utils/get_account_seed.rs`, `utils/property_tree.rs` and the
+// `AsByteVec` impls in `state/`. Kept byte-identical by the golden vectors in
+// programs/tapestry/tests/fixtures/hash_vectors.json.
+
+// Poseidon over BN254, e.g. `await WasmFactory.getInstance()` from
+// @lightprotocol/hasher.rs
+export interface PoseidonHasher {
+  poseidonHash(inputs: BN[]): Uint8Array;
+}
+
+export interface Properties {
+  key: string;
+  value: string;
+}
+
+// `hashv_to_bn254_field_size_be`: keccak with the first byte zeroed
+export const hashvToBn254FieldSizeBe = (bytes: Uint8Array[]) => {
+  const hash = keccak_256(Buffer.concat(bytes));
+  hash[0] = 0;
+  return hash;
+};
+
+// `hash_to_bn254_field_size_be`: like `hashvToBn254FieldSizeBe` with a 0xff
+// bump seed appended. Used for `#[truncate]` fields and the custom `AsByteVec`
+// impls, which hash the borsh encoding.
+export const hashToBn254FieldSizeBe = (bytes: Uint8Array) =>
+  hashvToBn254FieldSizeBe([bytes, Uint8Array.from([0xff])]);
+
+const utf8 = (value: string) => Buffer.from(value, "utf8");
+const strings = (...values: string[]) =>
+  Buffer.concat(values.map((value) => borsh.serialize("string", value)));
+const pubkeyOptionSchema: borsh.Schema = {
+  option: { array: { type: "u8", len: 32 } },
+};
+
+// Seed of addresses derived from account contents, out of reach of the
+// random `asset_id` seeds
+export function getDerivedAccountSeed(
+  accountKey: AccountKey,
+  derivedHash: Uint8Array,
+  programId = new PublicKey(PROGRAM_ID)
+): Uint8Array {
+  return hashvToBn254FieldSizeBe([
+    programId.toBytes(),
+    Uint8Array.from([accountKey]),
+    utf8("derived"),
+    derivedHash,
+  ]);
+}
+
+export function getLabelSeed(namespace: string, label: string): Uint8Array {
+  const labelHash = hashvToBn254FieldSizeBe([
+    strings("label", namespace, label),
+  ]);
+  return getDerivedAccountSeed(AccountKey.NodeV1, labelHash);
+}
+
+export function getEdgePairSeed(
+  namespace: string,
+  sourceNode: string,
+  targetNode: string,
+  edgeType: string
+): Uint8Array {
+  const pairHash = hashvToBn254FieldSizeBe([
+    strings("edge_pair", namespace, sourceNode, targetNode, edgeType),
+  ]);
+  return getDerivedAccountSeed(AccountKey.EdgeV1, pairHash);
+}
+
+export function getExternalIdSeed(
+  accountKey: AccountKey,
+  namespace: string,
+  externalId: string
+): Uint8Array {
+  const externalIdHash = hashvToBn254FieldSizeBe([
+    strings("external_id", namespace, externalId),
+  ]);
+  return getDerivedAccountSeed(accountKey, externalIdHash);
+}
+
+// Endpoints are sorted by their UTF-8 bytes like Rust `str` ordering, so both
+// sides derive the same address
+export function getUndirectedEdgeSeed(
+  namespace: string,
+  nodeA: string,
+  nodeB: string,
+  edgeType: string
+): Uint8Array {
+  const [first, second] =
+    Buffer.compare(utf8(nodeA), utf8(nodeB)) <= 0
+      ? [nodeA, nodeB]
+      : [nodeB, nodeA];
+  const pairHash = hashvToBn254FieldSizeBe([
+    strings("undirected_edge", namespace, first, second, edgeType),
+  ]);
+  return getDerivedAccountSeed(AccountKey.EdgeV1, pairHash);
+}
+
+// Decodes `properties_bytes` like the program's `try_from_slice`: trailing or
+// non-canonical bytes are rejected instead of hashed
+export function decodeProperties(bytes: Uint8Array): Properties[] {
+  const schema: borsh.Schema = { array: { type: propertiesSchema } };
+  const properties = borsh.deserialize(schema, bytes) as Properties[];
+  const canonical = borsh.serialize(schema, properties);
+  if (Buffer.compare(Buffer.from(canonical), Buffer.from(bytes)) !== 0) {
+    throw new Error("Properties bytes are not canonical");
+  }
+  return properties;
+}
+
+const PROPERTY_TREE_DEPTH = 4;
+
+const poseidon = (hasher: PoseidonHasher, inputs: Uint8Array[]) =>
+  Uint8Array.from(hasher.poseidonHash(inputs.map((bytes) => new BN(bytes))));
+
+export function propertyTreeRoot(
+  hasher: PoseidonHasher,
+  properties: Properties[]
+): Uint8Array {
+  let depth = PROPERTY_TREE_DEPTH;
+  while (1 << depth < properties.length) {
+    depth += 1;
+  }
+  let level = properties.map((property) =>
+    hashToBn254FieldSizeBe(borsh.serialize(propertiesSchema, property))
+  );
+  while (level.length < 1 << depth) {
+    level.push(new Uint8Array(32));
+  }
+  for (let i = 0; i < depth; i++) {
+    const next: Uint8Array[] = [];
+    for (let j = 0; j < level.length; j += 2) {
+      next.push(poseidon(hasher, [level[j], level[j + 1]]));
+    }
+    level = next;
+  }
+  return level[0];
+}
+
+// Data hash of a `NodeV2` as decoded with `rawNodeSchema`
+export function hashNodeV2(hasher: PoseidonHasher, node: any): Uint8Array {
+  const { nodeData } = node;
+  const nodeDataHash = poseidon(hasher, [
+    propertyTreeRoot(hasher, decodeProperties(nodeData.propertiesBytes)),
+    hashToBn254FieldSizeBe(Uint8Array.from(nodeData.creatorsBytes)),
+    hashToBn254FieldSizeBe(Uint8Array.from(nodeData.encryptedPropertiesBytes)),
+  ]);
+  return poseidon(hasher, [
+    hashToBn254FieldSizeBe(Uint8Array.from([node.key])),
+    hashToBn254FieldSizeBe(Uint8Array.from(node.owner)),
+    hashToBn254FieldSizeBe(
+      borsh.serialize(updateAuthoritySchema, node.updateAuthority)
+    ),
+    hashToBn254FieldSizeBe(utf8(node.namespace)),
+    utf8(node.label),
+    nodeDataHash,
+    Uint8Array.from([node.isMutable ? 1 : 0]),
+    hashToBn254FieldSizeBe(borsh.serialize(tokenGateSchema, node.tokenGate)),
+  ]);
+}
+
+// Data hash of an `EdgeV2` as decoded with `rawEdgeSchema`
+export function hashEdgeV2(hasher: PoseidonHasher, edge: any): Uint8Array {
+  const { edgeData } = edge;
+  const counted = new Uint8Array(32);
+  counted[31] = edgeData.counted ? 1 : 0;
+  const edgeDataHash = poseidon(hasher, [
+    propertyTreeRoot(hasher, decodeProperties(edgeData.propertiesBytes)),
+    hashToBn254FieldSizeBe(
+      borsh.serialize(tokenGateSchema, edgeData.tokenGate)
+    ),
+    counted,
+    hashToBn254FieldSizeBe(
+      borsh.serialize(pubkeyOptionSchema, edgeData.counterparty)
+    ),
+    hashToBn254FieldSizeBe(
+      borsh.serialize(pubkeyOptionSchema, edgeData.callerProgram)
+    ),
+  ]);
+  return poseidon(hasher, [
+    hashToBn254FieldSizeBe(Uint8Array.from([edge.key])),
+    hashToBn254FieldSizeBe(utf8(edge.sourceNode)),
+    hashToBn254FieldSizeBe(utf8(edge.targetNode)),
+    hashToBn254FieldSizeBe(utf8(edge.edgeType)),
+    hashToBn254FieldSizeBe(utf8(edge.namespace)),
+    hashToBn254FieldSizeBe(Uint8Array.from([edge.direction])),
+    edgeDataHash,
+    borsh.serialize("u64", edge.weight),
+    hashToBn254FieldSizeBe(borsh.serialize(edgeValiditySchema, edge.validity)),
+    Uint8Array.from([edge.isMutable ? 1 : 0]),
+    hashToBn254FieldSizeBe(Uint8Array.from(edge.owner)),
+    hashToBn254FieldSizeBe(
+      borsh.serialize(updateAuthoritySchema, edge.updateAuthority)
+    ),
+  ]);
+}
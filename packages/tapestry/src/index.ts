@@ -3,7 +3,7 @@ import idl from "../target/idl/tapestry.json";
 // main program
 export type { Tapestry } from "../target/types/tapestry";
 export { idl };
-export const PROGRAM_ID = "GraphUyqhPmEAckWzi7zAvbvUTXf8kqX7JtuvdGYRDRh";
+export { PROGRAM_ID, AccountKey } from "./constants";
 export { getDelegateRoleFromNumber, getBaseDataStateFromNumber } from "./utils";
 export {
   assetSchemaV1,
@@ -14,9 +14,34 @@ export {
   edgeSchemaV1,
   nodeArgsSchema,
   edgeArgsSchema,
+  edgeValiditySchema,
   propertiesSchema,
   creatorSchema,
+  encryptedPropertySchema,
+  tokenGateSchema,
+  edgeDataSchema,
+  updateAuthoritySchema,
   nodeDataSchema,
   rawNodeSchema,
+  rawNodeSchemaV1,
   rawEdgeSchema,
+  rawEdgeSchemaV1,
+  rawPendingEdgeSchema,
+  rawNodeCounterSchema,
+  rawDecompressedNodeSchema,
+  rawIdentityLinkSchema,
 } from "./schemas";
+export type { PoseidonHasher, Properties } from "./hashing";
+export {
+  hashvToBn254FieldSizeBe,
+  hashToBn254FieldSizeBe,
+  getDerivedAccountSeed,
+  getLabelSeed,
+  getEdgePairSeed,
+  getExternalIdSeed,
+  getUndirectedEdgeSeed,
+  decodeProperties,
+  propertyTreeRoot,
+  hashNodeV2,
+  hashEdgeV2,
+} from "./hashing";
@@ -67,12 +67,90 @@ export const creatorSchema: borsh.Schema = {
   },
 };
 
+// Who may update a node or edge; `threshold` requires M of the N signers and
+// `group` defers to the update authority of the group node at that address
+export const updateAuthoritySchema: borsh.Schema = {
+  enum: [
+    { struct: { none: { struct: {} } } },
+    { struct: { address: { array: { type: "u8", len: 32 } } } },
+    {
+      struct: {
+        program: {
+          struct: {
+            programId: { array: { type: "u8", len: 32 } },
+            authority: { array: { type: "u8", len: 32 } },
+          },
+        },
+      },
+    },
+    {
+      struct: {
+        threshold: {
+          struct: {
+            threshold: "u8",
+            signers: { array: { type: { array: { type: "u8", len: 32 } } } },
+          },
+        },
+      },
+    },
+    { struct: { group: { array: { type: "u8", len: 32 } } } },
+  ],
+};
+
+// Holding a node requires for edges pointing at it
+export const tokenGateSchema: borsh.Schema = {
+  enum: [
+    { struct: { none: { struct: {} } } },
+    {
+      struct: {
+        mint: {
+          struct: {
+            mint: { array: { type: "u8", len: 32 } },
+            minAmount: "u64",
+          },
+        },
+      },
+    },
+    {
+      struct: {
+        collection: {
+          struct: {
+            collection: { array: { type: "u8", len: 32 } },
+            minAmount: "u64",
+          },
+        },
+      },
+    },
+  ],
+};
+
+// Value encrypted client-side to `recipient`, only the ciphertext goes on chain
+export const encryptedPropertySchema: borsh.Schema = {
+  struct: {
+    key: "string",
+    recipient: { array: { type: "u8", len: 32 } },
+    ephemeralPublicKey: { array: { type: "u8", len: 32 } },
+    nonce: { array: { type: "u8", len: 24 } },
+    ciphertext: { array: { type: "u8" } },
+  },
+};
+
 export const nodeArgsSchema: borsh.Schema = {
   struct: {
     label: "string",
     properties: { array: { type: propertiesSchema } },
     isMutable: "bool",
     creators: { array: { type: creatorSchema } },
+    namespace: "string",
+    encryptedProperties: { array: { type: encryptedPropertySchema } },
+    tokenGate: tokenGateSchema,
+  },
+};
+
+export const edgeValiditySchema: borsh.Schema = {
+  struct: {
+    validFrom: { option: "i64" },
+    validUntil: { option: "i64" },
   },
 };
 
@@ -80,8 +158,12 @@ export const edgeArgsSchema: borsh.Schema = {
   struct: {
     sourceNode: "string",
     targetNode: "string",
+    edgeType: "string",
     properties: { array: { type: propertiesSchema } },
     isMutable: "bool",
+    weight: "u64",
+    validity: edgeValiditySchema,
+    namespace: "string",
   },
 };
 
@@ -106,12 +188,7 @@ export const nodeSchemaV1: borsh.Schema = {
   struct: {
     key: accountKeySchema,
     owner: { array: { type: "u8", len: 32 } },
-    updateAuthority: {
-      enum: [
-        { struct: { none: { struct: {} } } },
-        { struct: { address: { array: { type: "u8", len: 32 } } } },
-      ],
-    },
+    updateAuthority: updateAuthoritySchema,
     label: "string",
     nodeData: nodeDataSchema,
     isMutable: "bool",
@@ -160,13 +237,30 @@ export const stakeRecordSchemaV1: borsh.Schema = {
 // Create a very simplified version of the nodeSchema that handles the binary format directly
 // Without using custom enum types that are causing issues
 export const rawNodeSchema: borsh.Schema = {
+  struct: {
+    key: "u8", // AccountKey (3 for NodeV2)
+    owner: { array: { type: "u8", len: 32 } },
+    updateAuthority: updateAuthoritySchema,
+    namespace: "string",
+    label: "string",
+    nodeData: {
+      struct: {
+        propertiesBytes: { array: { type: "u8" } },
+        creatorsBytes: { array: { type: "u8" } },
+        encryptedPropertiesBytes: { array: { type: "u8" } },
+      },
+    },
+    isMutable: "bool",
+    tokenGate: tokenGateSchema,
+  },
+};
+
+// Legacy node layout, still readable until migrated with `migrate_node_v1_to_v2`
+export const rawNodeSchemaV1: borsh.Schema = {
   struct: {
     key: "u8", // AccountKey is a simple u8
     owner: { array: { type: "u8", len: 32 } },
-    updateAuthorityTag: "u8", // Just the tag value (0 or 1)
-    // The update authority is serialized differently than a standard option
-    // It appears to be a raw value, not an optional field
-    updateAuthorityData: { array: { type: "u8", len: 32 } }, // This is always present, not optional
+    updateAuthority: updateAuthoritySchema,
     label: "string",
     nodeData: {
       struct: {
@@ -179,8 +273,39 @@ export const rawNodeSchema: borsh.Schema = {
   },
 };
 
+// Edge data of edges and pending edges
+export const edgeDataSchema: borsh.Schema = {
+  struct: {
+    propertiesBytes: { array: { type: "u8" } },
+    tokenGate: tokenGateSchema, // Gate of the target node checked at creation
+    counted: "bool", // Whether the edge was counted on its node counters
+    // Target owner who co-signed an undirected edge
+    counterparty: { option: { array: { type: "u8", len: 32 } } },
+    // Program that created the edge through create_edge_cpi
+    callerProgram: { option: { array: { type: "u8", len: 32 } } },
+  },
+};
+
 // Create a schema based on the observed buffer layout from our debugging
 export const rawEdgeSchema: borsh.Schema = {
+  struct: {
+    key: "u8", // AccountKey (4 for EdgeV2)
+    sourceNode: "string",
+    targetNode: "string",
+    edgeType: "string",
+    namespace: "string",
+    direction: "u8", // EdgeDirection (0 = directed, 1 = undirected)
+    edgeData: edgeDataSchema,
+    weight: "u64",
+    validity: edgeValiditySchema,
+    isMutable: "bool",
+    owner: { array: { type: "u8", len: 32 } },
+    updateAuthority: updateAuthoritySchema,
+  },
+};
+
+// Legacy edge layout, still readable until migrated with `migrate_edge_v1_to_v2`
+export const rawEdgeSchemaV1: borsh.Schema = {
   struct: {
     key: "u8", // AccountKey (1 for EdgeV1)
     sourceNode: "string", // Source node identifier
@@ -193,8 +318,61 @@ export const rawEdgeSchema: borsh.Schema = {
     },
     isMutable: "bool",
     owner: { array: { type: "u8", len: 32 } }, // Owner Pubkey
-    updateAuthorityTag: "u8", // UpdateAuthority enum tag
-    updateAuthorityData: { array: { type: "u8", len: 32 } }, // UpdateAuthority data
+    updateAuthority: updateAuthoritySchema,
     initializedPlugins: "u16",
   },
 };
+
+// Pending edge created by `request_edge`, awaiting acceptance by the target owner
+export const rawPendingEdgeSchema: borsh.Schema = {
+  struct: {
+    key: "u8", // AccountKey (2 for PendingEdgeV1)
+    sourceNode: "string",
+    targetNode: "string",
+    edgeType: "string",
+    namespace: "string",
+    edgeData: edgeDataSchema,
+    weight: "u64",
+    validity: edgeValiditySchema,
+    isMutable: "bool",
+    owner: { array: { type: "u8", len: 32 } },
+    targetOwner: { array: { type: "u8", len: 32 } },
+    updateAuthority: updateAuthoritySchema,
+  },
+};
+
+// Follower / following counter of a node for one edge type. Only edges created
+// with counters (`counted` in their edge data) are included.
+export const rawNodeCounterSchema: borsh.Schema = {
+  struct: {
+    key: "u8", // AccountKey (5 for NodeCounterV1)
+    node: "string",
+    edgeType: "string",
+    countedInEdges: "u64",
+    countedOutEdges: "u64",
+  },
+};
+
+// Placeholder left at a node's address while it lives in a `NodeAccount` PDA
+export const rawDecompressedNodeSchema: borsh.Schema = {
+  struct: {
+    key: "u8", // AccountKey (6 for DecompressedNodeV1)
+    nodeAccount: { array: { type: "u8", len: 32 } },
+  },
+};
+
+// Edge from a node to an external identity attested by the attestation
+// authority. `provider` is 0 for wallets, 1 for Twitter and 2 for ENS names.
+export const rawIdentityLinkSchema: borsh.Schema = {
+  struct: {
+    key: "u8", // AccountKey (7 for IdentityLinkV1)
+    node: "string",
+    identity: {
+      struct: {
+        provider: "u8",
+        externalIdHash: { array: { type: "u8", len: 32 } },
+      },
+    },
+    attestationAuthority: { array: { type: "u8", len: 32 } },
+  },
+};
@@ -0,0 +1,169 @@
+import { WasmFactory } from "@lightprotocol/hasher.rs";
+import { deriveAddress, deriveAddressSeed } from "@lightprotocol/stateless.js";
+import BN from "bn.js";
+import * as borsh from "borsh";
+//@ts-expect-error
+import { describe, it, expect } from "bun:test";
+import { PublicKey } from "@solana/web3.js";
+import vectors from "../programs/tapestry/tests/fixtures/hash_vectors.json";
+import {
+  AccountKey,
+  PROGRAM_ID,
+  PoseidonHasher,
+  decodeProperties,
+  getEdgePairSeed,
+  getExternalIdSeed,
+  getLabelSeed,
+  getUndirectedEdgeSeed,
+  hashEdgeV2,
+  hashNodeV2,
+  hashToBn254FieldSizeBe,
+  rawEdgeSchema,
+  rawEdgeSchemaV1,
+  rawNodeSchema,
+  rawNodeSchemaV1,
+  updateAuthoritySchema,
+} from "../src";
+
+// Same golden vectors as programs/tapestry/tests/hashing.rs, so the SDK and the
+// program cannot silently disagree on account seeds, addresses or hashes
+const toHex = (bytes: Uint8Array) => Buffer.from(bytes).toString("hex");
+const fieldHash = hashToBn254FieldSizeBe;
+const utf8 = (value: string) => Buffer.from(value, "utf8");
+const bool = (value: boolean) => Uint8Array.from([value ? 1 : 0]);
+const u16 = (value: number) => new BN(value).toArrayLike(Buffer, "le", 2);
+const bytesVec = (bytes: Uint8Array) =>
+  borsh.serialize({ array: { type: "u8" } }, bytes);
+
+// Poseidon inputs of `NodeV1`, in field order of the `LightHasher` derive
+const nodeV1Inputs = (node: any) => [
+  fieldHash(Uint8Array.from([node.key])),
+  fieldHash(Uint8Array.from(node.owner)),
+  fieldHash(borsh.serialize(updateAuthoritySchema, node.updateAuthority)),
+  utf8(node.label),
+  fieldHash(
+    Buffer.concat([
+      bytesVec(node.nodeData.propertiesBytes),
+      bytesVec(node.nodeData.creatorsBytes),
+    ])
+  ),
+  bool(node.isMutable),
+  u16(node.initializedPlugins),
+];
+
+// Poseidon inputs of `EdgeV1`, in field order of the `LightHasher` derive
+const edgeV1Inputs = (edge: any) => [
+  fieldHash(Uint8Array.from([edge.key])),
+  fieldHash(utf8(edge.sourceNode)),
+  fieldHash(utf8(edge.targetNode)),
+  fieldHash(bytesVec(edge.edgeData.propertiesBytes)),
+  bool(edge.isMutable),
+  fieldHash(Uint8Array.from(edge.owner)),
+  fieldHash(borsh.serialize(updateAuthoritySchema, edge.updateAuthority)),
+  u16(edge.initializedPlugins),
+];
+
+const legacyHash =
+  (inputs: (account: any) => Uint8Array[]) =>
+  (hasher: PoseidonHasher, account: any) =>
+    hasher.poseidonHash(inputs(account).map((bytes) => new BN(bytes)));
+
+const LAYOUTS: Record<
+  string,
+  {
+    schema: borsh.Schema;
+    hash: (hasher: PoseidonHasher, account: any) => Uint8Array;
+  }
+> = {
+  NodeV1: { schema: rawNodeSchemaV1, hash: legacyHash(nodeV1Inputs) },
+  EdgeV1: { schema: rawEdgeSchemaV1, hash: legacyHash(edgeV1Inputs) },
+  NodeV2: { schema: rawNodeSchema, hash: hashNodeV2 },
+  EdgeV2: { schema: rawEdgeSchema, hash: hashEdgeV2 },
+};
+
+const derivedSeed = (vector: (typeof vectors.derived_seeds)[number]) => {
+  const [first, second, third] = vector.inputs;
+  switch (vector.kind) {
+    case "label":
+      return getLabelSeed(vector.namespace, first);
+    case "edge_pair":
+      return getEdgePairSeed(vector.namespace, first, second, third);
+    case "external_id":
+      return getExternalIdSeed(
+        AccountKey[vector.account_key as keyof typeof AccountKey],
+        vector.namespace,
+        first
+      );
+    case "undirected_edge":
+      return getUndirectedEdgeSeed(vector.namespace, first, second, third);
+  }
+  throw new Error(`unknown derived seed ${vector.kind}`);
+};
+
+describe("hash vectors", () => {
+  it("derives the same account seeds and addresses as the program", () => {
+    expect(vectors.program_id).toBe(PROGRAM_ID);
+
+    for (const vector of vectors.account_seeds) {
+      const seed = deriveAddressSeed(
+        [
+          Uint8Array.from([
+            AccountKey[vector.account_key as keyof typeof AccountKey],
+          ]),
+          Buffer.from(vector.asset_id, "hex"),
+        ],
+        new PublicKey(PROGRAM_ID)
+      );
+      expect(toHex(seed)).toBe(vector.seed);
+
+      const address = deriveAddress(
+        seed,
+        new PublicKey(vectors.address_merkle_tree)
+      );
+      expect(toHex(address.toBytes())).toBe(vector.address);
+    }
+  });
+
+  it("derives the same label, pair and external id seeds", () => {
+    for (const vector of vectors.derived_seeds) {
+      const seed = derivedSeed(vector);
+      expect(toHex(seed)).toBe(vector.seed);
+
+      const address = deriveAddress(
+        seed,
+        new PublicKey(vectors.address_merkle_tree)
+      );
+      expect(toHex(address.toBytes())).toBe(vector.address);
+    }
+  });
+
+  it("decodes accounts and hashes them like the program", async () => {
+    const hasher = await WasmFactory.getInstance();
+
+    for (const vector of vectors.account_hashes) {
+      const layout = LAYOUTS[vector.account_key];
+      const data = Buffer.from(vector.data, "hex");
+      const account = borsh.deserialize(layout.schema, data);
+      expect(toHex(borsh.serialize(layout.schema, account))).toBe(vector.data);
+      expect(toHex(layout.hash(hasher, account))).toBe(vector.hash);
+    }
+  });
+
+  it("rejects properties bytes the program can't decode", async () => {
+    const hasher = await WasmFactory.getInstance();
+    const vector = vectors.account_hashes.find(
+      (vector) => vector.account_key === "EdgeV2"
+    )!;
+    const edge: any = borsh.deserialize(
+      rawEdgeSchema,
+      Buffer.from(vector.data, "hex")
+    );
+    const propertiesBytes = Uint8Array.from(edge.edgeData.propertiesBytes);
+    expect(decodeProperties(propertiesBytes)).toEqual([]);
+
+    // Trailing bytes are ignored by a lenient decoder but fail the program's
+    // `try_from_slice`, so they must not hash to a valid account
+    edge.edgeData.propertiesBytes = [...propertiesBytes, 0];
+    expect(() => hashEdgeV2(hasher, edge)).toThrow();
+  });
+});
@@ -0,0 +1,46 @@
+import * as borsh from "borsh";
+//@ts-expect-error
+import { describe, it, expect } from "bun:test";
+import vectors from "../programs/tapestry/tests/fixtures/hash_vectors.json";
+import { nodeSchemaV1, rawNodeSchema, updateAuthoritySchema } from "../src";
+
+const pubkey = (byte: number) => new Array(32).fill(byte);
+
+const threshold = {
+  threshold: { threshold: 2, signers: [pubkey(1), pubkey(2), pubkey(3)] },
+};
+
+describe("schemas", () => {
+  it("decodes a node with a threshold update authority", () => {
+    const vector = vectors.account_hashes.find(
+      (vector) =>
+        vector.account_key === "NodeV2" && vector.data.slice(66, 68) === "03"
+    )!;
+    const node: any = borsh.deserialize(
+      rawNodeSchema,
+      Buffer.from(vector.data, "hex")
+    );
+    expect(node.updateAuthority.threshold.threshold).toBe(2);
+    expect(node.updateAuthority.threshold.signers).toHaveLength(2);
+    expect(node.label).toBe("bob");
+  });
+
+  it("encodes update authorities of nodeSchemaV1 like the program", () => {
+    const node = {
+      key: { nodeV1: {} },
+      owner: pubkey(7),
+      updateAuthority: threshold,
+      label: "alice",
+      nodeData: { propertiesBytes: [0, 0, 0, 0], creatorsBytes: [0, 0, 0, 0] },
+      isMutable: true,
+      initializedPlugins: 0,
+    };
+    const data = borsh.serialize(nodeSchemaV1, node);
+    const updateAuthority = borsh.serialize(updateAuthoritySchema, threshold);
+    // The enum key is a single byte, followed by the owner
+    expect(Buffer.from(data.slice(33, 33 + updateAuthority.length))).toEqual(
+      Buffer.from(updateAuthority)
+    );
+    expect(borsh.deserialize(nodeSchemaV1, data)).toEqual(node);
+  });
+});
@@ -28,8 +28,8 @@ import {
   PROGRAM_ID,
   propertiesSchema,
   creatorSchema,
-  rawNodeSchema as NodeSchemaV1,
-  rawEdgeSchema,
+  rawNodeSchemaV1 as NodeSchemaV1,
+  rawEdgeSchemaV1 as rawEdgeSchema,
 } from "../src";
 
 // Define mainnet RPC endpoint
@@ -158,6 +158,9 @@ describe("tapestry", () => {
           share: 100,
         },
       ],
+      namespace: "test",
+      encryptedProperties: [],
+      tokenGate: { none: {} },
     };
 
     const {
@@ -176,7 +179,7 @@ describe("tapestry", () => {
           c: proof.compressedProof.c,
         },
         newAddressParamsPacked[0].addressMerkleTreeRootIndex,
-        Array.from(randomBytes),
+        { random: { 0: Array.from(randomBytes) } },
         nodeArgs
       )
       .accounts({
@@ -299,6 +302,9 @@ describe("tapestry", () => {
           share: 100,
         },
       ],
+      namespace: "test",
+      encryptedProperties: [],
+      tokenGate: { none: {} },
     };
 
     const {
@@ -317,7 +323,7 @@ describe("tapestry", () => {
           c: proof.compressedProof.c,
         },
         newAddressParamsPacked[0].addressMerkleTreeRootIndex,
-        Array.from(randomBytes),
+        { random: { 0: Array.from(randomBytes) } },
         nodeArgs
       )
       .accounts({
@@ -422,6 +428,7 @@ describe("tapestry", () => {
     const edgeArgs = {
       sourceNode: "node-1", // Use the previously created node as source
       targetNode: "node-1", // For testing, we'll use the same node as target
+      edgeType: "follow",
       properties: [
         {
           key: "description",
@@ -433,6 +440,9 @@ describe("tapestry", () => {
         },
       ],
       isMutable: true,
+      weight: new anchor.BN(10),
+      validity: { validFrom: null, validUntil: null },
+      namespace: "test",
     };
 
     const {
@@ -451,13 +461,17 @@ describe("tapestry", () => {
           c: proof.compressedProof.c,
         },
         newAddressParamsPacked[0].addressMerkleTreeRootIndex,
-        Array.from(randomBytes),
-        edgeArgs
+        { random: { 0: Array.from(randomBytes) } },
+        edgeArgs,
+        null,
+        null
       )
       .accounts({
         payer: NAME_KEYPAIR.publicKey,
         updateAuthority: NAME_KEYPAIR.publicKey,
         owner: OWNER_KEYPAIR.publicKey,
+        gateTokenAccount: null,
+        gateMetadata: null,
         cpiAuthorityPda: PublicKey.findProgramAddressSync(
           [Buffer.from("cpi_authority")],
           program.programId
@@ -562,6 +576,7 @@ describe("tapestry", () => {
     const edgeArgs = {
       sourceNode: "node-1", // First node
       targetNode: "node-2", // Second node
+      edgeType: "follow",
       properties: [
         {
           key: "timestamp",
@@ -577,6 +592,9 @@ describe("tapestry", () => {
         },
       ],
       isMutable: true,
+      weight: new anchor.BN(10),
+      validity: { validFrom: null, validUntil: null },
+      namespace: "test",
     };
 
     const {
@@ -595,13 +613,17 @@ describe("tapestry", () => {
           c: proof.compressedProof.c,
         },
         newAddressParamsPacked[0].addressMerkleTreeRootIndex,
-        Array.from(randomBytes),
-        edgeArgs
+        { random: { 0: Array.from(randomBytes) } },
+        edgeArgs,
+        null,
+        null
       )
       .accounts({
         payer: NAME_KEYPAIR.publicKey,
         updateAuthority: NAME_KEYPAIR.publicKey,
         owner: OWNER_KEYPAIR.publicKey,
+        gateTokenAccount: null,
+        gateMetadata: null,
         cpiAuthorityPda: PublicKey.findProgramAddressSync(
           [Buffer.from("cpi_authority")],
           program.programId
@@ -668,8 +690,8 @@ describe("tapestry", () => {
       for (const item of accounts) {
         const data = Buffer.from(item.data.data);
 
-        // Check if it's an EdgeV1
-        if (data[0] === 1) {
+        // Check if it's an EdgeV2
+        if (data[0] === 4) {
           try {
             const edge = borsh.deserialize(rawEdgeSchema, data) as any;
 
@@ -800,7 +822,7 @@ describe("tapestry", () => {
 
     const decodedNode = borsh.deserialize(NodeSchemaV1, node.data.data) as any;
 
-    expect(decodedNode.key).toBe(0);
+    expect(decodedNode.key).toBe(3);
     expect(new PublicKey(decodedNode.owner).toBase58()).toBe(
       OWNER_KEYPAIR.publicKey.toBase58()
     );
@@ -845,9 +867,13 @@ describe("tapestry", () => {
     const decodedEdge = borsh.deserialize(rawEdgeSchema, buffer) as any;
 
     // Validate basic fields
-    expect(decodedEdge.key).toBe(1); // EdgeV1 key
+    expect(decodedEdge.key).toBe(4); // EdgeV2 key
+    expect(decodedEdge.namespace).toBe("test");
     expect(decodedEdge.sourceNode).toBe("node-1");
     expect(decodedEdge.targetNode).toBe("node-2");
+    expect(decodedEdge.edgeType).toBe("follow");
+    expect(decodedEdge.direction).toBe(0); // Directed
+    expect(decodedEdge.weight.toString()).toBe("10");
     expect(new PublicKey(decodedEdge.owner).toBase58()).toBe(
       OWNER_KEYPAIR.publicKey.toBase58()
     );
@@ -881,9 +907,13 @@ describe("tapestry", () => {
     const decodedEdge = borsh.deserialize(rawEdgeSchema, buffer) as any;
 
     // Validate basic fields
-    expect(decodedEdge.key).toBe(1); // EdgeV1 key
+    expect(decodedEdge.key).toBe(4); // EdgeV2 key
+    expect(decodedEdge.namespace).toBe("test");
     expect(decodedEdge.sourceNode).toBe("node-1");
     expect(decodedEdge.targetNode).toBe("node-2");
+    expect(decodedEdge.edgeType).toBe("follow");
+    expect(decodedEdge.direction).toBe(0); // Directed
+    expect(decodedEdge.weight.toString()).toBe("10");
     expect(new PublicKey(decodedEdge.owner).toBase58()).toBe(
       OWNER_KEYPAIR.publicKey.toBase58()
     );
@@ -989,6 +1019,9 @@ describe("tapestry", () => {
             share: 100,
           },
         ],
+        namespace: "test",
+        encryptedProperties: [],
+        tokenGate: { none: {} },
       };
 
       const {
@@ -1007,7 +1040,7 @@ describe("tapestry", () => {
             c: proof.compressedProof.c,
           },
           newAddressParamsPacked[0].addressMerkleTreeRootIndex,
-          Array.from(randomBytes),
+          { random: { 0: Array.from(randomBytes) } },
           nodeArgs
         )
         .accounts({
@@ -1112,6 +1145,7 @@ describe("tapestry", () => {
       const edgeArgs = {
         sourceNode: `node-${i + 1}`,
         targetNode: `node-${((i + 1) % 5) + 1}`,
+        edgeType: "follow",
         properties: [
           {
             key: "timestamp",
@@ -1127,6 +1161,9 @@ describe("tapestry", () => {
           },
         ],
         isMutable: true,
+        weight: new anchor.BN(i + 1),
+        validity: { validFrom: null, validUntil: null },
+        namespace: "test",
       };
 
       const {
@@ -1145,13 +1182,17 @@ describe("tapestry", () => {
             c: proof.compressedProof.c,
           },
           newAddressParamsPacked[0].addressMerkleTreeRootIndex,
-          Array.from(randomBytes),
-          edgeArgs
+          { random: { 0: Array.from(randomBytes) } },
+          edgeArgs,
+          null,
+          null
         )
         .accounts({
           payer: NAME_KEYPAIR.publicKey,
           updateAuthority: NAME_KEYPAIR.publicKey,
           owner: OWNER_KEYPAIR.publicKey,
+          gateTokenAccount: null,
+          gateMetadata: null,
           cpiAuthorityPda: PublicKey.findProgramAddressSync(
             [Buffer.from("cpi_authority")],
             program.programId
@@ -1214,7 +1255,7 @@ describe("tapestry", () => {
         node.data.data
       ) as any;
 
-      expect(decodedNode.key).toBe(0);
+      expect(decodedNode.key).toBe(3);
       expect(decodedNode.label).toBe(`node-${i + 1}`);
       expect(decodedNode.isMutable).toBe(true);
 
@@ -1243,7 +1284,7 @@ describe("tapestry", () => {
         edge.data.data
       ) as any;
 
-      expect(decodedEdge.key).toBe(1);
+      expect(decodedEdge.key).toBe(4);
       expect(decodedEdge.sourceNode).toBe(`node-${i + 1}`);
       expect(decodedEdge.targetNode).toBe(`node-${((i + 1) % 5) + 1}`);
       expect(decodedEdge.isMutable).toBe(true);
@@ -1408,6 +1449,9 @@ describe("tapestry", () => {
           share: 100,
         },
       ],
+      namespace: "test",
+      encryptedProperties: [],
+      tokenGate: { none: {} },
     };
 
     const {
@@ -1426,7 +1470,7 @@ describe("tapestry", () => {
           c: proof.compressedProof.c,
         },
         newAddressParamsPacked[0].addressMerkleTreeRootIndex,
-        Array.from(randomBytes),
+        { random: { 0: Array.from(randomBytes) } },
         nodeArgs
       )
       .accounts({
@@ -1507,7 +1551,7 @@ describe("tapestry", () => {
     const buffer = Buffer.from(openedAccount.account.data.data);
     const decodedNode = borsh.deserialize(NodeSchemaV1, buffer) as any;
     expect(decodedNode.label).toBe("compression-test-node");
-    expect(decodedNode.key).toBe(0); // NodeV1 key
+    expect(decodedNode.key).toBe(3); // NodeV2 key
     expect(new PublicKey(decodedNode.owner).toBase58()).toBe(
       OWNER_KEYPAIR.publicKey.toBase58()
     );